@@ -0,0 +1,112 @@
+// © Steven Kalt
+// SPDX-License-Identifier: APACHE-2.0
+
+//! Baseline timings for linting large messages. Run with
+//! `cargo bench -p base --features tracing`; see `./README.md`.
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use base::{
+    document::{
+        linting::{utils::construct_default_lint_tests_map, LintConfig, LintFn},
+        GitCommitDocument,
+    },
+    syntax_token_scopes::handle_all_tokens,
+};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+struct BenchConfig {
+    tests: HashMap<&'static str, Arc<LintFn<'static>>>,
+}
+impl LintConfig for BenchConfig {
+    fn worktree_root(&self) -> Option<PathBuf> {
+        None
+    }
+    fn get_test(&self, code: &str) -> Option<&Arc<LintFn<'_>>> {
+        self.tests.get(code)
+    }
+}
+
+/// a subject, a 1000-line body, trailers, and the usual `git commit` comments
+fn large_message() -> String {
+    let mut message = String::from("feat(bench): lint a very large message\n\n");
+    for i in 0..1000 {
+        message.push_str(&format!(
+            "line {i} of the body, which is long enough to resemble real prose in a commit\n"
+        ));
+    }
+    message.push_str("\nRefs: #123\nSigned-off-by: A U Thor <author@example.com>\n");
+    message.push_str("# Please enter the commit message for your changes.\n# On branch main\n");
+    message
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let message = large_message();
+    c.bench_function("parse 1000-line body", |b| {
+        b.iter(|| GitCommitDocument::new().with_text(black_box(message.clone())))
+    });
+}
+
+fn bench_lint(c: &mut Criterion) {
+    let config = BenchConfig {
+        tests: construct_default_lint_tests_map(50),
+    };
+    let doc = GitCommitDocument::new().with_text(large_message());
+    c.bench_function("default lints", |b| b.iter(|| config.lint(black_box(&doc))));
+}
+
+fn bench_semantic_tokens(c: &mut Criterion) {
+    let doc = GitCommitDocument::new().with_text(large_message());
+    let capabilities = lsp_types::ClientCapabilities::default();
+    let params = lsp_types::SemanticTokensParams {
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+        text_document: lsp_types::TextDocumentIdentifier {
+            uri: "file:///tmp/COMMIT_EDITMSG".parse().unwrap(),
+        },
+    };
+    c.bench_function("semantic tokens", |b| {
+        b.iter(|| handle_all_tokens(&capabilities, black_box(&doc), params.clone(), &[]).unwrap())
+    });
+}
+
+fn bench_incremental_edits(c: &mut Criterion) {
+    // type 100 characters into the middle of the body, one keystroke at a time
+    let edits: Vec<_> = (0..100u32)
+        .map(|i| lsp_types::TextDocumentContentChangeEvent {
+            range: Some(lsp_types::Range {
+                start: lsp_types::Position {
+                    line: 500,
+                    character: i,
+                },
+                end: lsp_types::Position {
+                    line: 500,
+                    character: i,
+                },
+            }),
+            range_length: None,
+            text: "x".into(),
+        })
+        .collect();
+    let message = large_message();
+    c.bench_function("100 incremental edits", |b| {
+        b.iter_batched(
+            || GitCommitDocument::new().with_text(message.clone()),
+            |mut doc| {
+                for edit in &edits {
+                    doc.edit(std::slice::from_ref(edit));
+                }
+                doc
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_parse,
+    bench_lint,
+    bench_semantic_tokens,
+    bench_incremental_edits
+);
+criterion_main!(benches);
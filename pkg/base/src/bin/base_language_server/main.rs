@@ -6,7 +6,14 @@ use std::{collections::HashMap, path::PathBuf, sync::Arc};
 #[cfg(feature = "tracing")]
 use base::config::ENV_PREFIX;
 
-use base::{cli::cli, document::linting::utils::construct_default_lint_tests_map};
+use base::{
+    cli::cli,
+    config::{ClientSettings, EnvLintOverrides},
+    document::linting::{
+        default::{ENABLED_LINTS, LINT_SEVERITY, MAX_HEADER_LINE_LENGTH},
+        utils::construct_default_lint_tests_map,
+    },
+};
 
 pub struct DefaultConfigStore(DefaultConfig);
 impl DefaultConfigStore {
@@ -21,6 +28,7 @@ impl Default for DefaultConfigStore {
     }
 }
 
+#[allow(clippy::arc_with_non_send_sync)] // configs never leave the server's thread
 impl base::config::ConfigStore for DefaultConfigStore {
     /// always returns a clone of the same DefaultConfig for each worktree_root
     fn get(
@@ -31,19 +39,42 @@ impl base::config::ConfigStore for DefaultConfigStore {
         cfg.worktree_root = worktree_root;
         Ok(Arc::new(cfg))
     }
+    fn set_settings(&mut self, settings: ClientSettings) {
+        self.0 = DefaultConfig::with_settings(&settings);
+    }
 }
 
 #[derive(Clone)]
 pub struct DefaultConfig {
     worktree_root: Option<PathBuf>,
     tests: HashMap<&'static str, Arc<base::document::linting::LintFn<'static>>>,
+    enabled_lints: Vec<String>,
+    max_subject_length: u16,
+    /// overrides `LINT_SEVERITY`
+    severity: HashMap<String, lsp_types::DiagnosticSeverity>,
 }
 
 impl DefaultConfig {
     pub fn new() -> Self {
+        Self::with_settings(&ClientSettings::default())
+    }
+    /// the defaults, overridden by the client's settings, overridden by the environment
+    pub fn with_settings(settings: &ClientSettings) -> Self {
+        let mut enabled_lints = settings
+            .enabled_lints
+            .clone()
+            .unwrap_or_else(|| ENABLED_LINTS.iter().map(|code| code.to_string()).collect());
+        let mut severity = HashMap::new();
+        EnvLintOverrides::from_env().apply(&mut enabled_lints, &mut severity);
+        let max_subject_length = settings
+            .max_subject_length
+            .unwrap_or(MAX_HEADER_LINE_LENGTH as u16);
         DefaultConfig {
             worktree_root: None,
-            tests: construct_default_lint_tests_map(50),
+            tests: construct_default_lint_tests_map(max_subject_length),
+            enabled_lints,
+            max_subject_length,
+            severity,
         }
     }
 }
@@ -58,11 +89,25 @@ impl base::document::linting::LintConfig for DefaultConfig {
     fn worktree_root(&self) -> Option<PathBuf> {
         self.worktree_root.clone()
     }
-    fn get_test(&self, code: &str) -> Option<&Arc<base::document::linting::LintFn>> {
+    fn enabled_lint_codes(&self) -> Vec<&str> {
+        self.enabled_lints.iter().map(String::as_str).collect()
+    }
+    fn lint_severity(&self, lint_code: &str) -> &lsp_types::DiagnosticSeverity {
+        self.severity.get(lint_code).unwrap_or_else(|| {
+            LINT_SEVERITY
+                .get(lint_code)
+                .unwrap_or(&lsp_types::DiagnosticSeverity::WARNING)
+        })
+    }
+    fn get_test(&self, code: &str) -> Option<&Arc<base::document::linting::LintFn<'_>>> {
         self.tests.get(code)
     }
 }
-impl base::config::Config for DefaultConfig {}
+impl base::config::Config for DefaultConfig {
+    fn max_subject_length(&self) -> u16 {
+        self.max_subject_length
+    }
+}
 
 fn main() -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
     cli(
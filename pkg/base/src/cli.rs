@@ -1,13 +1,13 @@
 // © Steven Kalt
 // SPDX-License-Identifier: APACHE-2.0
-use std::io::IsTerminal;
-use std::path::PathBuf;
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use crate::{
-    config::{Config, ConfigStore},
+    config::{with_profile, Config, ConfigStore},
     document::GitCommitDocument,
-    git::git,
+    git::{git, merge_base},
 };
 #[cfg(feature = "tracing")]
 use clap::{Arg, ArgAction, Command};
@@ -17,6 +17,9 @@ use tracing_subscriber::{self, prelude::*, util::SubscriberInitExt};
 #[cfg(feature = "telemetry")]
 const SENTRY_DSN: Option<&'static str> = std::option_env!("SENTRY_DSN");
 
+/// the version of `check --porcelain`'s record format, bumped only on breaking changes
+pub const PORCELAIN_VERSION: &str = "v1";
+
 // see https://doc.rust-lang.org/cargo/reference/environment-variables.html
 /// the name of the bin or crate that is getting compiled
 const PKG_NAME: &str = env!("CARGO_PKG_NAME");
@@ -28,70 +31,422 @@ pub fn serve<Cfg: ConfigStore>(
     sub_matches: &clap::ArgMatches,
     capabilities: &lsp_types::ServerCapabilities,
 ) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
-    let mut server = if sub_matches.get_flag("stdio") {
+    let mut features = crate::config::Features::default();
+    for name in sub_matches
+        .get_many::<String>("disable-feature")
+        .unwrap_or_default()
+    {
+        features.set(name, false)?;
+    }
+    let server = if sub_matches.get_flag("stdio") {
         crate::server::Server::from_stdio(cfg)
     } else if sub_matches.get_flag("tcp") {
-        crate::server::Server::from_tcp(cfg, 9999)
+        let port = *sub_matches.get_one::<u16>("port").unwrap();
+        crate::server::Server::from_tcp(cfg, port)?
     } else {
         unreachable!()
     };
+    let mut server = server.with_features(features);
     server.init(capabilities)?.serve()?;
     log_info!("language server terminated");
 
     Ok(())
 }
 
+/// lint the commit message in `file`
+fn lint_file(
+    cfg: &Arc<dyn Config>,
+    file: &Path,
+) -> Result<Vec<lsp_types::Diagnostic>, Box<dyn std::error::Error + Sync + Send>> {
+    lint_text(cfg, read_file(file)?)
+}
+
+/// read the commit message in `file`
+fn read_file(file: &Path) -> Result<String, Box<dyn std::error::Error + Sync + Send>> {
+    if !file.exists() {
+        return Err(format!("{} does not exist", file.display()).into());
+    }
+    if !file.is_file() {
+        return Err(format!("{} is not a file", file.display()).into());
+    }
+    Ok(std::fs::read_to_string(file)?)
+}
+
+/// parse a commit message's `text` per `cfg`
+fn parse_text(
+    cfg: &Arc<dyn Config>,
+    text: String,
+) -> Result<GitCommitDocument, Box<dyn std::error::Error + Sync + Send>> {
+    Ok(GitCommitDocument::try_new()?
+        .with_parse_timeout(cfg.parse_timeout_micros())
+        .with_comments_are_content(cfg.comments_are_content())
+        .with_text(text))
+}
+
+/// lint a commit message's `text`
+fn lint_text(
+    cfg: &Arc<dyn Config>,
+    text: String,
+) -> Result<Vec<lsp_types::Diagnostic>, Box<dyn std::error::Error + Sync + Send>> {
+    let doc = parse_text(cfg, text)?;
+    Ok(with_profile(cfg.clone(), &doc).lint(&doc))
+}
+
+/// format a commit message's `text`, returning the result and how many edits changed it
+fn fix_text(
+    cfg: &Arc<dyn Config>,
+    text: String,
+) -> Result<(String, usize), Box<dyn std::error::Error + Sync + Send>> {
+    let doc = parse_text(cfg, text)?;
+    let edits = doc.format(&with_profile(cfg.clone(), &doc).format_options());
+    Ok(crate::document::apply_edits(&doc.code.to_string(), &edits))
+}
+
+/// e.g. `applied 1 fix` or `applied 2 fixes`
+fn fix_summary(count: usize) -> String {
+    format!("applied {count} fix{}", if count == 1 { "" } else { "es" })
+}
+
+/// how `check` and `lint` print diagnostics
+#[derive(Debug, Clone, Copy)]
+enum OutputFormat {
+    Tab,
+    Gcc,
+    Porcelain,
+    Json,
+    Sarif,
+    Checkstyle,
+}
+
+/// a diagnostic and the file or commit it was found in
+type Finding = (String, lsp_types::Diagnostic);
+
+fn code_of(d: &lsp_types::Diagnostic) -> &str {
+    match d.code.as_ref().unwrap() {
+        lsp_types::NumberOrString::String(s) => s,
+        _ => panic!("expected code to be a string"),
+    }
+}
+
+/// `error`, `warning`, `info`, or `hint`
+fn severity_name(d: &lsp_types::Diagnostic) -> &'static str {
+    match d.severity.unwrap() {
+        lsp_types::DiagnosticSeverity::ERROR => "error",
+        lsp_types::DiagnosticSeverity::WARNING => "warning",
+        lsp_types::DiagnosticSeverity::INFORMATION => "info",
+        _ => "hint",
+    }
+}
+
+/// escape `text` for use in an XML attribute
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+impl OutputFormat {
+    fn from_matches(sub_matches: &clap::ArgMatches) -> Self {
+        if sub_matches.get_flag("porcelain") {
+            return OutputFormat::Porcelain;
+        }
+        match sub_matches.get_one::<String>("format").map(|f| f.as_str()) {
+            Some("gcc") => OutputFormat::Gcc,
+            Some("json") => OutputFormat::Json,
+            Some("sarif") => OutputFormat::Sarif,
+            Some("checkstyle") => OutputFormat::Checkstyle,
+            _ => OutputFormat::Tab,
+        }
+    }
+
+    /// print every finding, in order
+    fn render(self, findings: &[Finding]) -> String {
+        match self {
+            OutputFormat::Json => Self::render_json(findings),
+            OutputFormat::Sarif => Self::render_sarif(findings),
+            OutputFormat::Checkstyle => Self::render_checkstyle(findings),
+            OutputFormat::Tab | OutputFormat::Gcc | OutputFormat::Porcelain => {
+                let mut result = String::new();
+                for (group, d) in findings {
+                    self.write_line(&mut result, group, d);
+                }
+                result
+            }
+        }
+    }
+
+    /// append one line describing `d` to `result`
+    fn write_line(self, result: &mut String, group: &str, d: &lsp_types::Diagnostic) {
+        let code = code_of(d);
+        let start_line = d.range.start.line + 1;
+        let start_column = d.range.start.character + 1;
+        match self {
+            OutputFormat::Porcelain => {
+                // keep each record on one line with exactly 7 fields
+                let escape = |field: &str| {
+                    field
+                        .replace('\\', "\\\\")
+                        .replace('\t', "\\t")
+                        .replace('\n', "\\n")
+                };
+                result.push_str(&format!(
+                    "{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                    PORCELAIN_VERSION,
+                    escape(group),
+                    start_line,
+                    start_column,
+                    severity_name(d),
+                    code,
+                    escape(&d.message)
+                ));
+            }
+            OutputFormat::Gcc => {
+                // see https://www.gnu.org/prep/standards/html_node/Errors.html
+                let severity = match d.severity.unwrap() {
+                    lsp_types::DiagnosticSeverity::ERROR => "error",
+                    lsp_types::DiagnosticSeverity::WARNING => "warning",
+                    _ => "note",
+                };
+                result.push_str(&format!(
+                    "{}:{}:{}: {}: {} [{}]\n",
+                    group, start_line, start_column, severity, d.message, code
+                ));
+            }
+            _ => {
+                result.push_str(&format!(
+                    "{}:{}:{}\t{:?}\t{}\t{}\n",
+                    group,
+                    start_line,
+                    start_column,
+                    d.severity.unwrap(),
+                    code,
+                    d.message
+                ));
+            }
+        }
+    }
+
+    /// an array of findings with 1-indexed positions
+    fn render_json(findings: &[Finding]) -> String {
+        let findings: Vec<serde_json::Value> = findings
+            .iter()
+            .map(|(group, d)| {
+                serde_json::json!({
+                    "group": group,
+                    "line": d.range.start.line + 1,
+                    "column": d.range.start.character + 1,
+                    "endLine": d.range.end.line + 1,
+                    "endColumn": d.range.end.character + 1,
+                    "severity": severity_name(d),
+                    "code": code_of(d),
+                    "message": d.message,
+                })
+            })
+            .collect();
+        serde_json::to_string_pretty(&findings).unwrap()
+    }
+
+    /// a SARIF 2.1.0 log with one rule per diagnostic code;
+    /// see https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+    fn render_sarif(findings: &[Finding]) -> String {
+        let mut rules: Vec<&str> = vec![];
+        for (_, d) in findings {
+            if !rules.contains(&code_of(d)) {
+                rules.push(code_of(d));
+            }
+        }
+        let results: Vec<serde_json::Value> = findings
+            .iter()
+            .map(|(group, d)| {
+                let level = match d.severity.unwrap() {
+                    lsp_types::DiagnosticSeverity::ERROR => "error",
+                    lsp_types::DiagnosticSeverity::WARNING => "warning",
+                    _ => "note",
+                };
+                serde_json::json!({
+                    "ruleId": code_of(d),
+                    "level": level,
+                    "message": { "text": d.message },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": group },
+                            "region": {
+                                "startLine": d.range.start.line + 1,
+                                "startColumn": d.range.start.character + 1,
+                                "endLine": d.range.end.line + 1,
+                                "endColumn": d.range.end.character + 1,
+                            },
+                        },
+                    }],
+                })
+            })
+            .collect();
+        let log = serde_json::json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": crate::document::linting::default::ID,
+                        "rules": rules.iter().map(|id| serde_json::json!({ "id": id })).collect::<Vec<_>>(),
+                    },
+                },
+                "results": results,
+            }],
+        });
+        serde_json::to_string_pretty(&log).unwrap()
+    }
+
+    /// a Checkstyle XML report with a `<file>` per group, in order of appearance
+    fn render_checkstyle(findings: &[Finding]) -> String {
+        let mut groups: Vec<&str> = vec![];
+        for (group, _) in findings {
+            if !groups.contains(&group.as_str()) {
+                groups.push(group);
+            }
+        }
+        let mut result = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n",
+        );
+        for group in groups {
+            result.push_str(&format!("  <file name=\"{}\">\n", escape_xml(group)));
+            for (_, d) in findings.iter().filter(|(g, _)| g == group) {
+                let severity = match severity_name(d) {
+                    "hint" => "info",
+                    severity => severity,
+                };
+                result.push_str(&format!(
+                    "    <error line=\"{}\" column=\"{}\" severity=\"{}\" message=\"{}\" source=\"{}.{}\"/>\n",
+                    d.range.start.line + 1,
+                    d.range.start.character + 1,
+                    severity,
+                    escape_xml(&d.message),
+                    crate::document::linting::default::ID,
+                    escape_xml(code_of(d)),
+                ));
+            }
+            result.push_str("  </file>\n");
+        }
+        result.push_str("</checkstyle>");
+        result
+    }
+}
+
+/// the diagnostic to print; with `--suggestions-only`, the exit code still follows the original
+/// severities
+fn as_printed(d: &lsp_types::Diagnostic, suggestions_only: bool) -> lsp_types::Diagnostic {
+    let mut d = d.clone();
+    if suggestions_only {
+        crate::document::linting::utils::as_suggestion(&mut d);
+    }
+    d
+}
+
+/// count `(errors, warnings)` among `diagnostics`
+fn count_severities(diagnostics: &[lsp_types::Diagnostic]) -> (u8, u8) {
+    let (mut error_count, mut warning_count) = (0u8, 0u8);
+    diagnostics.iter().for_each(|d| match d.severity.unwrap() {
+        lsp_types::DiagnosticSeverity::ERROR => error_count += 1,
+        lsp_types::DiagnosticSeverity::WARNING => warning_count += 1,
+        _ => {}
+    });
+    (error_count, warning_count)
+}
+
 pub fn check(
     cfg: Arc<dyn Config>,
     sub_matches: &clap::ArgMatches,
+) -> Result<(String, u8, u8), Box<dyn std::error::Error + Sync + Send>> {
+    check_with_input(cfg, sub_matches, &mut std::io::stdin())
+}
+
+/// `check`, reading `--stdin` and `--files-from -` from `input`
+fn check_with_input(
+    cfg: Arc<dyn Config>,
+    sub_matches: &clap::ArgMatches,
+    input: &mut dyn Read,
 ) -> Result<(String, u8, u8), Box<dyn std::error::Error + Sync + Send>> {
     span!(tracing::Level::INFO, "check");
-    let mut result = String::new();
-    let mut error_count = 0u8;
-    let mut warning_count = 0u8;
-    let mut write_lint = |group: &str, d: &lsp_types::Diagnostic| {
-        let code = match d.code.as_ref().unwrap() {
-            lsp_types::NumberOrString::String(s) => s,
-            _ => panic!("expected code to be a string"),
-        };
-        let start_line = d.range.start.line + 1;
-        let start_column = d.range.start.character + 1;
-        result.push_str(&format!(
-            "{}:{}:{}\t{:?}\t{}\t{}\n",
-            group,
-            start_line,
-            start_column,
-            d.severity.unwrap(),
-            code,
-            d.message
-        ));
+    let mut findings: Vec<Finding> = vec![];
+    let mut write_lint =
+        |group: &str, d: &lsp_types::Diagnostic| findings.push((group.to_owned(), d.clone()));
+    if sub_matches.get_flag("require-config") {
+        // a malformed config file is reported but otherwise ignored
+        if let Some(err) = cfg.load_errors().first() {
+            return Err(format!("unable to load the commit convention config: {err}").into());
+        }
+        if cfg.source() == crate::document::linting::DEFAULT_SOURCE {
+            return Err(
+                "no commit convention config found; refusing to check with the defaults".into(),
+            );
+        }
+    }
+    let range = match sub_matches.get_one::<String>("merge-base") {
+        Some(reference) => Some(merge_base_range(reference, None)?),
+        None => sub_matches.get_one::<String>("range").cloned(),
     };
-    let diagnostics = if let Some(file) = sub_matches.get_one::<PathBuf>("file") {
-        if !file.exists() {
-            return Err(format!("{} does not exist", file.display()).into());
+    let fix = sub_matches.get_flag("fix");
+    // with `--stdin --fix`, the fixed message rather than the report goes to stdout
+    let mut fixed_stdin = None;
+    let diagnostics = if sub_matches.get_flag("stdin") {
+        let mut text = String::new();
+        input.read_to_string(&mut text)?;
+        if fix {
+            let (fixed, count) = fix_text(&cfg, text)?;
+            eprintln!("{}", fix_summary(count));
+            text = fixed.clone();
+            fixed_stdin = Some(fixed);
         }
-        if !file.is_file() {
-            return Err(format!("{} is not a file", file.display()).into());
+        let diagnostics = lint_text(&cfg, text)?;
+        diagnostics.iter().for_each(|d| write_lint("<stdin>", d));
+        diagnostics
+    } else if let Some(file) = sub_matches.get_one::<PathBuf>("file") {
+        if fix {
+            let (fixed, count) = fix_text(&cfg, read_file(file)?)?;
+            if count > 0 {
+                std::fs::write(file, fixed)?;
+            }
+            eprintln!("{} to {}", fix_summary(count), file.display());
         }
+        let diagnostics = lint_file(&cfg, file)?;
         let group = file.display().to_string();
-        let text = std::fs::read_to_string(file)?;
-        let doc = GitCommitDocument::new().with_text(text);
-        let diagnostics = cfg.lint(&doc);
         diagnostics.iter().for_each(|d| write_lint(&group, d));
         diagnostics
-    } else if let Some(range) = sub_matches.get_one::<String>("range") {
-        let raw_hashes = git(&["log", "--format=%h", range], None)?;
-        let hashes = raw_hashes
+    } else if let Some(list) = sub_matches.get_one::<PathBuf>("files-from") {
+        let list = if list.as_os_str() == "-" {
+            let mut list = String::new();
+            input.read_to_string(&mut list)?;
+            list
+        } else {
+            std::fs::read_to_string(list)?
+        };
+        let mut diagnostics = vec![];
+        for file in list
             .lines()
             .map(|line| line.trim())
             .filter(|s| !s.is_empty())
-            .collect::<Vec<_>>();
+        {
+            let diagnostics_for_file = lint_file(&cfg, Path::new(file))?;
+            diagnostics_for_file
+                .iter()
+                .for_each(|d| write_lint(file, d));
+            diagnostics.extend(diagnostics_for_file);
+        }
+        diagnostics
+    } else if let Some(range) = range {
+        let hashes = range_hashes(&range, None)?;
         let mut diagnostics = vec![];
         // process each hash's commit message
-        for hash in hashes {
+        for hash in &hashes {
             let message = git(&["log", "-n", "1", "--format=%B", hash], None)?;
-            let doc = GitCommitDocument::new().with_text(message);
-            let diagnostics_for_hash = cfg.lint(&doc);
+            let doc = GitCommitDocument::try_new()?
+                .with_parse_timeout(cfg.parse_timeout_micros())
+                .with_comments_are_content(cfg.comments_are_content())
+                .with_text(message)
+                .with_revision(hash);
+            let diagnostics_for_hash = with_profile(cfg.clone(), &doc).lint(&doc);
             diagnostics_for_hash
                 .iter()
                 .for_each(|d| write_lint(hash, d));
@@ -101,12 +456,154 @@ pub fn check(
     } else {
         unreachable!()
     };
-    diagnostics.iter().for_each(|d| match d.severity.unwrap() {
-        lsp_types::DiagnosticSeverity::ERROR => error_count += 1,
-        lsp_types::DiagnosticSeverity::WARNING => warning_count += 1,
-        _ => {}
-    });
-    Ok((result, error_count, warning_count))
+    let (error_count, warning_count) = count_severities(&diagnostics);
+    let report = render(&findings, sub_matches);
+    let output = match fixed_stdin {
+        Some(fixed) => {
+            if !report.is_empty() {
+                eprintln!("{report}");
+            }
+            fixed
+        }
+        None => report,
+    };
+    Ok((output, error_count, warning_count))
+}
+
+/// print `findings` in the format `sub_matches` asks for
+fn render(findings: &[Finding], sub_matches: &clap::ArgMatches) -> String {
+    let suggestions_only = sub_matches.get_flag("suggestions-only");
+    let printed: Vec<Finding> = findings
+        .iter()
+        .map(|(group, d)| (group.clone(), as_printed(d, suggestions_only)))
+        .collect();
+    OutputFormat::from_matches(sub_matches).render(&printed)
+}
+
+/// lint a commit message passed as an argument, or read from stdin if absent
+pub fn lint(
+    cfg: Arc<dyn Config>,
+    sub_matches: &clap::ArgMatches,
+) -> Result<(String, u8, u8), Box<dyn std::error::Error + Sync + Send>> {
+    span!(tracing::Level::INFO, "lint");
+    let text = match sub_matches.get_one::<String>("message") {
+        Some(message) => message.clone(),
+        None => {
+            let mut message = String::new();
+            std::io::stdin().read_to_string(&mut message)?;
+            message
+        }
+    };
+    let diagnostics = lint_text(&cfg, text)?;
+    let findings: Vec<Finding> = diagnostics
+        .iter()
+        .map(|d| ("-".to_owned(), d.clone()))
+        .collect();
+    let (error_count, warning_count) = count_severities(&diagnostics);
+    Ok((render(&findings, sub_matches), error_count, warning_count))
+}
+
+/// the abbreviated hashes of the commits in a revision range
+fn range_hashes(
+    range: &str,
+    cwd: Option<PathBuf>,
+) -> Result<Vec<String>, Box<dyn std::error::Error + Sync + Send>> {
+    Ok(git(&["log", "--format=%h", range], cwd)?
+        .lines()
+        .map(|line| line.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_owned())
+        .collect())
+}
+
+/// the range of commits on HEAD that aren't reachable from `reference`'s merge base
+fn merge_base_range(
+    reference: &str,
+    cwd: Option<PathBuf>,
+) -> Result<String, Box<dyn std::error::Error + Sync + Send>> {
+    Ok(format!("{}..HEAD", merge_base(reference, cwd)?))
+}
+
+/// the `check` subcommand's arguments
+fn check_command() -> Command {
+    Command::new("check").about("Lint commit message(s)").infer_long_args(true)
+        .arg(
+            Arg::new("file").short('f')
+                .help("A relative or absolute path to the file containing your commit message.")
+                .conflicts_with_all(["range", "files-from", "merge-base"])
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(Arg::new("range").short('r').help("A git revision range to check.").conflicts_with("files-from"))
+        .arg(
+            Arg::new("merge-base").long("merge-base")
+                .help("Check only the commits since HEAD's merge base with this ref, e.g. `origin/main`.")
+                .conflicts_with_all(["range", "files-from"]),
+        )
+        .arg(
+            Arg::new("files-from").long("files-from")
+                .help("A file listing paths to commit messages, one per line; `-` reads the list from stdin.")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("stdin").long("stdin").action(ArgAction::SetTrue)
+                .help("Read the commit message to check from stdin, e.g. piped from a hook.")
+                .conflicts_with_all(["file", "range", "files-from", "merge-base"]),
+        )
+        .arg(
+            Arg::new("fix").long("fix").action(ArgAction::SetTrue)
+                .help("Format the message before checking it, rewriting the file in place or printing the result of --stdin.")
+                .conflicts_with_all(["range", "files-from", "merge-base"]),
+        )
+        .arg(
+            Arg::new("require-config").long("require-config").action(ArgAction::SetTrue)
+                .help("Fail if no project config was found, rather than checking with the defaults"))
+        .args(output_args())
+        .arg(cache_dir_arg())
+}
+
+/// the `lint` subcommand's arguments
+fn lint_command() -> Command {
+    Command::new("lint")
+        .about("Lint a commit message passed as an argument or via stdin")
+        .infer_long_args(true)
+        .arg(Arg::new("message").help("The commit message to lint; read from stdin if absent"))
+        .args(output_args())
+}
+
+/// how to print diagnostics, shared by `check` and `lint`
+fn output_args() -> [Arg; 3] {
+    [
+        Arg::new("format").long("format")
+            .value_parser(["text", "tab", "gcc", "json", "sarif", "checkstyle"]).default_value("text")
+            .help("`text` (or `tab`): tab-separated fields; `gcc`: `file:line:col: severity: message [code]`, for editors' quickfix lists; \
+                `json`: an array of findings; `sarif`: a SARIF 2.1.0 log, for code scanning; `checkstyle`: Checkstyle XML, for CI reports"),
+        Arg::new("porcelain").long("porcelain").action(ArgAction::SetTrue).conflicts_with("format")
+            .help("Print stable, versioned records for scripts: `v1<TAB>group<TAB>line<TAB>col<TAB>severity<TAB>code<TAB>message`, \
+                with tabs, newlines, and backslashes in fields escaped as `\\t`, `\\n`, and `\\\\`"),
+        Arg::new("suggestions-only").long("suggestions-only").action(ArgAction::SetTrue)
+            .help("Print errors and warnings as information; the exit code still reflects any errors"),
+    ]
+}
+
+/// the config for the current directory, warning about any problems loading it on stderr
+fn load_config<Cfg: ConfigStore>(
+    mut store: Cfg,
+) -> Result<Arc<dyn Config>, Box<dyn std::error::Error + Sync + Send>> {
+    let cfg = store.get(None)?;
+    for err in cfg.load_errors() {
+        eprintln!("warning: {err}");
+    }
+    Ok(cfg)
+}
+
+/// where to cache data derived from git history
+fn cache_dir_arg() -> Arg {
+    Arg::new("cache-dir")
+        .long("cache-dir")
+        .help(
+            "Where to cache data derived from git history; defaults to $XDG_CACHE_HOME/cconvention",
+        )
+        .value_parser(clap::value_parser!(PathBuf))
 }
 
 pub fn cli<F, Cfg: ConfigStore>(
@@ -143,7 +640,10 @@ where
             };
         }
         #[cfg(not(feature = "telemetry"))]
-        reg.init();
+        {
+            let _ = enable_tracing; // only used to toggle telemetry's tracing layer
+            reg.init();
+        }
     };
     #[cfg(feature = "telemetry")]
     let _guard = if enable_error_reporting {
@@ -165,26 +665,57 @@ where
     };
 
     let cmd = Command::new(PKG_NAME).version(PKG_VERSION)
+        .arg(
+            Arg::new("init-config-from-history").long("init-config-from-history").action(ArgAction::SetTrue)
+                .help("Write a starter .config/commit_convention.json based on the types and scopes in `git log`"))
+        .arg(
+            Arg::new("min-count").long("min-count").requires("init-config-from-history")
+                .value_parser(clap::value_parser!(usize)).default_value("2")
+                .help("How many commits must use a type or scope for it to be included"))
+        .args_conflicts_with_subcommands(true)
         .subcommand(
             Command::new("serve").about("Run a language server")
                 .arg(Arg::new("stdio").short('s').long("stdio").action(ArgAction::SetTrue).help("Communicate via stdio"))
-                .arg(Arg::new("tcp").short('t').long("tcp").help("Communicate via TCP")))
-        .subcommand(
-            Command::new("check").about("Lint commit message(s)").infer_long_args(true)
+                .arg(Arg::new("tcp").short('t').long("tcp").action(ArgAction::SetTrue).conflicts_with("stdio").help("Communicate via TCP"))
                 .arg(
-                    Arg::new("file").short('f')
-                        .help("A relative or absolute path to the file containing your commit message.")
-                        .conflicts_with_all(["range"])
-                        .value_parser(clap::value_parser!(PathBuf)),
-                )
-                .arg(Arg::new("range").short('r').help("A git revision range to check.")),
-        ).subcommand_required(true);
-    match cmd.get_matches().subcommand() {
+                    Arg::new("port").short('p').long("port")
+                        .value_parser(clap::value_parser!(u16)).default_value("9999")
+                        .help("The port to listen on with --tcp"))
+                .arg(
+                    Arg::new("disable-feature").long("disable-feature").action(ArgAction::Append)
+                        .value_parser(clap::builder::PossibleValuesParser::new(crate::config::Features::NAMES))
+                        .help("Don't advertise or handle a language-server feature; repeatable"))
+                .arg(cache_dir_arg()))
+        .subcommand(check_command())
+        .subcommand(lint_command())
+        .arg_required_else_help(true);
+    let matches = cmd.get_matches();
+    if matches.get_flag("init-config-from-history") {
+        let min_count = *matches.get_one::<usize>("min-count").unwrap();
+        return init_config_from_history(min_count);
+    }
+    if let Some((_, sub_matches)) = matches.subcommand() {
+        if let Some(dir) = sub_matches.get_one::<PathBuf>("cache-dir") {
+            crate::config::set_cache_dir(dir.clone());
+        }
+    }
+    match matches.subcommand() {
         Some(("serve", sub_matches)) => serve(init()?, sub_matches, capabilities),
         Some(("check", sub_matches)) => {
-            // TODO: use a well-known format rather than whatever this is
-            // see https://eslint.org/docs/latest/use/formatters/ for inspiration
-            let (message, error_count, warning_count) = check(init()?.get(None)?, sub_matches)?;
+            let (message, error_count, warning_count) = check(load_config(init()?)?, sub_matches)?;
+            if sub_matches.get_flag("stdin") && sub_matches.get_flag("fix") {
+                print!("{message}"); // the fixed message, verbatim
+            } else if !message.is_empty() {
+                println!("{}", message);
+            };
+            if error_count == 0 {
+                Ok(())
+            } else {
+                Err(format!("{} errors, {} warnings", error_count, warning_count).into())
+            }
+        }
+        Some(("lint", sub_matches)) => {
+            let (message, error_count, warning_count) = lint(load_config(init()?)?, sub_matches)?;
             if !message.is_empty() {
                 println!("{}", message);
             };
@@ -199,4 +730,228 @@ where
     }
 }
 
-// TODO: use snapshot tests of check() output
+/// write a starter config inferred from the current repo's history
+pub fn init_config_from_history(
+    min_count: usize,
+) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+    let root = crate::git::get_worktree_root(&std::env::current_dir()?)?;
+    let path = root.join(".config").join("commit_convention.json");
+    if path.exists() {
+        return Err(format!("{} already exists", path.display()).into());
+    }
+    let log = git(&["log", "--no-merges", "--format=%h %s"], Some(root))?;
+    let commits = log
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .collect::<Vec<_>>();
+    let (config, unparsed) = crate::config::infer_config(commits, min_count);
+    if !unparsed.is_empty() {
+        eprintln!(
+            "{} commits didn't parse as conventional commits:",
+            unparsed.len()
+        );
+        unparsed.iter().for_each(|commit| eprintln!("  {commit}"));
+    }
+    std::fs::create_dir_all(path.parent().unwrap())?;
+    std::fs::write(&path, serde_json::to_string_pretty(&config)? + "\n")?;
+    eprintln!("wrote {}", path.display());
+    Ok(())
+}
+
+/// a config with only the default lints
+#[cfg(test)]
+fn test_config() -> Arc<dyn Config> {
+    use crate::document::linting::{utils::construct_default_lint_tests_map, LintConfig, LintFn};
+    use std::collections::HashMap;
+    struct TestConfig(HashMap<&'static str, Arc<LintFn<'static>>>);
+    impl LintConfig for TestConfig {
+        fn worktree_root(&self) -> Option<PathBuf> {
+            None
+        }
+        fn get_test(&self, code: &str) -> Option<&Arc<LintFn<'_>>> {
+            self.0.get(code)
+        }
+    }
+    impl Config for TestConfig {}
+    #[allow(clippy::arc_with_non_send_sync)]
+    Arc::new(TestConfig(construct_default_lint_tests_map(50)))
+}
+
+#[test]
+fn test_check_files_from() {
+    let dir = crate::git::TempRepo::new("files-from");
+    let ok = dir.write("ok", "feat: ok\n");
+    let bad = dir.write("bad", "feat:bad\n");
+    let list = dir.write("list", &format!("{}\n\n{}\n", ok.display(), bad.display()));
+    let matches = check_command()
+        .try_get_matches_from(["check", "--files-from", list.to_str().unwrap()])
+        .unwrap();
+    let result = check(test_config(), &matches);
+
+    let (output, _, warning_count) = result.unwrap();
+    assert_eq!(warning_count, 1);
+    assert!(
+        output.starts_with(&format!("{}:1:", bad.display())),
+        "{output}"
+    );
+}
+
+#[test]
+fn test_check_stdin() {
+    let check_stdin = |message: &str| {
+        let matches = check_command()
+            .try_get_matches_from(["check", "--stdin", "--format", "gcc"])
+            .unwrap();
+        check_with_input(test_config(), &matches, &mut message.as_bytes()).unwrap()
+    };
+    assert_eq!(check_stdin("feat: add a thing\n"), (String::new(), 0, 0));
+    let (output, error_count, warning_count) = check_stdin("feat:add a thing\n");
+    assert_eq!((error_count, warning_count), (0, 1));
+    assert!(output.starts_with("<stdin>:1:6: warning: "), "{output}");
+    let (_, error_count, _) = check_stdin("feat(): x\n");
+    assert!(error_count > 0);
+    assert!(check_command()
+        .try_get_matches_from(["check", "--stdin", "-f", "x"])
+        .is_err());
+}
+
+#[test]
+fn test_check_fix() {
+    let dir = crate::git::TempRepo::new("fix");
+    let file = dir.write("COMMIT_EDITMSG", "feat :add a thing\nbody\n");
+    let matches = check_command()
+        .try_get_matches_from(["check", "--fix", "-f", file.to_str().unwrap()])
+        .unwrap();
+    let result = check(test_config(), &matches);
+    let fixed = std::fs::read_to_string(&file);
+
+    assert_eq!(result.unwrap(), (String::new(), 0, 0));
+    assert_eq!(fixed.unwrap(), "feat: add a thing\n\nbody\n");
+
+    let matches = check_command()
+        .try_get_matches_from(["check", "--fix", "--stdin"])
+        .unwrap();
+    let (output, ..) =
+        check_with_input(test_config(), &matches, &mut "fix(api) : x\n".as_bytes()).unwrap();
+    assert_eq!(output, "fix(api): x\n");
+    assert!(check_command()
+        .try_get_matches_from(["check", "--fix", "-r", "HEAD"])
+        .is_err());
+}
+
+#[test]
+fn test_check_gcc_format() {
+    let dir = crate::git::TempRepo::new("gcc");
+    let file = dir.write(
+        "COMMIT_EDITMSG",
+        "feat:add a thing\nwith a wrapped subject\n",
+    );
+    let matches = check_command()
+        .try_get_matches_from(["check", "--format", "gcc", "-f", file.to_str().unwrap()])
+        .unwrap();
+    let result = check(test_config(), &matches);
+
+    let (output, _, _) = result.unwrap();
+    let output = output.replace(&file.display().to_string(), "COMMIT_EDITMSG");
+    assert_eq!(output, include_str!("../tests/snapshots/check_gcc.txt"));
+}
+
+#[test]
+fn test_check_structured_formats() {
+    let dir = crate::git::TempRepo::new("formats");
+    let file = dir.write(
+        "COMMIT_EDITMSG",
+        "feat:add a thing\nwith a wrapped subject\n",
+    );
+    let output = |format: &str| {
+        let matches = check_command()
+            .try_get_matches_from(["check", "--format", format, "-f", file.to_str().unwrap()])
+            .unwrap();
+        let (output, _, _) = check(test_config(), &matches).unwrap();
+        output.replace(&file.display().to_string(), "COMMIT_EDITMSG") + "\n"
+    };
+    let outputs = ["json", "sarif", "checkstyle"].map(output);
+
+    assert_eq!(
+        outputs[0],
+        include_str!("../tests/snapshots/check_json.json")
+    );
+    assert_eq!(
+        outputs[1],
+        include_str!("../tests/snapshots/check_sarif.json")
+    );
+    assert_eq!(
+        outputs[2],
+        include_str!("../tests/snapshots/check_checkstyle.xml")
+    );
+}
+
+#[test]
+fn test_merge_base_range() {
+    let dir = crate::git::TempRepo::new("merge-base");
+    dir.git(&["symbolic-ref", "HEAD", "refs/heads/main"]);
+    let commit = |subject: &str| {
+        dir.commit(&["--allow-empty", "-m", subject]);
+        dir.git(&["rev-parse", "--short", "HEAD"]).trim().to_owned()
+    };
+    // main: A -- B -- E
+    //           \
+    // feature:   C -- D
+    commit("chore: A");
+    dir.git(&["checkout", "--quiet", "-b", "feature"]);
+    let c = commit("feat: C");
+    let d = commit("feat: D");
+    dir.git(&["checkout", "--quiet", "main"]);
+    commit("chore: B");
+    commit("chore: E");
+    dir.git(&["checkout", "--quiet", "feature"]);
+    let hashes = merge_base_range("main", Some(dir.to_path_buf()))
+        .and_then(|range| range_hashes(&range, Some(dir.to_path_buf())));
+    let missing = merge_base_range("nonexistent", Some(dir.to_path_buf()));
+
+    assert_eq!(hashes.unwrap(), vec![d, c]);
+    assert!(missing.is_err());
+}
+
+#[test]
+fn test_check_porcelain() {
+    let dir = crate::git::TempRepo::new("porcelain");
+    let file = dir.write(
+        "COMMIT_EDITMSG",
+        "feat:add a thing\nwith a wrapped subject\n",
+    );
+    let matches = check_command()
+        .try_get_matches_from(["check", "--porcelain", "-f", file.to_str().unwrap()])
+        .unwrap();
+    let result = check(test_config(), &matches);
+
+    let (output, _, _) = result.unwrap();
+    let output = output.replace(&file.display().to_string(), "COMMIT_EDITMSG");
+    assert_eq!(
+        output,
+        include_str!("../tests/snapshots/check_porcelain.txt")
+    );
+    assert!(check_command()
+        .try_get_matches_from(["check", "--porcelain", "--format", "gcc", "-f", "x"])
+        .is_err());
+}
+
+#[test]
+fn test_lint_inline_message() {
+    let lint_message = |message: &str| {
+        let matches = lint_command()
+            .try_get_matches_from(["lint", "--format", "gcc", message])
+            .unwrap();
+        lint(test_config(), &matches).unwrap()
+    };
+    assert_eq!(lint_message("feat: add a thing"), (String::new(), 0, 0));
+    let (output, _, warning_count) = lint_message("feat:add a thing");
+    assert_eq!(warning_count, 1);
+    assert!(output.starts_with("-:1:"), "{output}");
+    let matches = lint_command()
+        .try_get_matches_from(["lint", "--suggestions-only", "feat:add a thing"])
+        .unwrap();
+    let (output, _, warning_count) = lint(test_config(), &matches).unwrap();
+    assert_eq!(warning_count, 1);
+    assert!(output.contains("\tInformation\t"), "{output}");
+}
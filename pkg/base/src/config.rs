@@ -1,11 +1,16 @@
 // © Steven Kalt
 // SPDX-License-Identifier: APACHE-2.0
 use regex::Regex;
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
 /// use this for reading configuration from the environment
 pub const ENV_PREFIX: &str = "GIT_CC_LS";
 
-use crate::document::linting::LintConfig;
+use crate::document::{linting::LintConfig, FormatOptions, GitCommitDocument};
 use crate::git;
 
 pub const DEFAULT_TYPES: &[(&str, &str)] = &[
@@ -29,15 +34,245 @@ pub const DEFAULT_TYPES: &[(&str, &str)] = &[
     ("temp", "A commit to be fixed/rebased later."),
 ];
 
+/// well-known git trailers offered as completions, with what each records
+pub const DEFAULT_TRAILERS: &[(&str, &str)] = &[
+    (
+        "Signed-off-by",
+        "Certifies the author may submit the change (the Developer Certificate of Origin).",
+    ),
+    ("Co-authored-by", "Credits a co-author of the change."),
+    ("Reviewed-by", "Credits a reviewer who approved the change."),
+    (
+        "Acked-by",
+        "Records a maintainer's acknowledgement of the change.",
+    ),
+    ("Tested-by", "Credits someone who tested the change."),
+    ("Refs", "References related issues, e.g. `#123`."),
+    ("Closes", "Closes the referenced issues once merged."),
+    ("Fixes", "Fixes the referenced issues once merged."),
+];
+
+/// the release a change calls for under Semantic Versioning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SemverBump {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+impl SemverBump {
+    /// the bump conventional-commit tooling (e.g. semantic-release) infers for a non-breaking type
+    pub fn for_type(type_: &str) -> Self {
+        match type_ {
+            "feat" => SemverBump::Minor,
+            "fix" | "perf" => SemverBump::Patch,
+            _ => SemverBump::None,
+        }
+    }
+}
+
+impl std::fmt::Display for SemverBump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SemverBump::None => "no release",
+            SemverBump::Patch => "⬆ patch release",
+            SemverBump::Minor => "⬆ minor release",
+            SemverBump::Major => "⬆ major release",
+        })
+    }
+}
+
 lazy_static! {
     static ref RE: Regex =
         Regex::new(r"^(?P<type>[^:\(!]+)(?:\((?P<scope>[^\)]+)\))?:\s*(?P<subject>.+)$").unwrap();
 }
 
+/// language-server features that can be disabled individually
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Features {
+    pub completion: bool,
+    pub hover: bool,
+    pub semantic_tokens: bool,
+    pub formatting: bool,
+    pub document_links: bool,
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Features {
+            completion: true,
+            hover: true,
+            semantic_tokens: true,
+            formatting: true,
+            document_links: true,
+        }
+    }
+}
+
+impl Features {
+    /// the names used by `--disable-feature` and the `features` config table
+    pub const NAMES: &'static [&'static str] = &[
+        "completion",
+        "hover",
+        "semanticTokens",
+        "formatting",
+        "documentLinks",
+    ];
+    /// toggle a feature by its name in `Features::NAMES`
+    pub fn set(&mut self, name: &str, enabled: bool) -> Result<(), String> {
+        let feature = match name {
+            "completion" => &mut self.completion,
+            "hover" => &mut self.hover,
+            "semanticTokens" => &mut self.semantic_tokens,
+            "formatting" => &mut self.formatting,
+            "documentLinks" => &mut self.document_links,
+            _ => return Err(format!("unknown feature {name:?}")),
+        };
+        *feature = enabled;
+        Ok(())
+    }
+    /// the features enabled in both `self` and `other`
+    pub fn intersect(self, other: Features) -> Features {
+        Features {
+            completion: self.completion && other.completion,
+            hover: self.hover && other.hover,
+            semantic_tokens: self.semantic_tokens && other.semantic_tokens,
+            formatting: self.formatting && other.formatting,
+            document_links: self.document_links && other.document_links,
+        }
+    }
+}
+
+/// overrides sent by the client in the `cconvention` section of a `workspace/didChangeConfiguration`
+/// notification, layered over each worktree's configuration
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(default)]
+pub struct ClientSettings {
+    /// the `header_max_line_length` cutoff
+    pub max_subject_length: Option<u16>,
+    /// the lint codes to run, replacing the configured ones
+    pub enabled_lints: Option<Vec<String>>,
+}
+
+/// lint overrides read from the environment, which win over both config files and client
+/// settings:
+/// - `$GIT_CC_LS_DISABLE_LINTS`: a comma-separated list of lint codes to turn off
+/// - `$GIT_CC_LS_SEVERITY_<CODE>`: `error`, `warning`, `info`, `hint`, or `none` to turn the lint
+///   off, e.g. `GIT_CC_LS_SEVERITY_SUBJECT_EMPTY=hint`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EnvLintOverrides {
+    pub disabled: Vec<String>,
+    pub severity: HashMap<String, lsp_types::DiagnosticSeverity>,
+}
+
+impl EnvLintOverrides {
+    pub fn from_env() -> Self {
+        Self::from_vars(std::env::vars())
+    }
+    /// parse the overrides out of `(name, value)` pairs of environment variables
+    pub fn from_vars(vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        let disable = format!("{ENV_PREFIX}_DISABLE_LINTS");
+        let severity_prefix = format!("{ENV_PREFIX}_SEVERITY_");
+        let mut overrides = Self::default();
+        for (name, value) in vars {
+            if name == disable {
+                overrides.disabled.extend(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|code| !code.is_empty())
+                        .map(String::from),
+                );
+            } else if let Some(code) = name.strip_prefix(&severity_prefix) {
+                let code = code.to_ascii_lowercase();
+                let severity = match value.trim().to_ascii_lowercase().as_str() {
+                    "error" => lsp_types::DiagnosticSeverity::ERROR,
+                    "warning" => lsp_types::DiagnosticSeverity::WARNING,
+                    "info" => lsp_types::DiagnosticSeverity::INFORMATION,
+                    "hint" => lsp_types::DiagnosticSeverity::HINT,
+                    "none" => {
+                        overrides.disabled.push(code);
+                        continue;
+                    }
+                    _other => {
+                        log_info!("ignoring ${name}: unknown severity {_other:?}");
+                        continue;
+                    }
+                };
+                overrides.severity.insert(code, severity);
+            }
+        }
+        overrides
+    }
+    /// turn off the disabled lints and layer the severity overrides over `severity`
+    pub fn apply(
+        &self,
+        enabled_lints: &mut Vec<String>,
+        severity: &mut HashMap<String, lsp_types::DiagnosticSeverity>,
+    ) {
+        enabled_lints.retain(|code| !self.disabled.contains(code));
+        severity.extend(self.severity.clone());
+    }
+}
+
 /// provides
 pub trait Config: LintConfig {
     // TODO: ^change to PathBuf or lsp_types::Url
     // TODO: ^consider removing in favor of a `search_path` method or similar?
+    /// opt-in formatting behavior; by default, only the always-on fixes are applied, plus
+    /// rewriting `[scope]` to `(scope)` when the `bracket_scope` lint is enabled
+    fn format_options(&self) -> FormatOptions {
+        FormatOptions {
+            parenthesize_bracket_scopes: self
+                .enabled_lint_codes()
+                .contains(&crate::document::linting::default::BRACKET_SCOPE),
+            ..Default::default()
+        }
+    }
+    /// problems loading this config that were worked around, e.g. a config file that failed to
+    /// parse and was replaced by the defaults
+    fn load_errors(&self) -> Vec<ConfigFileError> {
+        vec![]
+    }
+    /// whether `#`-prefixed lines are message content rather than git comments
+    fn comments_are_content(&self) -> bool {
+        false
+    }
+    /// how long to let tree-sitter parse a message before giving up, in microseconds.
+    /// `$GIT_CC_LS_PARSE_TIMEOUT_MICROS` takes precedence, if set.
+    fn parse_timeout_micros(&self) -> u64 {
+        parse_timeout_from_env().unwrap_or(crate::document::DEFAULT_PARSE_TIMEOUT_MICROS)
+    }
+    /// which language-server features to advertise
+    fn features(&self) -> Features {
+        Features::default()
+    }
+    /// whether to offer completions for Gerrit's `Change-Id` trailer
+    fn gerrit(&self) -> bool {
+        false
+    }
+    /// whether to offer gitmoji completions at the start of the subject's message
+    fn gitmoji(&self) -> bool {
+        false
+    }
+    /// the settings for the named profile, which a message selects with a
+    /// `# cconvention-profile: <name>` comment
+    fn profile(&self, _name: &str) -> Option<Arc<dyn Config>> {
+        None
+    }
+    /// types that are still accepted but shouldn't be used anymore, e.g. `feature` in favor of `feat`
+    fn deprecated_types(&self) -> Vec<String> {
+        vec![]
+    }
+    /// the subject length `header_max_line_length` allows; 0 means no limit
+    fn max_subject_length(&self) -> u16 {
+        crate::document::linting::default::MAX_HEADER_LINE_LENGTH as u16
+    }
+    /// the release a non-breaking commit of this type calls for; breaking changes are always major
+    fn semver_bump(&self, type_: &str) -> SemverBump {
+        SemverBump::for_type(type_)
+    }
     fn type_suggestions(&self) -> Vec<(String, String)> {
         let mut result = Vec::with_capacity(DEFAULT_TYPES.len());
         for (label, detail) in DEFAULT_TYPES {
@@ -45,46 +280,342 @@ pub trait Config: LintConfig {
         }
         result
     }
+    /// trailer keys to complete at the start of a body line, with a short description of each
+    fn trailer_suggestions(&self) -> Vec<(String, String)> {
+        DEFAULT_TRAILERS
+            .iter()
+            .map(|(key, detail)| (key.to_string(), detail.to_string()))
+            .collect()
+    }
     fn scope_suggestions(&self) -> Vec<(String, String)> {
         // guess the scopes from the staged files
-        let worktree_root = self.worktree_root();
-        let files = git::staged_files(worktree_root.clone());
-        let applicable_scopes: Vec<(String, String)> = {
-            let output = git::related_commits(files.as_slice(), worktree_root);
-            let unique: HashMap<&str, usize> = output
-                .iter()
-                .filter_map(|line| RE.captures(line))
-                .filter_map(|captures| captures.name("scope"))
-                .filter_map(|scope| Some(scope.as_str()))
-                // using an integer smaller than usize won't matter, since we're iterating
-                // over tuples of `(&str, _)` later which have alignment on usize boundaries.
-                .fold(HashMap::<&str, usize>::new(), |mut set, scope| {
-                    if let Some(count) = set.get_mut(scope) {
-                        *count += 1;
-                    } else {
-                        set.insert(scope, 1);
-                    };
-                    set
-                });
-            let mut sorted_descending: Vec<(&str, usize)> = unique.into_iter().collect();
-            sorted_descending.sort_by(|a, b| b.1.cmp(&a.1));
-            sorted_descending
-                .into_iter()
-                .map(|(scope, count)| {
-                    (
-                        scope.to_owned(),
-                        format!("used {} times in the currently-staged files", count),
-                    )
-                })
-                .collect()
+        staged_scope_suggestions(self.worktree_root(), DEFAULT_HISTORY_LIMIT)
+    }
+    /// where `type_` is declared in the config file, for go-to-definition
+    fn type_definition(&self, _type: &str) -> Option<lsp_types::Location> {
+        None
+    }
+    /// where `scope` is declared in the config file, for go-to-definition
+    fn scope_definition(&self, _scope: &str) -> Option<lsp_types::Location> {
+        None
+    }
+    /// the person or team responsible for a scope, e.g. `@org/frontend`
+    fn scope_owner(&self, _scope: &str) -> Option<String> {
+        None
+    }
+    /// markdown describing a scope and its owner, if anything is known about it
+    fn scope_hover(&self, scope: &str) -> Option<String> {
+        let doc = self
+            .scope_suggestions()
+            .into_iter()
+            .find(|(label, _)| label == scope)
+            .map(|(_, doc)| doc);
+        let owned_by = self
+            .scope_owner(scope)
+            .map(|owner| format!("owned by {owner}"));
+        match (doc, owned_by) {
+            (Some(doc), Some(owned_by)) if !doc.contains(&owned_by) => {
+                Some(format!("{doc}\n\n{owned_by}"))
+            }
+            (Some(doc), _) => Some(doc),
+            (None, owned_by) => owned_by,
+        }
+    }
+}
+
+/// the parse timeout from `$GIT_CC_LS_PARSE_TIMEOUT_MICROS`, if set
+pub fn parse_timeout_from_env() -> Option<u64> {
+    std::env::var(format!("{ENV_PREFIX}_PARSE_TIMEOUT_MICROS"))
+        .ok()
+        .and_then(|micros| micros.parse().ok())
+}
+
+/// a cache key and the scopes suggested for it
+type StagedScopes = (String, Vec<(String, String)>);
+lazy_static! {
+    /// worktree root => the latest cache key and its suggested scopes
+    static ref STAGED_SCOPES: Mutex<HashMap<Option<PathBuf>, StagedScopes>> =
+        Mutex::new(HashMap::new());
+    /// overrides the default cache directory; see `set_cache_dir`
+    static ref CACHE_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+/// store on-disk caches in `dir` rather than the OS's cache directory
+pub fn set_cache_dir(dir: PathBuf) {
+    *CACHE_DIR.lock().unwrap_or_else(|e| e.into_inner()) = Some(dir);
+}
+
+/// where to cache data derived from git or forges: `--cache-dir`, if given, else
+/// `$XDG_CACHE_HOME/cconvention` or `~/.cache/cconvention`
+pub fn cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = CACHE_DIR.lock().unwrap_or_else(|e| e.into_inner()).clone() {
+        return Some(dir);
+    }
+    std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .map(|dir| dir.join("cconvention"))
+}
+
+/// how many past commits to scan for scopes by default
+pub const DEFAULT_HISTORY_LIMIT: usize = 1000;
+
+/// The scopes of the last `history_limit` commits that touched the currently-staged files,
+/// most-relevant first. Results are cached in memory and on disk until HEAD or the set of staged
+/// files changes.
+pub fn staged_scope_suggestions(
+    worktree_root: Option<PathBuf>,
+    history_limit: usize,
+) -> Vec<(String, String)> {
+    let mut staged = git::staged_files(worktree_root.clone());
+    staged.sort();
+    let head = git::head(worktree_root.clone()).unwrap_or_default();
+    let key = format!(
+        "{:?}\n{head}\n{history_limit}\n{}",
+        worktree_root,
+        staged.join("\n")
+    );
+    cached_scopes(worktree_root.clone(), key, cache_dir().as_deref(), || {
+        let history = git::related_commits(&staged, history_limit, worktree_root);
+        scopes_for_staged_files(&history, &staged)
+    })
+}
+
+/// look up `key`'s suggestions in memory, then in `cache_dir`, computing and caching them on a miss
+fn cached_scopes(
+    worktree_root: Option<PathBuf>,
+    key: String,
+    cache_dir: Option<&Path>,
+    compute: impl FnOnce() -> Vec<(String, String)>,
+) -> Vec<(String, String)> {
+    let mut cache = STAGED_SCOPES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((cached_key, suggestions)) = cache.get(&worktree_root) {
+        if *cached_key == key {
+            return suggestions.clone();
+        }
+    }
+    let file = cache_dir.map(|dir| {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        key.hash(&mut hasher);
+        dir.join(format!("scopes-{:016x}.json", hasher.finish()))
+    });
+    // the file records its key in case of hash collisions
+    let from_disk = file
+        .as_ref()
+        .and_then(|file| std::fs::read_to_string(file).ok())
+        .and_then(|text| serde_json::from_str::<StagedScopes>(&text).ok())
+        .filter(|(cached_key, _)| *cached_key == key)
+        .map(|(_, suggestions)| suggestions);
+    let suggestions = from_disk.unwrap_or_else(|| {
+        let suggestions = compute();
+        if let Some(file) = &file {
+            // a missing cache is only a performance problem, so ignore failures
+            let _ = file.parent().map(std::fs::create_dir_all);
+            if let Ok(text) = serde_json::to_string(&(&key, &suggestions)) {
+                let _ = std::fs::write(file, text);
+            }
+        }
+        suggestions
+    });
+    cache.insert(worktree_root, (key, suggestions.clone()));
+    suggestions
+}
+
+/// Rank the scopes of past commits by how many touched the `staged` files, noting the directory
+/// containing the staged files each scope was used with. If nothing is staged, rank scopes by use.
+pub fn scopes_for_staged_files(
+    history: &[(String, Vec<String>)],
+    staged: &[String],
+) -> Vec<(String, String)> {
+    let mut scopes: indexmap::IndexMap<&str, (usize, Vec<&str>)> = indexmap::IndexMap::new();
+    for (subject, files) in history {
+        let Some(scope) = RE.captures(subject).and_then(|c| c.name("scope")) else {
+            continue;
         };
-        let mut result = Vec::with_capacity(applicable_scopes.len());
+        let matched: Vec<&str> = files
+            .iter()
+            .filter(|file| staged.contains(file))
+            .map(|file| file.as_str())
+            .collect();
+        if matched.is_empty() && !staged.is_empty() {
+            continue;
+        }
+        let (count, files) = scopes.entry(scope.as_str()).or_default();
+        *count += 1;
+        files.extend(matched);
+    }
+    // a stable sort keeps the more recently-used scope first in case of ties
+    scopes.sort_by(|_, (a, _), _, (b, _)| b.cmp(a));
+    scopes
+        .into_iter()
+        .map(|(scope, (count, files))| {
+            let detail = if files.is_empty() {
+                format!("used {count} times")
+            } else {
+                format!("matches staged files in {}", common_dir(&files))
+            };
+            (scope.to_owned(), detail)
+        })
+        .collect()
+}
 
-        for (label, detail) in applicable_scopes {
-            result.push((label, detail));
+/// the deepest directory containing all of `files`, with a trailing slash
+fn common_dir(files: &[&str]) -> String {
+    let mut prefix: Vec<&str> = files[0].split('/').collect();
+    prefix.pop(); // the file name
+    for file in &files[1..] {
+        let dirs: Vec<&str> = file.split('/').collect();
+        let shared = prefix
+            .iter()
+            .zip(&dirs[..dirs.len() - 1])
+            .take_while(|(a, b)| a == b)
+            .count();
+        prefix.truncate(shared);
+    }
+    if prefix.is_empty() {
+        "./".into()
+    } else {
+        format!("{}/", prefix.join("/"))
+    }
+}
+
+/// An error at a specific position within a config file, e.g. a custom rule's query that failed to
+/// compile. The language server publishes these as diagnostics on the config file.
+#[derive(Debug, Clone)]
+pub struct ConfigFileError {
+    pub path: PathBuf,
+    pub range: lsp_types::Range,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.path.display(),
+            self.range.start.line + 1,
+            self.range.start.character + 1,
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for ConfigFileError {}
+
+/// Infer a starter config from the `(hash, subject)` pairs of past commits, keeping the types and
+/// scopes used at least `min_count` times. Also returns the commits whose subjects didn't parse.
+pub fn infer_config<'a>(
+    commits: impl IntoIterator<Item = (&'a str, &'a str)>,
+    min_count: usize,
+) -> (serde_json::Value, Vec<String>) {
+    let mut types: HashMap<&str, usize> = HashMap::new();
+    let mut scopes: HashMap<&str, usize> = HashMap::new();
+    let mut unparsed = vec![];
+    for (hash, subject) in commits {
+        match RE.captures(subject) {
+            Some(captures) if !captures["type"].contains(char::is_whitespace) => {
+                *types
+                    .entry(captures.name("type").unwrap().as_str())
+                    .or_default() += 1;
+                if let Some(scope) = captures.name("scope") {
+                    *scopes.entry(scope.as_str()).or_default() += 1;
+                }
+            }
+            _ => unparsed.push(format!("{hash} {subject}")),
         }
-        result
     }
+    let describe = |counts: HashMap<&str, usize>, describe_known: bool| {
+        let mut map = serde_json::Map::new();
+        for (name, count) in counts.into_iter().filter(|(_, count)| *count >= min_count) {
+            let known = DEFAULT_TYPES
+                .iter()
+                .find(|(t, _)| describe_known && *t == name)
+                .map(|(_, doc)| doc.to_string());
+            map.insert(
+                name.to_owned(),
+                known
+                    .unwrap_or_else(|| format!("used {count} times"))
+                    .into(),
+            );
+        }
+        serde_json::Value::Object(map)
+    };
+    let config = serde_json::json!({
+        "types": describe(types, true),
+        "scopes": describe(scopes, false),
+        "header_line_max_length": {
+            "severity": "warning",
+            "max_length": crate::document::linting::default::MAX_HEADER_LINE_LENGTH,
+        },
+        "body_leading_blank": { "severity": "warning" },
+        "footer_leading_blank": { "severity": "warning" },
+    });
+    (config, unparsed)
+}
+
+#[test]
+fn test_env_lint_overrides() {
+    let vars = [
+        (
+            "GIT_CC_LS_DISABLE_LINTS",
+            "header_max_line_length, subject_empty,",
+        ),
+        ("GIT_CC_LS_SEVERITY_SUBJECT_LEADING_SPACE", "Error"),
+        ("GIT_CC_LS_SEVERITY_BODY_LEADING_BLANK", "none"),
+        ("GIT_CC_LS_SEVERITY_TYPE_EMPTY", "loud"),
+        ("GIT_CC_LS_PARSE_TIMEOUT_MICROS", "10"),
+    ];
+    let overrides =
+        EnvLintOverrides::from_vars(vars.map(|(name, value)| (name.into(), value.into())));
+    assert_eq!(
+        overrides.disabled,
+        vec![
+            "header_max_line_length",
+            "subject_empty",
+            "body_leading_blank"
+        ]
+    );
+    assert_eq!(
+        overrides.severity,
+        HashMap::from([(
+            "subject_leading_space".to_string(),
+            lsp_types::DiagnosticSeverity::ERROR
+        )])
+    );
+    let mut enabled: Vec<String> = ["subject_empty", "type_empty", "subject_leading_space"]
+        .map(String::from)
+        .into();
+    let mut severity = HashMap::from([(
+        "subject_leading_space".to_string(),
+        lsp_types::DiagnosticSeverity::HINT,
+    )]);
+    overrides.apply(&mut enabled, &mut severity);
+    assert_eq!(enabled, vec!["type_empty", "subject_leading_space"]);
+    // the environment wins
+    assert_eq!(
+        severity["subject_leading_space"],
+        lsp_types::DiagnosticSeverity::ERROR
+    );
+}
+
+#[test]
+fn test_infer_config() {
+    let commits = [
+        ("a1", "feat(api): add endpoint"),
+        ("b2", "feat(api): add another endpoint"),
+        ("c3", "fix(ui): fix button"),
+        ("d4", "Merge branch 'main'"),
+        ("e5", "wip stuff: more"),
+    ];
+    let (config, unparsed) = infer_config(commits, 2);
+    assert_eq!(config["types"]["feat"], "Adds a new feature.");
+    assert!(config["types"].get("fix").is_none());
+    assert_eq!(config["scopes"]["api"], "used 2 times");
+    assert_eq!(
+        unparsed,
+        vec!["d4 Merge branch 'main'", "e5 wip stuff: more"]
+    );
 }
 
 pub(crate) fn as_completion(items: &[(String, String)]) -> Vec<lsp_types::CompletionItem> {
@@ -97,16 +628,106 @@ pub(crate) fn as_completion(items: &[(String, String)]) -> Vec<lsp_types::Comple
     result
 }
 
+/// the profile `doc` selects, else the `default` profile, else `cfg` itself
+pub fn with_profile(cfg: Arc<dyn Config>, doc: &GitCommitDocument) -> Arc<dyn Config> {
+    let name = doc.profile().unwrap_or_else(|| "default".into());
+    cfg.profile(&name).unwrap_or(cfg)
+}
+
 pub trait ConfigStore {
     /// get the configuration relevant to the given worktree root
     fn get(
         &mut self,
         worktree_root: Option<PathBuf>,
     ) -> Result<Arc<dyn Config>, Box<dyn std::error::Error + Send + Sync>>;
+    /// get the configuration for `doc`'s worktree and selected profile
+    fn get_for(
+        &mut self,
+        doc: &GitCommitDocument,
+    ) -> Result<Arc<dyn Config>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(with_profile(self.get(doc.worktree_root.clone())?, doc))
+    }
+    /// `Name <email>` for each recent commit author in the worktree, most recent first
+    fn recent_authors(&mut self, worktree_root: Option<PathBuf>) -> Vec<String> {
+        git::recent_authors(worktree_root)
+    }
     // self has to ^ be mutable because we might need to update the cache of configurations
     /// mark the given paths as dirty, returning the paths associated with invalidated configuration
     /// in order to reload them and optionally push updates to affected lints
     fn set_dirty(&mut self, _paths: Vec<PathBuf>) -> Vec<PathBuf> {
         vec![]
     }
+    /// apply the client's settings to every configuration handed out from now on
+    fn set_settings(&mut self, _settings: ClientSettings) {}
+}
+
+#[test]
+fn test_scopes_for_staged_files() {
+    let commit = |subject: &str, files: &[&str]| {
+        (
+            subject.to_owned(),
+            files.iter().map(|f| f.to_string()).collect::<Vec<_>>(),
+        )
+    };
+    let history = vec![
+        commit(
+            "fix(api): handle timeouts",
+            &["src/api/client.rs", "src/api/mod.rs"],
+        ),
+        commit(
+            "feat(ui): add a button",
+            &["src/ui/button.rs", "src/api/routes.rs"],
+        ),
+        commit(
+            "feat(api): add routes",
+            &["src/api/routes.rs", "src/api/mod.rs"],
+        ),
+        commit("docs: explain", &["src/api/mod.rs"]),
+        commit("chore(ci): bump node", &[".github/workflows/ci.yml"]),
+    ];
+    let staged = vec!["src/api/mod.rs".to_owned(), "src/api/routes.rs".to_owned()];
+    assert_eq!(
+        scopes_for_staged_files(&history, &staged),
+        vec![
+            (
+                "api".to_owned(),
+                "matches staged files in src/api/".to_owned()
+            ),
+            (
+                "ui".to_owned(),
+                "matches staged files in src/api/".to_owned()
+            ),
+        ]
+    );
+    assert_eq!(
+        scopes_for_staged_files(&history, &[]),
+        vec![
+            ("api".to_owned(), "used 2 times".to_owned()),
+            ("ui".to_owned(), "used 1 times".to_owned()),
+            ("ci".to_owned(), "used 1 times".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn test_cached_scopes() {
+    let cache = crate::git::TempRepo::new("cache");
+    let dir: &Path = &cache;
+    let root = Some(dir.join("repo"));
+    let computed = std::cell::Cell::new(0);
+    let compute = || {
+        computed.set(computed.get() + 1);
+        vec![("api".to_owned(), "used 1 times".to_owned())]
+    };
+    let first = cached_scopes(root.clone(), "a".into(), Some(dir), compute);
+    // forget the in-memory cache, as if this were a fresh process
+    STAGED_SCOPES.lock().unwrap().remove(&root);
+    let from_disk = cached_scopes(root.clone(), "a".into(), Some(dir), compute);
+    let from_memory = cached_scopes(root.clone(), "a".into(), Some(dir), compute);
+    assert_eq!(computed.get(), 1);
+    cached_scopes(root.clone(), "b".into(), Some(dir), compute); // e.g. HEAD moved
+
+    assert_eq!(computed.get(), 2);
+    assert_eq!(first, from_disk);
+    assert_eq!(first, from_memory);
 }
@@ -0,0 +1,176 @@
+// © Steven Kalt
+// SPDX-License-Identifier: APACHE-2.0
+//! Typed lint codes, for consumers that would rather not match on `Diagnostic::code` strings.
+use std::fmt;
+
+use super::{default, INVALID};
+
+/// a built-in lint code, or the code of a lint provided by a config or plugin
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LintCode {
+    Invalid,
+    ConflictMarkers,
+    BodyLeadingBlank,
+    FooterLeadingBlank,
+    CommentLeadingBlank,
+    TrailerWithoutBody,
+    BracketScope,
+    SubjectFullStop,
+    SubjectUrl,
+    TypeLength,
+    ScopeLength,
+    SubjectLength,
+    TypeCase,
+    ScopeCase,
+    SubjectCase,
+    HeaderMaxLineLength,
+    BodyMaxLineLength,
+    TotalMaxLength,
+    ScopeEmpty,
+    SubjectEmpty,
+    SubjectLeadingSpace,
+    SubjectWrapped,
+    TypeEnum,
+    ChangeIdFormat,
+    MissingChangeId,
+    BreakingChangeCase,
+    TrailerSeparator,
+    ScopeMaxWords,
+    SubjectLengthTiers,
+    SubjectGitmoji,
+    /// any code not built into this crate
+    Other(String),
+}
+
+const BUILTIN: &[LintCode] = &[
+    LintCode::Invalid,
+    LintCode::ConflictMarkers,
+    LintCode::BodyLeadingBlank,
+    LintCode::FooterLeadingBlank,
+    LintCode::CommentLeadingBlank,
+    LintCode::TrailerWithoutBody,
+    LintCode::BracketScope,
+    LintCode::SubjectFullStop,
+    LintCode::SubjectUrl,
+    LintCode::TypeLength,
+    LintCode::ScopeLength,
+    LintCode::SubjectLength,
+    LintCode::TypeCase,
+    LintCode::ScopeCase,
+    LintCode::SubjectCase,
+    LintCode::HeaderMaxLineLength,
+    LintCode::BodyMaxLineLength,
+    LintCode::TotalMaxLength,
+    LintCode::ScopeEmpty,
+    LintCode::SubjectEmpty,
+    LintCode::SubjectLeadingSpace,
+    LintCode::SubjectWrapped,
+    LintCode::TypeEnum,
+    LintCode::ChangeIdFormat,
+    LintCode::MissingChangeId,
+    LintCode::BreakingChangeCase,
+    LintCode::TrailerSeparator,
+    LintCode::ScopeMaxWords,
+    LintCode::SubjectLengthTiers,
+    LintCode::SubjectGitmoji,
+];
+
+impl LintCode {
+    /// the string used as the `Diagnostic::code`
+    pub fn as_str(&self) -> &str {
+        match self {
+            LintCode::Invalid => INVALID,
+            LintCode::ConflictMarkers => default::CONFLICT_MARKERS,
+            LintCode::BodyLeadingBlank => default::BODY_LEADING_BLANK,
+            LintCode::FooterLeadingBlank => default::FOOTER_LEADING_BLANK,
+            LintCode::CommentLeadingBlank => default::COMMENT_LEADING_BLANK,
+            LintCode::TrailerWithoutBody => default::TRAILER_WITHOUT_BODY,
+            LintCode::BracketScope => default::BRACKET_SCOPE,
+            LintCode::SubjectFullStop => default::SUBJECT_FULL_STOP,
+            LintCode::SubjectUrl => default::SUBJECT_URL,
+            LintCode::TypeLength => default::TYPE_LENGTH,
+            LintCode::ScopeLength => default::SCOPE_LENGTH,
+            LintCode::SubjectLength => default::SUBJECT_LENGTH,
+            LintCode::TypeCase => default::TYPE_CASE,
+            LintCode::ScopeCase => default::SCOPE_CASE,
+            LintCode::SubjectCase => default::SUBJECT_CASE,
+            LintCode::HeaderMaxLineLength => default::HEADER_MAX_LINE_LENGTH,
+            LintCode::BodyMaxLineLength => default::BODY_MAX_LINE_LENGTH,
+            LintCode::TotalMaxLength => default::TOTAL_MAX_LENGTH,
+            LintCode::ScopeEmpty => default::SCOPE_EMPTY,
+            LintCode::SubjectEmpty => default::SUBJECT_EMPTY,
+            LintCode::SubjectLeadingSpace => default::SUBJECT_LEADING_SPACE,
+            LintCode::SubjectWrapped => default::SUBJECT_WRAPPED,
+            LintCode::TypeEnum => default::TYPE_ENUM,
+            LintCode::ChangeIdFormat => default::CHANGE_ID_FORMAT,
+            LintCode::MissingChangeId => default::MISSING_CHANGE_ID,
+            LintCode::BreakingChangeCase => default::BREAKING_CHANGE_CASE,
+            LintCode::TrailerSeparator => default::TRAILER_SEPARATOR,
+            LintCode::ScopeMaxWords => default::SCOPE_MAX_WORDS,
+            LintCode::SubjectLengthTiers => default::SUBJECT_LENGTH_TIERS,
+            LintCode::SubjectGitmoji => default::SUBJECT_GITMOJI,
+            LintCode::Other(code) => code,
+        }
+    }
+}
+
+impl From<&str> for LintCode {
+    fn from(code: &str) -> Self {
+        BUILTIN
+            .iter()
+            .find(|builtin| builtin.as_str() == code)
+            .cloned()
+            .unwrap_or_else(|| LintCode::Other(code.to_owned()))
+    }
+}
+
+impl fmt::Display for LintCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// the typed code of a diagnostic, if it has a string code
+pub fn code_of(diagnostic: &lsp_types::Diagnostic) -> Option<LintCode> {
+    match &diagnostic.code {
+        Some(lsp_types::NumberOrString::String(code)) => Some(code.as_str().into()),
+        _ => None,
+    }
+}
+
+/// every built-in lint code, whether or not it's enabled by default
+pub fn all_lint_codes() -> Vec<LintCode> {
+    BUILTIN.to_vec()
+}
+
+#[test]
+fn test_lint_code_round_trip() {
+    for code in all_lint_codes() {
+        assert_eq!(LintCode::from(code.as_str()), code);
+    }
+    assert_eq!(
+        LintCode::from("missing_dco"),
+        LintCode::Other("missing_dco".into())
+    );
+}
+
+#[test]
+fn test_every_default_code_is_builtin() {
+    // every `pub const NAME: &str = "snake_case";` in default.rs but the diagnostic source is a code
+    let pattern = regex::Regex::new(r#"(?m)^pub const (\w+): &str = "([a-z_]+)";"#).unwrap();
+    let source = include_str!("default.rs");
+    let codes: Vec<&str> = pattern
+        .captures_iter(source)
+        .filter(|captures| &captures[1] != "ID")
+        .map(|captures| captures.get(2).unwrap().as_str())
+        .collect();
+    assert!(codes.contains(&default::SUBJECT_GITMOJI));
+    for code in codes {
+        let typed = LintCode::from(code);
+        assert!(
+            !matches!(typed, LintCode::Other(_)),
+            "{code} isn't a LintCode"
+        );
+        assert_eq!(typed.as_str(), code);
+    }
+}
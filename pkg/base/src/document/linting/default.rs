@@ -14,12 +14,71 @@ pub const BODY_LEADING_BLANK: &str = "body_leading_blank";
 pub const FOOTER_LEADING_BLANK: &str = "footer_leading_blank";
 pub const HEADER_MAX_LINE_LENGTH: &str = "header_max_line_length";
 pub const BODY_MAX_LINE_LENGTH: &str = "body_max_line_length";
+pub const TOTAL_MAX_LENGTH: &str = "total_max_length";
+pub const COMMENT_LEADING_BLANK: &str = "comment_leading_blank";
+/// trailers right after the subject, e.g. a bare `Signed-off-by`, often mean a forgotten body
+pub const TRAILER_WITHOUT_BODY: &str = "trailer_without_body";
 pub const SCOPE_EMPTY: &str = "scope_empty";
+/// scopes should be short identifiers, not sentences
+pub const SCOPE_MAX_WORDS: &str = "scope_max_words";
 pub const SUBJECT_EMPTY: &str = "subject_empty";
 pub const SUBJECT_LEADING_SPACE: &str = "missing_subject_leading_space";
+pub const SUBJECT_WRAPPED: &str = "subject_wrapped";
 pub const TYPE_ENUM: &str = "type_enum";
+/// https://www.conventionalcommits.org/en/v1.0.0/#specification, item 15
+pub const BREAKING_CHANGE_CASE: &str = "breaking_change_case";
+/// see https://git-scm.com/docs/git-interpret-trailers
+pub const TRAILER_SEPARATOR: &str = "trailer_separator";
+/// leftovers of a botched rebase or merge, e.g. `<<<<<<< HEAD`
+pub const CONFLICT_MARKERS: &str = "conflict_markers";
+/// see https://gerrit-review.googlesource.com/Documentation/user-changeid.html
+pub const CHANGE_ID_FORMAT: &str = "change_id_format";
+pub const MISSING_CHANGE_ID: &str = "missing_change_id";
+/// a two-stage alternative to `header_max_line_length`
+pub const SUBJECT_LENGTH_TIERS: &str = "subject_length_tiers";
+/// legacy Angular-style `type[scope]: ` rather than `type(scope): `
+pub const BRACKET_SCOPE: &str = "bracket_scope";
+/// https://commitlint.js.org/#/reference-rules?id=subject-full-stop
+pub const SUBJECT_FULL_STOP: &str = "subject_full_stop";
+/// links belong in the body, where they don't eat into the subject's length budget
+pub const SUBJECT_URL: &str = "subject_url";
+/// https://commitlint.js.org/#/reference-rules?id=type-min-length and `type-max-length`
+pub const TYPE_LENGTH: &str = "type_length";
+/// https://commitlint.js.org/#/reference-rules?id=scope-min-length and `scope-max-length`
+pub const SCOPE_LENGTH: &str = "scope_length";
+/// https://commitlint.js.org/#/reference-rules?id=subject-min-length and `subject-max-length`
+pub const SUBJECT_LENGTH: &str = "subject_length";
+/// https://commitlint.js.org/#/reference-rules?id=type-case
+pub const TYPE_CASE: &str = "type_case";
+/// https://commitlint.js.org/#/reference-rules?id=scope-case
+pub const SCOPE_CASE: &str = "scope_case";
+/// https://commitlint.js.org/#/reference-rules?id=subject-case
+pub const SUBJECT_CASE: &str = "subject_case";
+/// see https://gitmoji.dev
+pub const SUBJECT_GITMOJI: &str = "subject_gitmoji";
 use crate::LANGUAGE;
 
+/// lints that only read the subject line, so their results survive edits to the body. Lints whose
+/// fixes point into the body, like `subject_url`'s `Refs:` trailer, don't belong here: a body edit
+/// would leave a reused fix pointing at stale positions.
+pub const SUBJECT_LINTS: &[&str] = &[
+    TYPE_ENUM,
+    HEADER_MAX_LINE_LENGTH,
+    SUBJECT_EMPTY,
+    SUBJECT_LEADING_SPACE,
+    SCOPE_MAX_WORDS,
+    SUBJECT_LENGTH_TIERS,
+    BRACKET_SCOPE,
+    SUBJECT_FULL_STOP,
+    TYPE_LENGTH,
+    SCOPE_LENGTH,
+    SUBJECT_LENGTH,
+    TYPE_CASE,
+    SCOPE_CASE,
+    SUBJECT_CASE,
+    SUBJECT_GITMOJI,
+];
+
 pub const ENABLED_LINTS: &[&str] = &[
     TYPE_ENUM,
     BODY_LEADING_BLANK,
@@ -27,10 +86,22 @@ pub const ENABLED_LINTS: &[&str] = &[
     HEADER_MAX_LINE_LENGTH,
     SUBJECT_EMPTY,
     SUBJECT_LEADING_SPACE,
+    SUBJECT_WRAPPED,
+    BREAKING_CHANGE_CASE,
+    TRAILER_SEPARATOR,
+    SCOPE_MAX_WORDS,
 ];
 /// a suggested number from https://git-scm.com/docs/git-commit#_discussion ;
 /// GitHub also uses this number.
 pub const MAX_HEADER_LINE_LENGTH: u8 = 50;
+/// where GitHub stops showing the whole subject in most UIs
+pub const HARD_MAX_HEADER_LINE_LENGTH: u16 = 72;
+/// a generous default budget for a whole message; some hooks and tools choke on more.
+pub const MAX_TOTAL_LENGTH: u16 = 4096;
+/// the punctuation `subject_full_stop` forbids by default
+pub const DEFAULT_FULL_STOPS: &str = ".";
+/// enough for compound names like `deps-dev` or `ui_kit_v2`
+pub const MAX_SCOPE_WORDS: u16 = 3;
 
 lazy_static! {
     pub static ref LINT_SEVERITY: HashMap<&'static str, lsp_types::DiagnosticSeverity> = {
@@ -39,16 +110,41 @@ lazy_static! {
             // rule of thumb: if it's in the spec and we can't auto-fix it, it's an error
             // else, it's a warning
             (INVALID, Severity::ERROR),
+            (CONFLICT_MARKERS, Severity::ERROR), // the message is clearly broken
             (TYPE_ENUM, Severity::HINT), // not fixable, but not in the spec
             (HEADER_MAX_LINE_LENGTH, Severity::WARNING), // not in the spec
+            (TOTAL_MAX_LENGTH, Severity::WARNING), // not in the spec
             (BODY_LEADING_BLANK, Severity::WARNING), // fixable
             (FOOTER_LEADING_BLANK, Severity::WARNING), // fixable
+            (COMMENT_LEADING_BLANK, Severity::WARNING), // fixable
+            (TRAILER_WITHOUT_BODY, Severity::HINT), // sometimes intentional
             (SUBJECT_LEADING_SPACE, Severity::WARNING), // fixable
+            (SUBJECT_WRAPPED, Severity::WARNING), // fixable
+            (BREAKING_CHANGE_CASE, Severity::WARNING), // fixable
+            (TRAILER_SEPARATOR, Severity::WARNING), // fixable
+            (CHANGE_ID_FORMAT, Severity::ERROR), // gerrit rejects these
+            (MISSING_CHANGE_ID, Severity::ERROR), // gerrit rejects these
             (SCOPE_EMPTY, Severity::ERROR), // not fixable, probably unintentional
+            (SCOPE_MAX_WORDS, Severity::WARNING), // not in the spec
+            (BRACKET_SCOPE, Severity::WARNING), // fixable
+            (SUBJECT_FULL_STOP, Severity::WARNING), // fixable
+            (SUBJECT_URL, Severity::HINT), // advisory
+            (TYPE_LENGTH, Severity::WARNING), // not in the spec
+            (SCOPE_LENGTH, Severity::WARNING), // not in the spec
+            (SUBJECT_LENGTH, Severity::WARNING), // not in the spec
+            (SUBJECT_LENGTH_TIERS, Severity::WARNING), // overridden per-tier
+            (TYPE_CASE, Severity::WARNING), // not in the spec
+            (SCOPE_CASE, Severity::WARNING), // not in the spec
+            (SUBJECT_CASE, Severity::WARNING), // not in the spec
+            (SUBJECT_GITMOJI, Severity::WARNING), // not in the spec
             (SUBJECT_EMPTY, Severity::ERROR),
         ])
     };
 
+    /// a trailer-ish line, with loose separators: `Key: Value`, `Key:Value`, `Key : Value`
+    static ref LOOSE_TRAILER: regex::Regex =
+        regex::Regex::new(r"^(?P<key>BREAKING CHANGE|[A-Za-z][A-Za-z0-9-]*)(?P<sep>[ \t]*:[ \t]*)(?P<value>.*)$").unwrap();
+    static ref URL: regex::Regex = regex::Regex::new(r"https?://[^\s<>()\[\]]+").unwrap();
     static ref BAD_TRAILER_QUERY: tree_sitter::Query = tree_sitter::Query::new(
         &LANGUAGE,
         include_str!("./queries/bad_trailer.scm"),
@@ -74,6 +170,10 @@ pub fn check_body_leading_blank(doc: &GitCommitDocument, code: &str) -> Vec<lsp_
                         ),
                     );
                     lint.code = Some(lsp_types::NumberOrString::String(code.to_string()));
+                    // only a missing blank line is fixable; extra ones might be intentional
+                    if let Some(fix) = doc.subject_padding_fix() {
+                        utils::set_fix(&mut lint, vec![fix]);
+                    }
                     lints.push(lint);
                 }
                 break;
@@ -131,6 +231,38 @@ pub fn check_subject_line_length(
     lints
 }
 
+/// Warn about the part of the subject past `soft` chars, and add a hint on the part past `hard`
+/// chars, where UIs start truncating it. When both fire, the warning stops at `hard` so the two
+/// diagnostics don't overlap.
+pub fn check_subject_length_tiers(
+    doc: &GitCommitDocument,
+    code: &str,
+    soft: u16,
+    hard: u16,
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let Some(subject) = &doc.subject else {
+        return lints;
+    };
+    let line_number = subject.line_number as u32;
+    if let Some(mut lint) = check_line_length(&subject.line, line_number, code, hard, || {
+        format!("Subject line too long (max {hard} chars); it will be truncated")
+    }) {
+        lint.severity = Some(lsp_types::DiagnosticSeverity::HINT);
+        lints.push(lint);
+    }
+    if let Some(mut lint) = check_line_length(&subject.line, line_number, code, soft, || {
+        format!("Subject line longer than {soft} chars")
+    }) {
+        if hard > soft && !lints.is_empty() {
+            lint.range.end.character = hard as u32; // don't overlap the hint
+        }
+        lint.severity = Some(lsp_types::DiagnosticSeverity::WARNING);
+        lints.push(lint);
+    }
+    lints
+}
+
 pub fn check_body_line_length(
     doc: &GitCommitDocument,
     code: &str,
@@ -149,6 +281,428 @@ pub fn check_body_line_length(
     lints
 }
 
+/// Check the subject wasn't accidentally split across lines, i.e. the line right after the
+/// subject is neither blank, a comment, nor a trailer.
+pub fn check_subject_wrapped(doc: &GitCommitDocument, code: &str) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let Some(subject) = &doc.subject else {
+        return lints;
+    };
+    let subject_line_number = subject.line_number as usize;
+    let Some((line_number, line)) = doc.get_body().next() else {
+        return lints;
+    };
+    if line_number != subject_line_number + 1
+        || line.chars().all(|c| c.is_whitespace())
+        || is_trailer(doc, line_number, &line.to_string())
+    {
+        return lints;
+    }
+    let indent = line.chars().take_while(|c| c.is_whitespace()).count() as u32;
+    let subject_end = doc.code.line(subject_line_number).chars().count() as u32;
+    let mut lint = utils::make_line_diagnostic(
+        "Subject line appears to be wrapped onto the next line".into(),
+        line_number,
+        indent,
+        line.chars().count() as u32,
+    );
+    lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+    utils::set_fix(
+        &mut lint,
+        vec![lsp_types::TextEdit {
+            range: lsp_types::Range {
+                start: lsp_types::Position {
+                    line: subject_line_number as u32,
+                    character: subject_end,
+                },
+                end: lsp_types::Position {
+                    line: line_number as u32,
+                    character: indent,
+                },
+            },
+            new_text: " ".into(),
+        }],
+    );
+    lints.push(lint);
+    lints
+}
+
+#[test]
+fn test_subject_wrapped() {
+    let wrapped = GitCommitDocument::new().with_text("feat: add a very\nlong thing\n".into());
+    let lints = check_subject_wrapped(&wrapped, SUBJECT_WRAPPED);
+    assert_eq!(lints.len(), 1);
+    let fix = utils::get_fix(&lints[0]).unwrap();
+    assert_eq!(
+        fix[0].range.start.character,
+        "feat: add a very".len() as u32
+    );
+    assert_eq!(fix[0].range.end.line, 1);
+
+    let body = GitCommitDocument::new().with_text("feat: add a thing\n\nlong body\n".into());
+    assert!(check_subject_wrapped(&body, SUBJECT_WRAPPED).is_empty());
+    let comment = GitCommitDocument::new().with_text("feat: add a thing\n# comment\n".into());
+    assert!(check_subject_wrapped(&comment, SUBJECT_WRAPPED).is_empty());
+}
+
+/// Check `BREAKING CHANGE:` footers are uppercase, since tools ignore any other casing.
+pub fn check_breaking_change_case(
+    doc: &GitCommitDocument,
+    code: &str,
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    for (line_number, line) in doc.get_body() {
+        let line = line.to_string();
+        let key_len = "BREAKING CHANGE".len();
+        let Some(key) = line.get(..key_len) else {
+            continue;
+        };
+        let canonical = key.to_ascii_uppercase();
+        if key == canonical
+            || !(canonical == "BREAKING CHANGE" || canonical == "BREAKING-CHANGE")
+            || !line[key_len..].trim_start().starts_with(':')
+        {
+            continue;
+        }
+        let mut lint = utils::make_line_diagnostic(
+            format!("{key:?} should be uppercase: {canonical:?}"),
+            line_number,
+            0,
+            key_len as u32,
+        );
+        lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+        let range = lint.range;
+        utils::set_fix(
+            &mut lint,
+            vec![lsp_types::TextEdit {
+                range,
+                new_text: canonical,
+            }],
+        );
+        lints.push(lint);
+    }
+    lints
+}
+
+#[test]
+fn test_breaking_change_case() {
+    let lint = |text: &str| {
+        let doc = GitCommitDocument::new().with_text(format!("feat!: x\n\nbody\n\n{text}\n"));
+        check_breaking_change_case(&doc, BREAKING_CHANGE_CASE)
+    };
+    for bad in [
+        "Breaking change: x",
+        "breaking change: x",
+        "Breaking-Change: x",
+        "BREAKING change : x",
+    ] {
+        let lints = lint(bad);
+        assert_eq!(lints.len(), 1, "{bad}");
+        let fix = utils::get_fix(&lints[0]).unwrap();
+        assert_eq!(fix[0].new_text, bad[..15].to_ascii_uppercase());
+    }
+    for ok in [
+        "BREAKING CHANGE: x",
+        "BREAKING-CHANGE: x",
+        "breaking changes are fun",
+    ] {
+        assert!(lint(ok).is_empty(), "{ok}");
+    }
+}
+
+/// Check trailers separate their keys and values with exactly `: `, considering both the parsed
+/// trailers and a final paragraph made up of trailer-ish lines.
+pub fn check_trailer_separator(doc: &GitCommitDocument, code: &str) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let mut candidates: Vec<usize> = doc
+        .get_trailers_lines()
+        .into_iter()
+        .map(|line_number| line_number as usize)
+        .collect();
+    let body: Vec<(usize, String)> = doc
+        .get_body()
+        .map(|(line_number, line)| (line_number, line.to_string()))
+        .collect();
+    let last_paragraph = body
+        .iter()
+        .rposition(|(_, line)| line.trim().is_empty())
+        .map(|blank| &body[blank + 1..])
+        .unwrap_or_default();
+    if !last_paragraph.is_empty()
+        && last_paragraph
+            .iter()
+            .all(|(_, line)| LOOSE_TRAILER.is_match(line))
+    {
+        candidates.extend(last_paragraph.iter().map(|(line_number, _)| *line_number));
+    }
+    candidates.sort();
+    candidates.dedup();
+    for line_number in candidates {
+        let line = doc.code.line(line_number).to_string();
+        let Some(captures) = LOOSE_TRAILER.captures(&line) else {
+            continue;
+        };
+        let (sep, value) = (&captures["sep"], &captures["value"]);
+        if sep == ": " || value.is_empty() {
+            continue; // empty values are checked by `check_trailer_values`
+        }
+        let start = captures["key"].chars().count() as u32;
+        let mut lint = utils::make_line_diagnostic(
+            format!(
+                "Trailer {:?} should separate its key and value with \": \"",
+                &captures["key"]
+            ),
+            line_number,
+            start,
+            start + sep.chars().count() as u32,
+        );
+        lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+        let range = lint.range;
+        utils::set_fix(
+            &mut lint,
+            vec![lsp_types::TextEdit {
+                range,
+                new_text: ": ".into(),
+            }],
+        );
+        lints.push(lint);
+    }
+    lints
+}
+
+#[test]
+fn test_trailer_separator() {
+    let lint = |trailers: &str| {
+        let doc = GitCommitDocument::new().with_text(format!("feat: x\n\nbody\n\n{trailers}\n"));
+        check_trailer_separator(&doc, TRAILER_SEPARATOR)
+    };
+    let lints = lint("Refs:#1\nSigned-off-by : A U Thor <a@example.com>");
+    let fixes: Vec<_> = lints
+        .iter()
+        .map(|l| {
+            (
+                l.range.start.line,
+                l.range.start.character,
+                l.range.end.character,
+            )
+        })
+        .collect();
+    assert_eq!(fixes, vec![(4, 4, 5), (5, 13, 16)]);
+    assert_eq!(utils::get_fix(&lints[1]).unwrap()[0].new_text, ": ");
+    assert!(lint("Refs: #1\nSigned-off-by: A U Thor <a@example.com>").is_empty());
+    // prose isn't a trailer block
+    assert!(lint("Note:this paragraph\nis just prose").is_empty());
+}
+
+fn is_change_id(value: &str) -> bool {
+    value.len() == 41
+        && value.starts_with('I')
+        && value[1..]
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+}
+
+/// generate a Gerrit `Change-Id` value: `I` followed by 40 hex digits
+pub fn generate_change_id(seed: &str) -> String {
+    use std::hash::BuildHasher;
+    let state = std::collections::hash_map::RandomState::new();
+    let mut id = String::with_capacity(41);
+    id.push('I');
+    for round in 0u8.. {
+        let hash = state.hash_one((seed, round, std::time::SystemTime::now()));
+        id.push_str(&format!("{hash:016x}"));
+        if id.len() >= 41 {
+            break;
+        }
+    }
+    id.truncate(41);
+    id
+}
+
+/// Check any `Change-Id:` trailers are formatted as Gerrit expects
+pub fn check_change_id_format(doc: &GitCommitDocument, code: &str) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    for (range, key) in doc.get_trailer_keys() {
+        if !key.eq_ignore_ascii_case("Change-Id") {
+            continue;
+        }
+        let line = doc.code.line(range.start.line as usize).to_string();
+        let value = line[key.len()..].trim_start_matches(':').trim();
+        if !is_change_id(value) {
+            let mut lint = utils::make_line_diagnostic(
+                format!("Invalid Change-Id {value:?}: expected `I` followed by 40 hex digits"),
+                range.start.line as usize,
+                0,
+                line.chars().count() as u32,
+            );
+            lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+            lints.push(lint);
+        }
+    }
+    lints
+}
+
+/// Check there's a `Change-Id:` trailer
+pub fn check_missing_change_id(doc: &GitCommitDocument, code: &str) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let has_change_id = doc
+        .get_trailer_keys()
+        .iter()
+        .any(|(_, key)| key.eq_ignore_ascii_case("Change-Id"));
+    if let (false, Some(subject)) = (has_change_id, &doc.subject) {
+        let mut lint = utils::make_line_diagnostic(
+            "Missing required `Change-Id:` trailer".into(),
+            subject.line_number as usize,
+            0,
+            subject.line.chars().count() as u32,
+        );
+        lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+        lints.push(lint);
+    }
+    lints
+}
+
+#[test]
+fn test_change_id() {
+    let id = generate_change_id("feat: x");
+    assert!(is_change_id(&id), "{id}");
+    let valid = GitCommitDocument::new().with_text(format!("feat: x\n\nbody\n\nChange-Id: {id}\n"));
+    assert!(check_change_id_format(&valid, CHANGE_ID_FORMAT).is_empty());
+    assert!(check_missing_change_id(&valid, MISSING_CHANGE_ID).is_empty());
+
+    let invalid = GitCommitDocument::new().with_text("feat: x\n\nbody\n\nChange-Id: I123\n".into());
+    assert_eq!(check_change_id_format(&invalid, CHANGE_ID_FORMAT).len(), 1);
+    let missing = GitCommitDocument::new().with_text("feat: x\n\nbody\n".into());
+    assert_eq!(
+        check_missing_change_id(&missing, MISSING_CHANGE_ID).len(),
+        1
+    );
+}
+
+/// Check the subject's message starts with a gitmoji, either the emoji or its `:shortcode:`
+pub fn check_subject_gitmoji(doc: &GitCommitDocument, code: &str) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    if let Some(subject) = &doc.subject {
+        let message = subject.message_text();
+        let trimmed = message.trim_start();
+        if crate::gitmoji::leading(trimmed).is_none() {
+            let leading_space = message.chars().count() - trimmed.chars().count();
+            let start = subject.prefix_text().chars().count() + leading_space;
+            let end = start
+                + trimmed
+                    .split_whitespace()
+                    .next()
+                    .map_or(0, |w| w.chars().count());
+            let mut lint = utils::make_line_diagnostic(
+                "the subject should start with a gitmoji, e.g. `:sparkles:`".into(),
+                subject.line_number as usize,
+                start as u32,
+                end as u32,
+            );
+            lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+            lints.push(lint);
+        }
+    }
+    lints
+}
+
+#[test]
+fn test_subject_gitmoji() {
+    let check = |text: &str| {
+        let doc = GitCommitDocument::new().with_text(text.into());
+        check_subject_gitmoji(&doc, SUBJECT_GITMOJI)
+            .iter()
+            .map(|lint| lint.range.start.character..lint.range.end.character)
+            .collect::<Vec<_>>()
+    };
+    assert!(check("feat: :sparkles: add a thing\n").is_empty());
+    assert!(check("feat(ui): ✨ add a thing\n").is_empty());
+    assert_eq!(check("feat: add a thing\n"), vec![6..9]);
+    assert_eq!(check("fix: :sparkle: typo\n"), vec![5..14]);
+}
+
+/// Check the non-comment content of the message fits within `cutoff` characters, counting newlines.
+/// The diagnostic is attached to the last line of content.
+pub fn check_total_length(
+    doc: &GitCommitDocument,
+    code: &str,
+    cutoff: u16,
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    if cutoff == 0 {
+        return lints;
+    }
+    let mut total = 0usize;
+    let mut pending_newlines = 0usize; // trailing blank lines don't count
+    let mut last_line: Option<(usize, usize)> = None;
+    for (line_number, line) in doc.message_lines() {
+        if doc.is_comment(&line) {
+            continue;
+        }
+        if last_line.is_some() {
+            pending_newlines += 1;
+        }
+        if line.chars().any(|c| !c.is_whitespace()) {
+            let n_chars = line.chars().count();
+            total += pending_newlines + n_chars;
+            pending_newlines = 0;
+            last_line = Some((line_number, n_chars));
+        }
+    }
+    if let Some((line_number, n_chars)) = last_line {
+        if total > cutoff as usize {
+            let mut lint = utils::make_line_diagnostic(
+                format!("Message too long ({total} > {cutoff} chars)"),
+                line_number,
+                0,
+                n_chars as u32,
+            );
+            lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+            lints.push(lint);
+        }
+    }
+    lints
+}
+
+#[test]
+fn test_subject_length_tiers() {
+    let check = |n_chars: usize| {
+        let subject = format!("feat: {}", "x".repeat(n_chars - 6));
+        let doc = GitCommitDocument::new().with_text(subject);
+        check_subject_length_tiers(&doc, SUBJECT_LENGTH_TIERS, 50, 72)
+            .into_iter()
+            .map(|lint| {
+                let range = lint.range.start.character..lint.range.end.character;
+                (lint.severity.unwrap(), range)
+            })
+            .collect::<Vec<_>>()
+    };
+    use lsp_types::DiagnosticSeverity as Severity;
+    assert!(check(50).is_empty());
+    assert_eq!(check(51), vec![(Severity::WARNING, 50..51)]);
+    assert_eq!(check(72), vec![(Severity::WARNING, 50..72)]);
+    assert_eq!(
+        check(73),
+        vec![(Severity::HINT, 72..73), (Severity::WARNING, 50..72)]
+    );
+    // once both fire, the warning stops where the hint starts
+    assert_eq!(
+        check(80),
+        vec![(Severity::HINT, 72..80), (Severity::WARNING, 50..72)]
+    );
+}
+
+#[test]
+fn test_total_length() {
+    let doc = GitCommitDocument::new()
+        .with_text("feat: x\n\n0123456789\n\n# a long comment that doesn't count\n\n".into());
+    // "feat: x" + "\n\n" + "0123456789"
+    assert!(check_total_length(&doc, TOTAL_MAX_LENGTH, 19).is_empty());
+    let lints = check_total_length(&doc, TOTAL_MAX_LENGTH, 18);
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].range.start.line, 2);
+}
+
 /// Check that there's at least one leading blank before the trailers
 pub fn check_footer_leading_blank(
     doc: &GitCommitDocument,
@@ -165,11 +719,168 @@ pub fn check_footer_leading_blank(
             0,
         );
         lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+        if let Some(fix) = doc.trailer_padding_fix() {
+            utils::set_fix(&mut lint, vec![fix]);
+        }
+        lints.push(lint);
+    }
+    lints
+}
+
+/// whether `line` is a parsed trailer or a well-known trailer key the grammar missed
+fn is_trailer(doc: &GitCommitDocument, line_number: usize, line: &str) -> bool {
+    doc.get_trailers_lines().contains(&(line_number as u32))
+        || LOOSE_TRAILER.captures(line).is_some_and(|captures| {
+            crate::document::CANONICAL_TRAILER_KEYS
+                .iter()
+                .any(|key| key.eq_ignore_ascii_case(&captures["key"]))
+        })
+}
+
+/// Check some body text precedes the trailers, if any
+pub fn check_trailer_without_body(
+    doc: &GitCommitDocument,
+    code: &str,
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let Some((line_number, line)) = doc
+        .get_body()
+        .find(|(_, line)| line.chars().any(|c| !c.is_whitespace()))
+    else {
+        return lints;
+    };
+    let line = line.to_string();
+    if is_trailer(doc, line_number, &line) {
+        let mut lint = utils::make_line_diagnostic(
+            "Trailers without a body; explain the change before the trailers.".into(),
+            line_number,
+            0,
+            line.trim_end().chars().count() as u32,
+        );
+        lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+        lints.push(lint);
+    }
+    lints
+}
+
+#[test]
+fn test_trailer_without_body() {
+    let check = |text: &str| -> Vec<u32> {
+        let doc = GitCommitDocument::new().with_text(text.into());
+        check_trailer_without_body(&doc, TRAILER_WITHOUT_BODY)
+            .iter()
+            .map(|lint| lint.range.start.line)
+            .collect()
+    };
+    assert_eq!(check("feat: x\n\nSigned-off-by: A <a@b.c>\n"), vec![2]);
+    assert!(check("feat: x\n\nwhy\n\nSigned-off-by: A <a@b.c>\n").is_empty());
+    assert!(check("feat: x\n\nwhy\n").is_empty());
+    assert_eq!(check("feat: x\nSigned-off-by: A <a@b.c>\n"), vec![1]);
+    // one diagnostic per problem: `subject_wrapped` leaves this to us
+    let doc = GitCommitDocument::new().with_text("feat: x\nSigned-off-by: A <a@b.c>\n".into());
+    assert!(check_subject_wrapped(&doc, SUBJECT_WRAPPED).is_empty());
+}
+
+/// Check there's a blank line between the message and git's trailing comment block
+pub fn check_comment_leading_blank(
+    doc: &GitCommitDocument,
+    code: &str,
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    if let Some(comment_start) = doc.get_missing_comment_padding_line() {
+        let previous_line = comment_start - 1;
+        let mut lint = utils::make_line_diagnostic(
+            "Missing blank line before comments.".into(),
+            previous_line,
+            0,
+            doc.code.line(previous_line).chars().count() as u32,
+        );
+        lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+        if let Some(fix) = doc.comment_padding_fix() {
+            utils::set_fix(&mut lint, vec![fix]);
+        }
         lints.push(lint);
     }
     lints
 }
 
+#[test]
+fn test_comment_leading_blank() {
+    let doc = GitCommitDocument::new().with_text("feat: x\n\nbody\n# comment\n".into());
+    let lints = check_comment_leading_blank(&doc, COMMENT_LEADING_BLANK);
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].range.start.line, 2);
+    let options = crate::document::FormatOptions {
+        comment_leading_blank: true,
+        ..Default::default()
+    };
+    assert!(doc
+        .format(&options)
+        .iter()
+        .any(|edit| edit.range.start.line == 3 && edit.new_text == "\n"));
+    assert!(!doc
+        .format(&Default::default())
+        .iter()
+        .any(|edit| edit.range.start.line == 3));
+
+    let padded = GitCommitDocument::new().with_text("feat: x\n\nbody\n\n# comment\n".into());
+    assert!(check_comment_leading_blank(&padded, COMMENT_LEADING_BLANK).is_empty());
+    let no_comments = GitCommitDocument::new().with_text("feat: x\n\nbody\n".into());
+    assert!(check_comment_leading_blank(&no_comments, COMMENT_LEADING_BLANK).is_empty());
+}
+
+/// Check for git's merge-conflict markers. `=======` alone could be a heading's underline, so it's
+/// only flagged alongside other markers.
+pub(crate) fn check_conflict_markers(doc: &GitCommitDocument) -> Vec<lsp_types::Diagnostic> {
+    let is_marker = |line: &str, marker: &str| {
+        line.strip_prefix(marker)
+            .map(|rest| rest.is_empty() || rest.starts_with(' '))
+            .unwrap_or(false)
+    };
+    let markers: Vec<(usize, String)> = doc
+        .message_lines()
+        .map(|(line_number, line)| (line_number, line.to_string()))
+        .filter(|(_, line)| {
+            ["<<<<<<<", "|||||||", "=======", ">>>>>>>"]
+                .iter()
+                .any(|marker| is_marker(line, marker))
+        })
+        .collect();
+    if markers.iter().all(|(_, line)| line == "=======") {
+        return vec![];
+    }
+    markers
+        .into_iter()
+        .map(|(line_number, line)| {
+            let mut lint = utils::make_line_diagnostic(
+                "Merge-conflict marker in the commit message".into(),
+                line_number,
+                0,
+                line.chars().count() as u32,
+            );
+            lint.code = Some(lsp_types::NumberOrString::String(CONFLICT_MARKERS.into()));
+            lint.severity = Some(lsp_types::DiagnosticSeverity::ERROR);
+            lint
+        })
+        .collect()
+}
+
+#[test]
+fn test_conflict_markers() {
+    let doc = GitCommitDocument::new().with_text(
+        "<<<<<<< HEAD\nfeat: ours\n=======\nfeat: theirs\n>>>>>>> abc123 (feat: theirs)\n".into(),
+    );
+    let lines: Vec<u32> = doc
+        .get_mandatory_lints()
+        .iter()
+        .filter(|l| l.code == Some(lsp_types::NumberOrString::String(CONFLICT_MARKERS.into())))
+        .map(|l| l.range.start.line)
+        .collect();
+    assert_eq!(lines, vec![0, 2, 4]);
+    let heading = GitCommitDocument::new().with_text("feat: x\n\nNotes\n=======\n".into());
+    assert!(check_conflict_markers(&heading).is_empty());
+}
+
 /// Check all trailers have both a key and a value
 pub(crate) fn check_trailer_values(doc: &GitCommitDocument) -> Vec<lsp_types::Diagnostic> {
     utils::query_lint(
@@ -211,6 +922,598 @@ pub(crate) fn check_type_enum(doc: &GitCommitDocument, code: &str) -> Vec<lsp_ty
     lints
 }
 
+/// Flag scopes that read like a message: ones containing spaces or more than `max_words` words,
+/// counting `-` and `_` as word separators.
+pub fn check_scope_max_words(
+    doc: &GitCommitDocument,
+    code: &str,
+    max_words: u16,
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let Some(subject) = &doc.subject else {
+        return lints;
+    };
+    let scope_text = subject.scope_text();
+    let scope = subject.scope_name();
+    let n_words = scope
+        .split(|c: char| c.is_whitespace() || c == '-' || c == '_' || c == ',')
+        .filter(|word| !word.is_empty())
+        .count();
+    if n_words as u16 > max_words || scope.trim().contains(char::is_whitespace) {
+        let start = subject.type_text().chars().count() as u32;
+        let mut lint = utils::make_line_diagnostic(
+            format!(
+                "Scopes should be short identifiers (at most {max_words} words without spaces); \
+                move the description into the subject."
+            ),
+            subject.line_number as usize,
+            start,
+            start + scope_text.chars().count() as u32,
+        );
+        lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+        lints.push(lint);
+    }
+    lints
+}
+
+#[test]
+fn test_scope_max_words() {
+    let check = |text: &str| {
+        let doc = GitCommitDocument::new().with_text(text.into());
+        check_scope_max_words(&doc, SCOPE_MAX_WORDS, MAX_SCOPE_WORDS).len()
+    };
+    assert_eq!(check("fix(this is actually a sentence): x\n"), 1);
+    assert_eq!(check("fix(this-is-actually-a-sentence): x\n"), 1);
+    assert_eq!(check("fix(web ui): x\n"), 1);
+    assert_eq!(check("fix(deps-dev): x\n"), 0);
+    assert_eq!(check("fix(parser): x\n"), 0);
+    assert_eq!(check("fix: x\n"), 0);
+}
+
+/// Flag a subject ending in any of `stops`, e.g. `feat: add a thing.`
+pub fn check_subject_full_stop(
+    doc: &GitCommitDocument,
+    code: &str,
+    stops: &str,
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let Some(subject) = &doc.subject else {
+        return lints;
+    };
+    let message = subject.message_text().trim_end();
+    let Some(stop) = message.chars().last().filter(|c| stops.contains(*c)) else {
+        return lints;
+    };
+    let end = (subject.prefix_text().chars().count() + message.chars().count()) as u32;
+    let mut lint = utils::make_line_diagnostic(
+        format!("Subject should not end with {stop:?}"),
+        subject.line_number as usize,
+        end - 1,
+        end,
+    );
+    lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+    let range = lint.range;
+    utils::set_fix(
+        &mut lint,
+        vec![lsp_types::TextEdit {
+            range,
+            new_text: String::new(),
+        }],
+    );
+    lints.push(lint);
+    lints
+}
+
+#[test]
+fn test_subject_full_stop() {
+    let check = |text: &str, stops: &str| -> Vec<(u32, u32)> {
+        let doc = GitCommitDocument::new().with_text(text.into());
+        check_subject_full_stop(&doc, SUBJECT_FULL_STOP, stops)
+            .iter()
+            .map(|lint| (lint.range.start.character, lint.range.end.character))
+            .collect()
+    };
+    assert_eq!(
+        check("feat: add a thing.\n", DEFAULT_FULL_STOPS),
+        vec![(17, 18)]
+    );
+    assert_eq!(
+        check("feat: add a thing. \n", DEFAULT_FULL_STOPS),
+        vec![(17, 18)]
+    );
+    assert!(check("feat: add a thing?\n", DEFAULT_FULL_STOPS).is_empty());
+    assert_eq!(check("feat: add a thing?\n", ".?!"), vec![(17, 18)]);
+    assert!(check("feat: add a thing\n", ".?!").is_empty());
+    assert!(check("feat: \n", ".?!").is_empty());
+
+    let doc = GitCommitDocument::new().with_text("feat: add a thing.\n".into());
+    let format = |stops: &str| {
+        let options = crate::document::FormatOptions {
+            subject_full_stops: stops.into(),
+            ..Default::default()
+        };
+        doc.format(&options)[0].new_text.clone()
+    };
+    assert_eq!(format(""), "feat: add a thing.");
+    assert_eq!(format(DEFAULT_FULL_STOPS), "feat: add a thing");
+}
+
+/// Flag a URL in the subject, offering to move it to a `Refs:` trailer.
+pub fn check_subject_url(doc: &GitCommitDocument, code: &str) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let Some(subject) = &doc.subject else {
+        return lints;
+    };
+    let message = subject.message_text();
+    let Some(found) = URL.find(message) else {
+        return lints;
+    };
+    // sentence punctuation probably isn't part of the link
+    let url = found
+        .as_str()
+        .trim_end_matches(['.', ',', ';', ':', '!', '?']);
+    let url_start = found.start();
+    let prefix_chars = subject.prefix_text().chars().count();
+    let start = (prefix_chars + message[..url_start].chars().count()) as u32;
+    let end = start + url.chars().count() as u32;
+    let line_number = subject.line_number as u32;
+    let mut lint = utils::make_line_diagnostic(
+        "Move links out of the subject and into the body".into(),
+        line_number as usize,
+        start,
+        end,
+    );
+    lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+    let position = |line: u32, character: u32| lsp_types::Position { line, character };
+    // take the whitespace before the link with it
+    let n_whitespace = message[..url_start]
+        .chars()
+        .rev()
+        .take_while(|c| c.is_whitespace())
+        .count() as u32;
+    let mut edits = vec![lsp_types::TextEdit {
+        range: lsp_types::Range::new(
+            position(line_number, start - n_whitespace),
+            position(line_number, end),
+        ),
+        new_text: String::new(),
+    }];
+    let last = doc
+        .get_body()
+        .filter(|(_, line)| line.chars().any(|c| !c.is_whitespace()))
+        .last();
+    let (line, text, separator) = match last {
+        Some((n, text)) if doc.get_trailers_lines().contains(&(n as u32)) => {
+            (n as u32, text.to_string(), "\n")
+        }
+        Some((n, text)) => (n as u32, text.to_string(), "\n\n"),
+        None => (line_number, subject.line.clone(), "\n\n"),
+    };
+    let eol = position(
+        line,
+        text.trim_end_matches(['\r', '\n']).chars().count() as u32,
+    );
+    edits.push(lsp_types::TextEdit {
+        range: lsp_types::Range::new(eol, eol),
+        new_text: format!("{separator}Refs: {url}"),
+    });
+    utils::set_fix(&mut lint, edits);
+    lints.push(lint);
+    lints
+}
+
+#[test]
+fn test_subject_url() {
+    let fix = |text: &str| -> Option<Vec<String>> {
+        let doc = GitCommitDocument::new().with_text(text.into());
+        let lints = check_subject_url(&doc, SUBJECT_URL);
+        assert!(lints.len() <= 1);
+        let edits = utils::get_fix(lints.first()?)?;
+        Some(
+            edits
+                .iter()
+                .map(|e| {
+                    let (start, end) = (e.range.start, e.range.end);
+                    format!(
+                        "{}:{}-{}:{} {:?}",
+                        start.line, start.character, end.line, end.character, e.new_text
+                    )
+                })
+                .collect(),
+        )
+    };
+    assert_eq!(fix("fix: handle timeouts\n"), None);
+    assert_eq!(
+        fix("fix: handle timeouts, see https://example.com/issues/1.\n"),
+        Some(vec![
+            "0:25-0:54 \"\"".into(),
+            "0:55-0:55 \"\\n\\nRefs: https://example.com/issues/1\"".into(),
+        ])
+    );
+    assert_eq!(
+        fix("fix: handle https://example.com/x timeouts\n\nbody\n\nSigned-off-by: A <a@b.c>\n")
+            .unwrap()[1],
+        "4:24-4:24 \"\\nRefs: https://example.com/x\""
+    );
+    assert_eq!(
+        fix("fix: handle https://example.com/x timeouts\n\nbody\n# a comment\n").unwrap()[1],
+        "2:4-2:4 \"\\n\\nRefs: https://example.com/x\""
+    );
+}
+
+/// Flag `type[scope]: ` with a fix to the canonical `type(scope): `.
+pub fn check_bracket_scope(doc: &GitCommitDocument, code: &str) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let Some(subject) = doc.subject.as_ref().filter(|s| s.scope_is_bracketed()) else {
+        return lints;
+    };
+    let scope_text = subject.scope_text();
+    let start = subject.type_text().chars().count() as u32;
+    let mut lint = utils::make_line_diagnostic(
+        "Scopes should be wrapped in parentheses, not brackets".into(),
+        subject.line_number as usize,
+        start,
+        start + scope_text.chars().count() as u32,
+    );
+    lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+    let range = lint.range;
+    let scope = &scope_text[1..scope_text.len() - 1];
+    utils::set_fix(
+        &mut lint,
+        vec![lsp_types::TextEdit {
+            range,
+            new_text: format!("({scope})"),
+        }],
+    );
+    lints.push(lint);
+    lints
+}
+
+#[test]
+fn test_bracket_scope() {
+    let doc = GitCommitDocument::new().with_text("feat[api]: add a thing\n".into());
+    assert!(doc.get_mandatory_lints().is_empty());
+    let lints = check_bracket_scope(&doc, BRACKET_SCOPE);
+    assert_eq!(lints.len(), 1);
+    let fix = utils::get_fix(&lints[0]).unwrap();
+    assert_eq!(fix[0].new_text, "(api)");
+    assert_eq!(
+        (fix[0].range.start.character, fix[0].range.end.character),
+        (4, 9)
+    );
+    // formatting only rewrites brackets when asked to
+    let formatted = doc.format(&Default::default());
+    assert_eq!(formatted[0].new_text, "feat[api]: add a thing");
+    let formatted = doc.format(&crate::document::FormatOptions {
+        parenthesize_bracket_scopes: true,
+        ..Default::default()
+    });
+    assert_eq!(formatted[0].new_text, "feat(api): add a thing");
+    let doc = GitCommitDocument::new().with_text("feat(api): add a thing\n".into());
+    assert!(check_bracket_scope(&doc, BRACKET_SCOPE).is_empty());
+}
+
+/// a part of the subject line that length lints measure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Component {
+    Type,
+    /// the scope, without its parentheses
+    Scope,
+    /// the message after the `: `
+    Subject,
+}
+
+impl Component {
+    /// the component's trimmed text and the char offset where it starts
+    fn locate<'a>(&self, subject: &'a crate::document::subject::Subject) -> (u32, &'a str) {
+        let (start, text) = match self {
+            Component::Type => (0, subject.type_text()),
+            Component::Scope => {
+                let scope_text = subject.scope_text();
+                let start = subject.type_text().chars().count();
+                let (start, inner) = match scope_text.strip_prefix(['(', '[']) {
+                    Some(inner) => (start + 1, inner),
+                    None => (start, scope_text),
+                };
+                (start, inner.strip_suffix([')', ']']).unwrap_or(inner))
+            }
+            Component::Subject => (
+                subject.prefix_text().chars().count(),
+                subject.message_text(),
+            ),
+        };
+        let trimmed = text.trim_start();
+        let start = start + text.chars().count() - trimmed.chars().count();
+        (start as u32, trimmed.trim_end())
+    }
+}
+
+impl std::fmt::Display for Component {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Component::Type => "Type",
+            Component::Scope => "Scope",
+            Component::Subject => "Subject",
+        })
+    }
+}
+
+/// Flag a `component` shorter than `min` or longer than `max` chars. Missing components are left
+/// to `subject_empty` and `missing_scope`.
+pub fn check_component_length(
+    doc: &GitCommitDocument,
+    code: &str,
+    component: Component,
+    min: Option<u16>,
+    max: Option<u16>,
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let Some(subject) = &doc.subject else {
+        return lints;
+    };
+    let (start, text) = component.locate(subject);
+    let length = text.chars().count();
+    if length == 0 {
+        return lints;
+    }
+    let message = match (min, max) {
+        (Some(min), Some(max)) if min == max && length != min as usize => {
+            format!("{component} should be exactly {min} chars, not {length}")
+        }
+        (Some(min), _) if length < min as usize => {
+            format!("{component} should be at least {min} chars, not {length}")
+        }
+        (_, Some(max)) if length > max as usize => {
+            format!("{component} should be at most {max} chars, not {length}")
+        }
+        _ => return lints,
+    };
+    let mut lint = utils::make_line_diagnostic(
+        message,
+        subject.line_number as usize,
+        start,
+        start + length as u32,
+    );
+    lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+    lints.push(lint);
+    lints
+}
+
+#[test]
+fn test_component_length() {
+    let check = |text: &str, component, min, max| -> Vec<(u32, u32, String)> {
+        let doc = GitCommitDocument::new().with_text(text.into());
+        check_component_length(&doc, "length", component, min, max)
+            .into_iter()
+            .map(|lint| {
+                (
+                    lint.range.start.character,
+                    lint.range.end.character,
+                    lint.message,
+                )
+            })
+            .collect()
+    };
+    let text = "feat(ui): add a thing\n";
+    assert_eq!(
+        check(text, Component::Type, Some(5), None),
+        vec![(0, 4, "Type should be at least 5 chars, not 4".into())]
+    );
+    assert!(check(text, Component::Type, Some(4), Some(4)).is_empty());
+    assert_eq!(
+        check(text, Component::Type, Some(3), Some(3)),
+        vec![(0, 4, "Type should be exactly 3 chars, not 4".into())]
+    );
+    assert_eq!(
+        check(text, Component::Scope, Some(3), Some(10)),
+        vec![(5, 7, "Scope should be at least 3 chars, not 2".into())]
+    );
+    assert_eq!(
+        check(text, Component::Subject, None, Some(5)),
+        vec![(10, 21, "Subject should be at most 5 chars, not 11".into())]
+    );
+    assert!(check(text, Component::Subject, Some(1), Some(72)).is_empty());
+    // a missing scope or message isn't too short
+    assert!(check("feat: add a thing\n", Component::Scope, Some(3), None).is_empty());
+    assert!(check("feat: \n", Component::Subject, Some(3), None).is_empty());
+}
+
+/// a casing convention for the type, scope, or subject
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    /// `lowercase`
+    Lower,
+    /// `kebab-case`
+    Kebab,
+    /// `camelCase`
+    Camel,
+    /// `PascalCase`
+    Pascal,
+    /// `Sentence case`
+    Sentence,
+}
+
+impl Case {
+    pub fn matches(&self, text: &str) -> bool {
+        let alphanumeric = |c: char| c.is_alphanumeric();
+        match self {
+            Case::Lower => text == text.to_lowercase(),
+            Case::Kebab => {
+                text == text.to_lowercase()
+                    && text
+                        .split('-')
+                        .all(|word| !word.is_empty() && word.chars().all(alphanumeric))
+            }
+            Case::Camel | Case::Pascal => {
+                let mut chars = text.chars();
+                chars.next().is_some_and(|first| {
+                    first.is_alphabetic() && first.is_uppercase() == (*self == Case::Pascal)
+                }) && chars.all(alphanumeric)
+            }
+            Case::Sentence => {
+                let mut chars = text.chars();
+                chars.next().is_some_and(|first| !first.is_lowercase())
+                    && chars.as_str() == chars.as_str().to_lowercase()
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Case {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Case::Lower => "lower case",
+            Case::Kebab => "kebab-case",
+            Case::Camel => "camelCase",
+            Case::Pascal => "PascalCase",
+            Case::Sentence => "Sentence case",
+        })
+    }
+}
+
+/// flag `text`, found at `start` on the subject line, unless it's in `case`
+fn check_case(
+    subject: &crate::document::subject::Subject,
+    code: &str,
+    case: Case,
+    what: &str,
+    text: &str,
+    start: u32,
+) -> Option<lsp_types::Diagnostic> {
+    if text.is_empty() || case.matches(text) {
+        return None;
+    }
+    let mut lint = utils::make_line_diagnostic(
+        format!("{what} {text:?} should be {case}"),
+        subject.line_number as usize,
+        start,
+        start + text.chars().count() as u32,
+    );
+    lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+    Some(lint)
+}
+
+pub fn check_type_case(
+    doc: &GitCommitDocument,
+    code: &str,
+    case: Case,
+) -> Vec<lsp_types::Diagnostic> {
+    let Some(subject) = &doc.subject else {
+        return vec![];
+    };
+    let type_text = subject.type_text();
+    let start = (type_text.chars().count() - type_text.trim_start().chars().count()) as u32;
+    check_case(subject, code, case, "type", type_text.trim(), start)
+        .into_iter()
+        .collect()
+}
+
+/// check each of a scope's comma- or slash-separated parts, e.g. `api` and `web` in `(api,web)`
+pub fn check_scope_case(
+    doc: &GitCommitDocument,
+    code: &str,
+    case: Case,
+) -> Vec<lsp_types::Diagnostic> {
+    let Some(subject) = &doc.subject else {
+        return vec![];
+    };
+    // just past the opening paren
+    let mut start = subject.type_text().chars().count() as u32 + 1;
+    let scope = subject.scope_name();
+    let mut lints = vec![];
+    for part in scope.split([',', '/']) {
+        let trimmed = part.trim_start();
+        let offset = (part.chars().count() - trimmed.chars().count()) as u32;
+        lints.extend(check_case(
+            subject,
+            code,
+            case,
+            "scope",
+            trimmed.trim_end(),
+            start + offset,
+        ));
+        start += part.chars().count() as u32 + 1;
+    }
+    lints
+}
+
+pub fn check_subject_case(
+    doc: &GitCommitDocument,
+    code: &str,
+    case: Case,
+) -> Vec<lsp_types::Diagnostic> {
+    let Some(subject) = &doc.subject else {
+        return vec![];
+    };
+    let Some((start, _)) = utils::first_word(subject) else {
+        return vec![];
+    };
+    let message = subject.message_text().trim();
+    check_case(subject, code, case, "subject", message, start)
+        .into_iter()
+        .collect()
+}
+
+#[test]
+fn test_case() {
+    let cases = [
+        Case::Lower,
+        Case::Kebab,
+        Case::Camel,
+        Case::Pascal,
+        Case::Sentence,
+    ];
+    let matching = |text: &str| -> Vec<Case> {
+        cases
+            .into_iter()
+            .filter(|case| case.matches(text))
+            .collect()
+    };
+    assert_eq!(
+        matching("feat"),
+        vec![Case::Lower, Case::Kebab, Case::Camel]
+    );
+    assert_eq!(matching("ui-kit"), vec![Case::Lower, Case::Kebab]);
+    assert_eq!(matching("uiKit"), vec![Case::Camel]);
+    assert_eq!(matching("UiKit"), vec![Case::Pascal]);
+    assert_eq!(matching("Feat"), vec![Case::Pascal, Case::Sentence]);
+    assert_eq!(matching("Add a thing"), vec![Case::Sentence]);
+    assert_eq!(matching("add a thing"), vec![Case::Lower]);
+    assert_eq!(matching("add a-thing"), vec![Case::Lower]);
+}
+
+#[test]
+fn test_check_case() {
+    let ranges = |lints: Vec<lsp_types::Diagnostic>| -> Vec<(u32, u32)> {
+        lints
+            .iter()
+            .map(|lint| (lint.range.start.character, lint.range.end.character))
+            .collect()
+    };
+    let doc = GitCommitDocument::new().with_text("Feat(api,Web): Add a thing\n".into());
+    assert_eq!(
+        ranges(check_type_case(&doc, TYPE_CASE, Case::Lower)),
+        vec![(0, 4)]
+    );
+    assert!(check_type_case(&doc, TYPE_CASE, Case::Pascal).is_empty());
+    assert_eq!(
+        ranges(check_scope_case(&doc, SCOPE_CASE, Case::Kebab)),
+        vec![(9, 12)]
+    );
+    assert_eq!(
+        ranges(check_scope_case(&doc, SCOPE_CASE, Case::Pascal)),
+        vec![(5, 8)]
+    );
+    assert_eq!(
+        ranges(check_subject_case(&doc, SUBJECT_CASE, Case::Lower)),
+        vec![(15, 26)]
+    );
+    assert!(check_subject_case(&doc, SUBJECT_CASE, Case::Sentence).is_empty());
+    let doc = GitCommitDocument::new().with_text("feat: add a thing\n".into());
+    assert!(check_scope_case(&doc, SCOPE_CASE, Case::Kebab).is_empty());
+    assert!(check_subject_case(&doc, SUBJECT_CASE, Case::Lower).is_empty());
+}
+
 pub fn check_subject_leading_space(
     doc: &GitCommitDocument,
     code: &str,
@@ -220,6 +1523,7 @@ pub fn check_subject_leading_space(
         let message = subject.message_text();
         let n_whitespace = message.chars().take_while(|c| c.is_whitespace()).count();
         if n_whitespace != 1 || !message.starts_with(' ') {
+            // right after the colon, whatever the shape of the prefix
             let start = subject.prefix_text().chars().count() as u32;
             let mut lint = utils::make_line_diagnostic(
                 "message should start with 1 space".into(),
@@ -228,13 +1532,49 @@ pub fn check_subject_leading_space(
                 start + n_whitespace as u32,
             );
             lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+            let range = lint.range;
+            utils::set_fix(
+                &mut lint,
+                vec![lsp_types::TextEdit {
+                    range,
+                    new_text: " ".into(),
+                }],
+            );
             lints.push(lint);
-            //
         }
     }
     lints
 }
 
+#[test]
+fn test_subject_leading_space() {
+    for (subject, colon) in [
+        ("feat:no-space", 5),
+        ("feat(api):no-space", 10),
+        ("feat!:no-space", 6),
+        ("feat(api)!:no-space", 11),
+    ] {
+        let doc = GitCommitDocument::new().with_text(format!("{subject}\n"));
+        let lints = check_subject_leading_space(&doc, SUBJECT_LEADING_SPACE);
+        assert_eq!(lints.len(), 1, "{subject}");
+        let fix = utils::get_fix(&lints[0]).unwrap();
+        assert_eq!(
+            (fix[0].range.start.character, fix[0].range.end.character),
+            (colon, colon),
+            "{subject}"
+        );
+        assert_eq!(fix[0].new_text, " ");
+    }
+    let doc = GitCommitDocument::new().with_text("feat(api)!:   extra spaces\n".into());
+    let fix = utils::get_fix(&check_subject_leading_space(&doc, SUBJECT_LEADING_SPACE)[0]).unwrap();
+    assert_eq!(
+        (fix[0].range.start.character, fix[0].range.end.character),
+        (11, 14)
+    );
+    let doc = GitCommitDocument::new().with_text("feat(api)!: ok\n".into());
+    assert!(check_subject_leading_space(&doc, SUBJECT_LEADING_SPACE).is_empty());
+}
+
 pub fn check_subject_empty(doc: &GitCommitDocument, code: &str) -> Vec<lsp_types::Diagnostic> {
     let mut lints = vec![];
     if let Some(subject) = &doc.subject {
@@ -3,10 +3,13 @@
 use std::{path::PathBuf, sync::Arc};
 
 use super::GitCommitDocument;
+pub mod codes;
 pub mod default;
 pub mod utils;
 /// a fatal parse error according to the conventional commit spec
 pub const INVALID: &str = "INVALID";
+/// the `LintConfig::source` of a config that wasn't loaded from anywhere
+pub const DEFAULT_SOURCE: &str = "<default>";
 
 /// a lint-fn is a test that can return zero to many logically equivalent diagnostics
 /// differentiated by a message: e.g. `[line-too-long, line-too-short]`
@@ -15,7 +18,7 @@ pub type LintFn<'cfg> = dyn Fn(&GitCommitDocument) -> Vec<lsp_types::Diagnostic>
 pub trait LintConfig {
     /// provides information to the user about where the lint configuration came from.
     fn source(&self) -> &str {
-        "<default>"
+        DEFAULT_SOURCE
     }
     fn worktree_root(&self) -> Option<PathBuf>;
     fn enabled_lint_codes(&self) -> Vec<&str> {
@@ -27,11 +30,32 @@ pub trait LintConfig {
             .unwrap_or(&lsp_types::DiagnosticSeverity::WARNING)
     }
 
+    /// the newline used by lints' fixes
+    fn line_ending(&self) -> super::LineEnding {
+        super::LineEnding::Auto
+    }
+
+    /// whether `code`'s lint only reads the subject line, so its results can be reused until the
+    /// subject changes
+    fn is_subject_lint(&self, code: &str) -> bool {
+        default::SUBJECT_LINTS.contains(&code)
+    }
+
+    /// report every lint as at most information, e.g. for contributors who find red squiggles
+    /// disruptive. Since `lint` applies this, `check` and `lint` exit zero when it's set; their
+    /// `--suggestions-only` flag instead clamps only what they print.
+    fn suggestions_only(&self) -> bool {
+        false
+    }
+
     // fn lint_tests(&self) -> &HashMap<&str, Box<LintFn>>;
-    fn get_test(&self, code: &str) -> Option<&Arc<LintFn>>;
+    fn get_test(&self, code: &str) -> Option<&Arc<LintFn<'_>>>;
     fn lint(&self, doc: &GitCommitDocument) -> Vec<lsp_types::Diagnostic> {
         log_debug!("linting document: {}", doc.code);
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
         let mut diagnostics = doc.get_mandatory_lints();
+        let reuse_subject_lints = doc.subject_unchanged.replace(false);
         log_debug!(
             "mandatory diagnostics: {:?}",
             diagnostics
@@ -49,9 +73,32 @@ pub trait LintConfig {
                     if test.is_none() {
                         log_debug!("Missing test for code {:?}", code);
                     }
-                    test
+                    test.map(|test| (code, test))
+                })
+                .map(|(code, f)| {
+                    span!(tracing::Level::DEBUG, "lint", code = code);
+                    #[cfg(feature = "tracing")]
+                    let started = std::time::Instant::now();
+                    let is_subject_lint = self.is_subject_lint(code);
+                    if reuse_subject_lints && is_subject_lint {
+                        if let Some(cached) = doc.subject_lint_cache.borrow().get(*code) {
+                            log_debug!("reusing subject lint {}", code);
+                            return cached.clone();
+                        }
+                    }
+                    let diagnostics = f(doc);
+                    if is_subject_lint {
+                        doc.subject_lint_cache
+                            .borrow_mut()
+                            .insert(code.to_string(), diagnostics.clone());
+                    }
+                    log_debug!(
+                        micros = started.elapsed().as_micros() as u64,
+                        "ran lint {}",
+                        code
+                    );
+                    diagnostics
                 })
-                .map(|f| f(doc))
                 .map(|mut v| {
                     for diagnostic in v.iter_mut() {
                         if diagnostic.severity.is_none() {
@@ -74,6 +121,24 @@ pub trait LintConfig {
                 })
                 .unwrap_or(vec![]),
         );
+        let newline = doc.newline(self.line_ending());
+        if newline != "\n" {
+            for diagnostic in diagnostics.iter_mut() {
+                if let Some(mut fix) = utils::get_fix(diagnostic) {
+                    fix.iter_mut().for_each(|edit| {
+                        edit.new_text = super::convert_newlines(&edit.new_text, newline)
+                    });
+                    utils::set_fix(diagnostic, fix);
+                }
+            }
+        }
+        if self.suggestions_only() {
+            diagnostics.iter_mut().for_each(utils::as_suggestion);
+        }
+        log_debug!(
+            micros = started.elapsed().as_micros() as u64,
+            "linted document in total"
+        );
         diagnostics
     }
 }
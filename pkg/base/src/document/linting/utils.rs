@@ -4,11 +4,15 @@ use std::{collections::HashMap, sync::Arc};
 
 use crate::document::{
     linting::default::{
-        check_body_leading_blank, check_footer_leading_blank, check_subject_empty,
-        check_subject_leading_space, check_subject_line_length, check_type_enum,
-        BODY_LEADING_BLANK, FOOTER_LEADING_BLANK, HEADER_MAX_LINE_LENGTH, SUBJECT_EMPTY,
-        SUBJECT_LEADING_SPACE, TYPE_ENUM,
+        check_body_leading_blank, check_bracket_scope, check_breaking_change_case,
+        check_footer_leading_blank, check_scope_max_words, check_subject_empty,
+        check_subject_leading_space, check_subject_line_length, check_subject_wrapped,
+        check_trailer_separator, check_type_enum, BODY_LEADING_BLANK, BRACKET_SCOPE,
+        BREAKING_CHANGE_CASE, FOOTER_LEADING_BLANK, HEADER_MAX_LINE_LENGTH, MAX_SCOPE_WORDS,
+        SCOPE_MAX_WORDS, SUBJECT_EMPTY, SUBJECT_LEADING_SPACE, SUBJECT_WRAPPED, TRAILER_SEPARATOR,
+        TYPE_ENUM,
     },
+    subject::Subject,
     GitCommitDocument,
 };
 
@@ -34,6 +38,14 @@ pub fn construct_default_lint_tests_map(
     insert!(TYPE_ENUM, check_type_enum);
     insert!(SUBJECT_EMPTY, check_subject_empty);
     insert!(SUBJECT_LEADING_SPACE, check_subject_leading_space);
+    insert!(SUBJECT_WRAPPED, check_subject_wrapped);
+    insert!(BREAKING_CHANGE_CASE, check_breaking_change_case);
+    insert!(TRAILER_SEPARATOR, check_trailer_separator);
+    insert!(BRACKET_SCOPE, check_bracket_scope);
+    tests.insert(
+        SCOPE_MAX_WORDS,
+        Arc::new(move |doc| check_scope_max_words(doc, SCOPE_MAX_WORDS, MAX_SCOPE_WORDS)),
+    );
     tests
 }
 
@@ -80,7 +92,7 @@ pub fn query_lint(
     let mut lints = vec![];
     let mut cursor = tree_sitter::QueryCursor::new();
     let names = query.capture_names();
-    let mut required_missing: bool = names.iter().any(|name| *name == "required");
+    let mut required_missing: bool = names.contains(&"required");
     if required_missing {
         log_debug!("[{}] starting search for required capture", code);
     }
@@ -125,3 +137,71 @@ pub fn query_lint(
 
     lints
 }
+
+/// downgrade errors and warnings to information, which editors underline gently
+pub fn as_suggestion(diagnostic: &mut lsp_types::Diagnostic) {
+    if matches!(
+        diagnostic.severity,
+        Some(lsp_types::DiagnosticSeverity::ERROR | lsp_types::DiagnosticSeverity::WARNING)
+    ) {
+        diagnostic.severity = Some(lsp_types::DiagnosticSeverity::INFORMATION);
+    }
+}
+
+/// attach a suggested fix to a diagnostic as a list of edits. Fixes are stored in the
+/// diagnostic's `data` field so that they survive the round-trip through the client.
+pub fn set_fix(diagnostic: &mut lsp_types::Diagnostic, edits: Vec<lsp_types::TextEdit>) {
+    diagnostic.data = Some(serde_json::json!({ "fix": edits }));
+}
+
+/// retrieve the fix attached to a diagnostic by `set_fix`, if any
+pub fn get_fix(diagnostic: &lsp_types::Diagnostic) -> Option<Vec<lsp_types::TextEdit>> {
+    diagnostic
+        .data
+        .as_ref()
+        .and_then(|data| data.get("fix"))
+        .and_then(|fix| serde_json::from_value(fix.clone()).ok())
+}
+
+/// the first whitespace-delimited word of the subject's message, usually its verb, along with the
+/// char offset of its start within the subject line
+pub fn first_word(subject: &Subject) -> Option<(u32, &str)> {
+    let message = subject.message_text();
+    let trimmed = message.trim_start();
+    let word = trimmed.split_whitespace().next()?;
+    let start =
+        subject.prefix_text().chars().count() + message.chars().count() - trimmed.chars().count();
+    Some((start as u32, word))
+}
+
+/// the Levenshtein distance between two strings, counted in chars
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = prev[j] + if a_char == *b_char { 0 } else { 1 };
+            current[j + 1] = substitution.min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut current);
+    }
+    prev[b.len()]
+}
+
+#[test]
+fn test_first_word() {
+    let doc = GitCommitDocument::new().with_text("feat(ui)!:  Add dark mode\n".into());
+    assert_eq!(first_word(doc.subject.as_ref().unwrap()), Some((12, "Add")));
+    let doc = GitCommitDocument::new().with_text("feat: \n".into());
+    assert_eq!(first_word(doc.subject.as_ref().unwrap()), None);
+}
+
+#[test]
+fn test_edit_distance() {
+    assert_eq!(edit_distance("database", "database"), 0);
+    assert_eq!(edit_distance("databse", "database"), 1);
+    assert_eq!(edit_distance("", "api"), 3);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+}
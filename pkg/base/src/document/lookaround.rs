@@ -28,10 +28,11 @@ pub(crate) fn find_byte_offset(text: &Rope, pos: lsp_types::Position) -> usize {
     byte_offset
 }
 
-/// transform a line/column position into a tree-sitter Point struct
-pub(crate) fn to_point(p: lsp_types::Position) -> tree_sitter::Point {
+/// the tree-sitter Point of a byte offset in the text. Tree-sitter columns count bytes, not chars.
+pub(crate) fn to_point(text: &Rope, byte_offset: usize) -> tree_sitter::Point {
+    let row = text.line_of_byte(byte_offset);
     tree_sitter::Point {
-        row: p.line as usize,
-        column: p.character as usize,
+        row,
+        column: byte_offset - text.byte_of_line(row),
     }
 }
@@ -3,7 +3,11 @@
 pub mod linting;
 mod lookaround;
 pub(crate) mod subject;
-use std::path::PathBuf;
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    path::PathBuf,
+};
 
 use crop::{Rope, RopeSlice};
 use lookaround::{find_byte_offset, to_point};
@@ -22,12 +26,57 @@ lazy_static! {
         tree_sitter::Query::new(&LANGUAGE, include_str!("./queries/trailer.scm")).unwrap();
     static ref FILE_QUERY: tree_sitter::Query =
         tree_sitter::Query::new(&LANGUAGE, include_str!("./queries/filepath.scm")).unwrap();
+    /// `#123`, `owner/repo#123`, `GH-123`, or `@mention`, not inside a word, path, or email
+    static ref REFERENCE: regex::Regex = regex::Regex::new(
+        r"(?:^|[^\w@/#.-])(?P<reference>(?:[\w.-]+/[\w.-]+)?#\d+|GH-\d+|@[\w-]+)"
+    )
+    .unwrap();
+    /// a markdown list item's indentation and marker, e.g. `  - ` or `1. `
+    static ref LIST_ITEM: regex::Regex = regex::Regex::new(r"^\s*(?:[-*+]|\d+[.)])\s+").unwrap();
+}
+
+/// the web URL an issue, PR, or user `reference` points to on `remote`
+pub(crate) fn reference_target(
+    remote: &crate::git::WebRemote,
+    reference: &str,
+) -> Option<lsp_types::Url> {
+    let url = if let Some(user) = reference.strip_prefix('@') {
+        remote.user_url(user)
+    } else if let Some(number) = reference.strip_prefix("GH-") {
+        if remote.forge != crate::git::Forge::GitHub {
+            return None;
+        }
+        remote.issue_url(None, number.parse().ok()?)
+    } else {
+        let (repo, number) = reference.split_once('#')?;
+        let repo = Some(repo).filter(|repo| !repo.is_empty());
+        remote.issue_url(repo, number.parse().ok()?)
+    };
+    lsp_types::Url::parse(&url).ok()
+}
+
+/// whether `line` is a git comment, as opposed to message content
+pub(crate) fn is_comment(line: &RopeSlice, comments_are_content: bool) -> bool {
+    !comments_are_content && line.bytes().next() == Some(b'#')
 }
 
-fn get_subject_line(code: &Rope) -> Option<(RopeSlice, usize)> {
-    for (number, line) in code.lines().enumerate() {
+/// git's scissors line: git drops it and everything below it, like the diff `git commit --verbose`
+/// adds to the template
+pub const SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+/// whether `line` is git's scissors line
+pub(crate) fn is_scissors(line: &RopeSlice) -> bool {
+    line.bytes().next() == Some(b'#') && line.to_string().trim_end() == SCISSORS
+}
+
+fn get_subject_line(code: &Rope, comments_are_content: bool) -> Option<(RopeSlice<'_>, usize)> {
+    for (number, line) in code
+        .lines()
+        .take_while(|line| !is_scissors(line))
+        .enumerate()
+    {
         if !line.is_empty()
-            && line.bytes().next() != Some(b'#')
+            && !is_comment(&line, comments_are_content)
             && line.chars().any(|c| !c.is_whitespace())
         {
             return Some((line, number));
@@ -36,34 +85,214 @@ fn get_subject_line(code: &Rope) -> Option<(RopeSlice, usize)> {
     None
 }
 
+/// the newline inserted by formatting edits and lint fixes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\r\n` if the document already uses it, else `\n`
+    #[default]
+    Auto,
+    Lf,
+    Crlf,
+}
+
+/// apply `edits` to `text`, returning the result and how many edits changed it. Edits are applied
+/// from the last position to the first so earlier positions stay valid.
+#[cfg(any(feature = "cli", test))]
+pub(crate) fn apply_edits(text: &str, edits: &[lsp_types::TextEdit]) -> (String, usize) {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let offset = |position: &lsp_types::Position| -> usize {
+        let Some(&start) = line_starts.get(position.line as usize) else {
+            return text.len();
+        };
+        let line = &text[start..];
+        let line = &line[..line.find('\n').unwrap_or(line.len())];
+        start
+            + line
+                .char_indices()
+                .nth(position.character as usize)
+                .map(|(i, _)| i)
+                .unwrap_or(line.len())
+    };
+    let mut spans: Vec<(usize, usize, &str)> = edits
+        .iter()
+        .map(|edit| {
+            (
+                offset(&edit.range.start),
+                offset(&edit.range.end),
+                edit.new_text.as_str(),
+            )
+        })
+        .filter(|(start, end, new_text)| text[*start..*end] != **new_text)
+        .collect();
+    // a stable sort keeps insertions at the same position in order once reversed
+    spans.sort_by_key(|(start, ..)| *start);
+    let mut result = text.to_owned();
+    for (start, end, new_text) in spans.iter().rev() {
+        result.replace_range(start..end, new_text);
+    }
+    (result, spans.len())
+}
+
+/// rewrite every newline in `text` as `newline`
+pub(crate) fn convert_newlines(text: &str, newline: &str) -> String {
+    if newline == "\n" || !text.contains('\n') {
+        return text.to_owned();
+    }
+    text.replace("\r\n", "\n").replace('\n', newline)
+}
+
+/// opt-in formatting behavior for `GitCommitDocument::format`
+#[derive(Debug, Default, Clone)]
+pub struct FormatOptions {
+    /// the newline to insert
+    pub line_ending: LineEnding,
+    /// insert a blank line between the message and git's trailing comment block
+    pub comment_leading_blank: bool,
+    /// rewrite trailer keys to their canonical casing, e.g. `signed-off-by` => `Signed-off-by`
+    pub normalize_trailer_keys: bool,
+    /// canonical trailer keys in addition to `CANONICAL_TRAILER_KEYS`
+    pub trailer_keys: Vec<String>,
+    /// sort the trailer block by key in this order; unlisted keys go last
+    pub trailer_order: Vec<String>,
+    /// strip these characters from the end of the subject, e.g. `.`
+    pub subject_full_stops: String,
+    /// alias => canonical scope, e.g. `fe` => `ui`; aliased scopes are rewritten
+    pub scope_aliases: HashMap<String, String>,
+    /// re-wrap body paragraphs with lines longer than this many characters; 0 disables wrapping
+    pub body_wrap: u16,
+    /// rewrite legacy `type[scope]: ` subjects to `type(scope): `
+    pub parenthesize_bracket_scopes: bool,
+}
+
+/// well-known trailer keys, spelled the way git and forges spell them
+pub const CANONICAL_TRAILER_KEYS: &[&str] = &[
+    "BREAKING CHANGE",
+    "BREAKING-CHANGE",
+    "Signed-off-by",
+    "Co-authored-by",
+    "Reviewed-by",
+    "Acked-by",
+    "Tested-by",
+    "Reported-by",
+    "Suggested-by",
+    "Helped-by",
+    "Cc",
+    "Fixes",
+    "Closes",
+    "Refs",
+    "Change-Id",
+];
+
 pub struct GitCommitDocument {
     pub code: crop::Rope,
     parser: tree_sitter::Parser, // since the parser is stateful, it needs to be owned by the document
     pub syntax_tree: tree_sitter::Tree,
     pub subject: Option<Subject>,
     pub worktree_root: Option<PathBuf>,
+    /// the commit this message belongs to, if any. `None` for a message that's still being written.
+    pub revision: Option<String>,
+    /// treat `#`-prefixed lines as message content rather than git comments, e.g. for messages
+    /// written outside of `git commit`
+    pub comments_are_content: bool,
+    /// the latest version of the document the client sent, if any
+    pub version: Option<i32>,
+    /// each subject-only lint's last diagnostics, cleared whenever the subject is re-parsed
+    pub(crate) subject_lint_cache: RefCell<HashMap<String, Vec<lsp_types::Diagnostic>>>,
+    /// whether the last `edit` left the subject line alone, so the cache can be reused
+    pub(crate) subject_unchanged: Cell<bool>,
+}
+
+/// a comment selecting a named config profile, e.g. `# cconvention-profile: release`
+pub const PROFILE_DIRECTIVE: &str = "cconvention-profile:";
+
+/// how long to let tree-sitter parse a message before giving up
+pub const DEFAULT_PARSE_TIMEOUT_MICROS: u64 = 500_000; // .5 seconds
+
+/// the bundled git-commit grammar was generated for a tree-sitter ABI the linked runtime can't load,
+/// usually after bumping only one of `tree-sitter` and `tree-sitter-gitcommit`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrammarError {
+    /// the grammar's ABI version
+    pub version: usize,
+}
+impl std::fmt::Display for GrammarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the bundled git-commit grammar has ABI version {}, but the tree-sitter runtime only \
+            supports versions {}..={}; rebuild cconvention with compatible versions of the \
+            `tree-sitter` and `tree-sitter-gitcommit` crates",
+            self.version,
+            tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION,
+            tree_sitter::LANGUAGE_VERSION,
+        )
+    }
+}
+impl std::error::Error for GrammarError {}
+
+/// whether the linked tree-sitter runtime can load a grammar with ABI version `version`
+fn check_grammar_version(version: usize) -> Result<(), GrammarError> {
+    if (tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION..=tree_sitter::LANGUAGE_VERSION)
+        .contains(&version)
+    {
+        Ok(())
+    } else {
+        Err(GrammarError { version })
+    }
+}
+
+/// parse `text`, degrading to an empty syntax tree rather than panicking if parsing times out
+fn parse_or_empty(
+    parser: &mut tree_sitter::Parser,
+    text: &str,
+    old_tree: Option<&tree_sitter::Tree>,
+) -> tree_sitter::Tree {
+    parser.parse(text, old_tree).unwrap_or_else(|| {
+        let timeout = parser.timeout_micros();
+        log_info!("parsing timed out after {}µs", timeout);
+        parser.reset();
+        parser.set_timeout_micros(0); // parsing "" can't take long
+        let tree = parser.parse("", None).unwrap();
+        parser.set_timeout_micros(timeout);
+        tree
+    })
 }
 
 /// state management for a git commit document
 impl GitCommitDocument {
+    /// # Panics
+    /// if the bundled grammar can't be loaded; see [`GitCommitDocument::try_new`]
     pub fn new() -> Self {
+        Self::try_new().unwrap_or_else(|err| panic!("{err}"))
+    }
+    pub fn try_new() -> Result<Self, GrammarError> {
+        let version = LANGUAGE.version();
+        check_grammar_version(version)?;
         let code = crop::Rope::from("".to_string());
         let mut parser = {
-            let language = tree_sitter_gitcommit::language();
             let mut parser = tree_sitter::Parser::new();
-            parser.set_language(&language).unwrap();
-            parser.set_timeout_micros(500_000); // .5 seconds
+            parser
+                .set_language(&LANGUAGE)
+                .map_err(|_| GrammarError { version })?;
+            parser.set_timeout_micros(DEFAULT_PARSE_TIMEOUT_MICROS);
             parser
         };
         let syntax_tree = parser.parse("", None).unwrap();
 
-        GitCommitDocument {
+        Ok(GitCommitDocument {
             code,
             parser,
             syntax_tree,
             worktree_root: None,
             subject: None,
-        }
+            revision: None,
+            comments_are_content: false,
+            version: None,
+            subject_lint_cache: RefCell::new(HashMap::new()),
+            subject_unchanged: Cell::new(false),
+        })
     }
     pub fn with_url(mut self, url: &lsp_types::Url) -> Self {
         self.worktree_root = to_path(url)
@@ -72,9 +301,44 @@ impl GitCommitDocument {
         self
     }
 
+    pub fn with_revision(mut self, revision: &str) -> Self {
+        self.revision = Some(revision.to_owned());
+        self
+    }
+
+    /// how long to let tree-sitter parse the message; 0 means no limit
+    pub fn with_parse_timeout(mut self, timeout_micros: u64) -> Self {
+        self.parser.set_timeout_micros(timeout_micros);
+        self
+    }
+
+    pub fn with_comments_are_content(mut self, comments_are_content: bool) -> Self {
+        self.comments_are_content = comments_are_content;
+        self.update_subject();
+        self
+    }
+    /// the newline `ending` resolves to for this document
+    pub fn newline(&self, ending: LineEnding) -> &'static str {
+        match ending {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Auto => {
+                let first_line_end = self.code.bytes().position(|b| b == b'\n');
+                match first_line_end {
+                    Some(i) if i > 0 && self.code.byte(i - 1) == b'\r' => "\r\n",
+                    _ => "\n",
+                }
+            }
+        }
+    }
+    /// whether the line is a git comment, respecting `comments_are_content`
+    pub fn is_comment(&self, line: &RopeSlice) -> bool {
+        is_comment(line, self.comments_are_content)
+    }
+
     pub fn set_text(&mut self, text: String) -> &mut Self {
         self.code = crop::Rope::from(text.clone());
-        self.syntax_tree = self.parser.parse(&text, None).unwrap();
+        self.syntax_tree = parse_or_empty(&mut self.parser, &text, None);
         self.update_subject();
         self
     }
@@ -84,6 +348,8 @@ impl GitCommitDocument {
     }
 
     fn update_subject(&mut self) -> &mut Self {
+        self.subject_lint_cache.get_mut().clear();
+        self.subject_unchanged.set(false);
         self.subject =
             if let Some((subject_line, line_number)) = self.get_subject_line_with_number() {
                 let subject = Subject::new(subject_line, line_number);
@@ -97,56 +363,56 @@ impl GitCommitDocument {
             };
         self
     }
-    pub(crate) fn edit(
-        &mut self,
-        edits: &[lsp_types::TextDocumentContentChangeEvent],
-    ) -> &mut Self {
-        // FIXME: sometimes deletions/bulk inserts cause duplicate characters to creep in
+    /// apply the incremental changes from a `textDocument/didChange` notification
+    pub fn edit(&mut self, edits: &[lsp_types::TextDocumentContentChangeEvent]) -> &mut Self {
+        let mut subject_changed = false;
+        // a different profile may lint the same subject differently
+        let profile = self.profile();
         for edit in edits {
             debug_assert!(edit.range.is_some(), "range is none");
             if edit.range.is_none() {
                 continue;
             }
             let range = edit.range.unwrap();
+            // edits below the subject line can't change it
+            let touches_subject = self
+                .subject
+                .as_ref()
+                .is_none_or(|subject| range.start.line <= subject.line_number as u32);
             let start_byte = find_byte_offset(&self.code, range.start);
             let end_byte = find_byte_offset(&self.code, range.end);
+            // compute every point from the rope's bytes so the edit stays consistent with the
+            // text tree-sitter sees, even around multibyte chars
+            let start_position = to_point(&self.code, start_byte);
+            let old_end_position = to_point(&self.code, end_byte);
             self.code.replace(start_byte..end_byte, &edit.text);
-            let new_end_position = match edit.text.rfind('\n') {
-                Some(ind) => {
-                    let num_newlines = edit.text.bytes().filter(|&c| c == b'\n').count();
-                    tree_sitter::Point {
-                        row: range.start.line as usize + num_newlines,
-                        column: edit.text.len() - ind,
-                    }
-                }
-                None => tree_sitter::Point {
-                    row: range.end.line as usize,
-                    column: range.end.character as usize + edit.text.len(),
-                },
-            };
+            let new_end_byte = start_byte + edit.text.len();
             log_debug!("found end position, submitting edit");
             self.syntax_tree.edit(&tree_sitter::InputEdit {
                 start_byte,
                 old_end_byte: end_byte,
-                new_end_byte: start_byte + edit.text.len(),
-                start_position: to_point(range.start),
-                old_end_position: to_point(range.end),
-                new_end_position,
+                new_end_byte,
+                start_position,
+                old_end_position,
+                new_end_position: to_point(&self.code, new_end_byte),
             });
             log_debug!("parsing");
             {
                 // update the semantic ranges --------------------------------------
-                let prev_tree = &self.syntax_tree;
-                self.syntax_tree = self
-                    .parser
-                    .parse(&(self.code.to_string()), Some(prev_tree))
-                    .unwrap();
+                self.syntax_tree = parse_or_empty(
+                    &mut self.parser,
+                    &self.code.to_string(),
+                    Some(&self.syntax_tree),
+                );
                 log_info!("{}", &self.syntax_tree.root_node().to_sexp());
-                // TODO: detect if the subject line changed.
-                // HACK: for now, just recompute the indices
-                self.update_subject();
+                if touches_subject {
+                    self.update_subject();
+                    subject_changed = true;
+                }
             }
         }
+        subject_changed |= self.profile() != profile;
+        self.subject_unchanged.set(!subject_changed);
 
         self
     }
@@ -160,39 +426,60 @@ impl Default for GitCommitDocument {
 
 /// navigation & queries
 impl GitCommitDocument {
+    /// the config profile named by a `PROFILE_DIRECTIVE` on the first comment line, if any
+    pub fn profile(&self) -> Option<String> {
+        let line = self
+            .code
+            .lines()
+            .find(|line| self.is_comment(line))?
+            .to_string();
+        let name = line
+            .trim_start_matches('#')
+            .trim()
+            .strip_prefix(PROFILE_DIRECTIVE)?
+            .trim();
+        (!name.is_empty()).then(|| name.to_string())
+    }
+    /// each line of the message with its 0-indexed line number, stopping at git's scissors line
+    pub fn message_lines(&self) -> impl Iterator<Item = (usize, RopeSlice<'_>)> + '_ {
+        self.code
+            .lines()
+            .take_while(|line| !is_scissors(line))
+            .enumerate()
+    }
+    /// the 0-indexed line number of git's scissors line, if any
+    pub fn scissors_line(&self) -> Option<usize> {
+        self.code.lines().position(|line| is_scissors(&line))
+    }
     /// returns the 0-indexed line number of each body line, NOT including the subject
     /// line but including trailers and blank lines
-    fn get_body(&self) -> impl Iterator<Item = (usize, RopeSlice)> + '_ {
+    pub fn get_body(&self) -> impl Iterator<Item = (usize, RopeSlice<'_>)> + '_ {
         let subject_line_number = if let Some(subject) = &self.subject {
             subject.line_number + 1
         } else {
             0
         };
-        return self
-            .code
-            .lines()
-            .enumerate()
+        self.message_lines()
             .skip(subject_line_number.into())
-            .filter(|(_, line)| line.bytes().next() != Some(b'#'));
+            .filter(|(_, line)| !self.is_comment(line))
     }
-    pub(crate) fn slice_of(&self, node: tree_sitter::Node) -> crop::RopeSlice {
+    pub(crate) fn slice_of(&self, node: tree_sitter::Node) -> crop::RopeSlice<'_> {
         self.code.byte_slice(node.byte_range())
     }
     fn get_subject_line_with_number(&self) -> Option<(String, usize)> {
-        if let Some(node) = self.get_ts_subject_line() {
-            return Some((
-                node.utf8_text(self.slice_of(node).to_string().as_bytes())
-                    .unwrap()
-                    .to_string(),
-                node.start_position().row,
-            ));
+        // the grammar always treats `#` lines as comments
+        if let Some(node) = self
+            .get_ts_subject_line()
+            .filter(|_| !self.comments_are_content)
+        {
+            return Some((self.slice_of(node).to_string(), node.start_position().row));
         }
-        if let Some((text, number)) = get_subject_line(&self.code) {
+        if let Some((text, number)) = get_subject_line(&self.code, self.comments_are_content) {
             return Some((text.to_string(), number));
         }
         None
     }
-    fn get_ts_subject_line(&self) -> Option<tree_sitter::Node> {
+    fn get_ts_subject_line(&self) -> Option<tree_sitter::Node<'_>> {
         let mut cursor = tree_sitter::QueryCursor::new();
         let names = SUBJECT_QUERY.capture_names();
         let matches = cursor.matches(
@@ -235,6 +522,458 @@ impl GitCommitDocument {
         }
         line_numbers
     }
+    /// the files this message describes: those changed by `revision` if set, else the staged files
+    pub fn changed_files(&self) -> Vec<String> {
+        match &self.revision {
+            Some(revision) => crate::git::changed_files(revision, self.worktree_root.clone()),
+            None => crate::git::staged_files(self.worktree_root.clone()),
+        }
+    }
+    /// the author email of this message's commit, or the configured `user.email` if the message
+    /// is still being written
+    pub fn author_email(&self) -> Option<String> {
+        match &self.revision {
+            Some(revision) => crate::git::author_email(revision, self.worktree_root.clone()),
+            None => crate::git::user_email(self.worktree_root.clone()),
+        }
+    }
+    /// the number of lines added or deleted by this message's commit, or by the staged changes
+    /// if the message is still being written
+    pub fn changed_line_count(&self) -> usize {
+        match &self.revision {
+            Some(revision) => crate::git::changed_line_count(revision, self.worktree_root.clone()),
+            None => crate::git::staged_line_count(self.worktree_root.clone()),
+        }
+    }
+    /// whether the commit marks a breaking change, via `!` before the subject's colon or a
+    /// `BREAKING CHANGE:` footer
+    pub fn is_breaking(&self) -> bool {
+        let bang = self
+            .subject
+            .as_ref()
+            .is_some_and(|subject| subject.rest_text().contains('!'));
+        // the grammar doesn't parse `BREAKING CHANGE` as a trailer key, so scan the body
+        bang || self.get_body().any(|(_, line)| {
+            let line = line.to_string();
+            ["BREAKING CHANGE", "BREAKING-CHANGE"].iter().any(|key| {
+                line.get(..key.len())
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(key))
+                    && line[key.len()..].trim_start().starts_with(':')
+            })
+        })
+    }
+    /// returns the range and text of each trailer's key, e.g. `Signed-off-by`
+    pub fn get_trailer_keys(&self) -> Vec<(lsp_types::Range, String)> {
+        let mut keys = vec![];
+        for line_number in self.get_trailers_lines() {
+            let line = self.code.line(line_number as usize).to_string();
+            let key = line
+                .split(|c: char| c == ':' || c.is_whitespace())
+                .next()
+                .unwrap_or_default();
+            if key.is_empty() {
+                continue;
+            }
+            let range = lsp_types::Range {
+                start: lsp_types::Position {
+                    line: line_number,
+                    character: 0,
+                },
+                end: lsp_types::Position {
+                    line: line_number,
+                    character: key.chars().count() as u32,
+                },
+            };
+            keys.push((range, key.to_owned()));
+        }
+        keys
+    }
+    /// returns each trailer's key and value, e.g. `("Signed-off-by", "A U Thor <a@example.com>")`
+    pub fn get_trailers(&self) -> Vec<(String, String)> {
+        self.get_trailers_lines()
+            .into_iter()
+            .filter_map(|line_number| {
+                let line = self.code.line(line_number as usize).to_string();
+                let (key, value) = line
+                    .split_once(':')
+                    .or_else(|| line.split_once(char::is_whitespace))?;
+                let key = key.trim();
+                (!key.is_empty()).then(|| (key.to_owned(), value.trim().to_owned()))
+            })
+            .collect()
+    }
+    /// returns the range of the trailer key under the cursor, if any
+    pub(crate) fn trailer_key_at(
+        &self,
+        position: &lsp_types::Position,
+    ) -> Option<lsp_types::Range> {
+        self.get_trailer_keys()
+            .into_iter()
+            .map(|(range, _)| range)
+            .find(|range| {
+                range.start.line == position.line
+                    && range.start.character <= position.character
+                    && position.character <= range.end.character
+            })
+    }
+    /// rename every occurrence of the trailer key under the cursor to `new_name`.
+    /// Keys are compared case-insensitively, like git does.
+    pub(crate) fn rename_trailer_key(
+        &self,
+        position: &lsp_types::Position,
+        new_name: &str,
+    ) -> Option<Vec<lsp_types::TextEdit>> {
+        let keys = self.get_trailer_keys();
+        let (_, old_name) = keys.iter().find(|(range, _)| {
+            range.start.line == position.line
+                && range.start.character <= position.character
+                && position.character <= range.end.character
+        })?;
+        Some(
+            keys.iter()
+                .filter(|(_, key)| key.eq_ignore_ascii_case(old_name))
+                .map(|(range, _)| lsp_types::TextEdit {
+                    range: *range,
+                    new_text: new_name.to_owned(),
+                })
+                .collect(),
+        )
+    }
+    /// the formatted subject line after replacing its `part` (`"type"` or `"scope"`) with `label`
+    pub(crate) fn completion_preview(&self, part: &str, label: &str) -> Option<String> {
+        let subject = self.subject.as_ref()?;
+        let type_text = subject.type_text();
+        let line = match part {
+            "type" => format!("{label}{}", &subject.line[type_text.len()..]),
+            "scope" => format!(
+                "{type_text}({label}){}",
+                &subject.line[type_text.len() + subject.scope_text().len()..]
+            ),
+            _ => return None,
+        };
+        Some(Subject::new(line, subject.line_number as usize).auto_format(false))
+    }
+    /// highlight every whole-word occurrence of the subject's scope if the cursor is on the scope
+    pub(crate) fn scope_highlights(
+        &self,
+        position: &lsp_types::Position,
+    ) -> Vec<lsp_types::DocumentHighlight> {
+        let Some(subject) = &self.subject else {
+            return vec![];
+        };
+        let start = subject.type_text().chars().count() as u32;
+        let end = start + subject.scope_text().chars().count() as u32;
+        if position.line != subject.line_number as u32
+            || position.character < start
+            || position.character > end
+        {
+            return vec![];
+        }
+        let scope = subject.scope_name().trim();
+        if scope.is_empty() {
+            return vec![];
+        }
+        let is_word_char = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+        let mut highlights = vec![];
+        for (line_number, line) in self.message_lines() {
+            if self.is_comment(&line) {
+                continue;
+            }
+            let line = line.to_string();
+            for (byte_index, _) in line.match_indices(scope) {
+                let before = line[..byte_index].chars().next_back();
+                let after = line[byte_index + scope.len()..].chars().next();
+                if before.is_some_and(is_word_char) || after.is_some_and(is_word_char) {
+                    continue;
+                }
+                let character = line[..byte_index].chars().count() as u32;
+                highlights.push(lsp_types::DocumentHighlight {
+                    range: lsp_types::Range {
+                        start: lsp_types::Position {
+                            line: line_number as u32,
+                            character,
+                        },
+                        end: lsp_types::Position {
+                            line: line_number as u32,
+                            character: character + scope.chars().count() as u32,
+                        },
+                    },
+                    kind: Some(lsp_types::DocumentHighlightKind::TEXT),
+                });
+            }
+        }
+        highlights
+    }
+    /// the nested ranges to expand a selection through from `position`: in the subject, from
+    /// the part under the cursor out through the type+scope, the prefix, and the whole line; in
+    /// a trailer, its line; elsewhere, an empty range at `position`
+    pub(crate) fn selection_range(
+        &self,
+        position: &lsp_types::Position,
+    ) -> lsp_types::SelectionRange {
+        let line_range = |line: u32, start: u32, end: u32| lsp_types::Range {
+            start: lsp_types::Position {
+                line,
+                character: start,
+            },
+            end: lsp_types::Position {
+                line,
+                character: end,
+            },
+        };
+        // innermost first
+        let mut ranges = vec![];
+        match &self.subject {
+            Some(subject) if position.line == subject.line_number as u32 => {
+                let line = position.line;
+                let type_end = subject.type_text().chars().count() as u32;
+                let scope_end = type_end + subject.scope_text().chars().count() as u32;
+                let prefix_end = scope_end + subject.rest_text().chars().count() as u32;
+                let line_end = subject.line.trim_end().chars().count() as u32;
+                let c = position.character;
+                if c <= type_end {
+                    ranges.push(line_range(line, 0, type_end));
+                } else if c <= scope_end {
+                    ranges.push(line_range(line, type_end, scope_end));
+                } else if c <= prefix_end {
+                    ranges.push(line_range(line, scope_end, prefix_end));
+                } else {
+                    ranges.push(line_range(line, prefix_end, line_end));
+                }
+                if c <= scope_end {
+                    ranges.push(line_range(line, 0, scope_end));
+                }
+                if c <= prefix_end {
+                    ranges.push(line_range(line, 0, prefix_end));
+                }
+                ranges.push(line_range(line, 0, line_end));
+            }
+            _ if self.get_trailers_lines().contains(&position.line) => {
+                let line = self.code.line(position.line as usize).to_string();
+                let end = line.trim_end().chars().count() as u32;
+                ranges.push(line_range(position.line, 0, end));
+            }
+            _ => ranges.push(lsp_types::Range {
+                start: *position,
+                end: *position,
+            }),
+        }
+        ranges.dedup();
+        ranges
+            .into_iter()
+            .rev()
+            .fold(None, |parent, range| {
+                Some(lsp_types::SelectionRange {
+                    range,
+                    parent: parent.map(Box::new),
+                })
+            })
+            .unwrap()
+    }
+    /// a folding range for each multi-line body paragraph and for the trailer block, each
+    /// collapsing to its first line
+    pub(crate) fn folding_ranges(&self) -> Vec<lsp_types::FoldingRange> {
+        let trailers = self.get_trailers_lines();
+        let fold = |start: u32, end: u32| lsp_types::FoldingRange {
+            start_line: start,
+            end_line: end,
+            kind: Some(lsp_types::FoldingRangeKind::Region),
+            ..Default::default()
+        };
+        let mut ranges = vec![];
+        // (start, end) of the paragraph being scanned
+        let mut paragraph: Option<(u32, u32)> = None;
+        for (line_number, line) in self.get_body() {
+            let line_number = line_number as u32;
+            let is_text =
+                !trailers.contains(&line_number) && line.chars().any(|c| !c.is_whitespace());
+            paragraph = match paragraph {
+                // comments are skipped by `get_body`, so a gap in line numbers ends a paragraph
+                Some((start, end)) if is_text && end + 1 == line_number => {
+                    Some((start, line_number))
+                }
+                previous => {
+                    if let Some((start, end)) = previous.filter(|(start, end)| start < end) {
+                        ranges.push(fold(start, end));
+                    }
+                    is_text.then_some((line_number, line_number))
+                }
+            };
+        }
+        if let Some((start, end)) = paragraph.filter(|(start, end)| start < end) {
+            ranges.push(fold(start, end));
+        }
+        if let (Some(&first), Some(&last)) = (trailers.iter().min(), trailers.iter().max()) {
+            if first < last {
+                ranges.push(fold(first, last));
+            }
+        }
+        ranges
+    }
+    /// an outline of the message: the subject and its parts, the body, and each trailer
+    pub(crate) fn document_symbols(&self) -> Vec<lsp_types::DocumentSymbol> {
+        let line_range = |start: (u32, u32), end: (u32, u32)| lsp_types::Range {
+            start: lsp_types::Position::new(start.0, start.1),
+            end: lsp_types::Position::new(end.0, end.1),
+        };
+        #[allow(deprecated)] // `deprecated` is superseded by `tags`, but must still be set
+        let symbol = |name: &str,
+                      detail: &str,
+                      kind: lsp_types::SymbolKind,
+                      range: lsp_types::Range,
+                      children: Vec<lsp_types::DocumentSymbol>| {
+            lsp_types::DocumentSymbol {
+                name: name.to_owned(),
+                detail: (!detail.is_empty()).then(|| detail.to_owned()),
+                kind,
+                tags: None,
+                deprecated: None,
+                range,
+                selection_range: range,
+                children: (!children.is_empty()).then_some(children),
+            }
+        };
+        let line_end = |line_number: u32| {
+            let line = self.code.line(line_number as usize).to_string();
+            line.trim_end().chars().count() as u32
+        };
+        let mut symbols = vec![];
+        if let Some(subject) = &self.subject {
+            let line = subject.line_number as u32;
+            let type_end = subject.type_text().chars().count() as u32;
+            let scope_end = type_end + subject.scope_text().chars().count() as u32;
+            let message = subject.message_text();
+            let description_start = scope_end
+                + subject.rest_text().chars().count() as u32
+                + (message.chars().count() - message.trim_start().chars().count()) as u32;
+            let end = subject.line.trim_end().chars().count() as u32;
+            let mut parts = vec![];
+            for (name, text, start, end) in [
+                ("type", subject.type_text(), 0, type_end),
+                ("scope", subject.scope_text(), type_end, scope_end),
+                ("description", message, description_start, end),
+            ] {
+                let text = text.trim();
+                if !text.is_empty() {
+                    parts.push(symbol(
+                        name,
+                        text,
+                        lsp_types::SymbolKind::STRING,
+                        line_range((line, start), (line, end)),
+                        vec![],
+                    ));
+                }
+            }
+            symbols.push(symbol(
+                "Subject",
+                subject.line.trim_end(),
+                lsp_types::SymbolKind::NAMESPACE,
+                line_range((line, 0), (line, end)),
+                parts,
+            ));
+        }
+        let trailers = self.get_trailers_lines();
+        let body_lines: Vec<u32> = self
+            .get_body()
+            .filter(|(_, line)| line.chars().any(|c| !c.is_whitespace()))
+            .map(|(line_number, _)| line_number as u32)
+            .filter(|line_number| !trailers.contains(line_number))
+            .collect();
+        if let (Some(&first), Some(&last)) = (body_lines.first(), body_lines.last()) {
+            symbols.push(symbol(
+                "Body",
+                "",
+                lsp_types::SymbolKind::NAMESPACE,
+                line_range((first, 0), (last, line_end(last))),
+                vec![],
+            ));
+        }
+        if let (Some(&first), Some(&last)) = (trailers.iter().min(), trailers.iter().max()) {
+            let children = trailers
+                .iter()
+                .filter_map(|&line| {
+                    let text = self.code.line(line as usize).to_string();
+                    let (key, value) = text
+                        .split_once(':')
+                        .or_else(|| text.split_once(char::is_whitespace))?;
+                    let key = key.trim();
+                    (!key.is_empty()).then(|| {
+                        symbol(
+                            key,
+                            value.trim(),
+                            lsp_types::SymbolKind::PROPERTY,
+                            line_range((line, 0), (line, line_end(line))),
+                            vec![],
+                        )
+                    })
+                })
+                .collect();
+            symbols.push(symbol(
+                "Trailers",
+                "",
+                lsp_types::SymbolKind::NAMESPACE,
+                line_range((first, 0), (last, line_end(last))),
+                children,
+            ));
+        }
+        symbols
+    }
+    /// a hint after the subject counting the characters left before `cutoff`, or how far past it
+    /// the subject runs; none without a subject or a cutoff
+    pub(crate) fn subject_length_hint(&self, cutoff: u16) -> Option<lsp_types::InlayHint> {
+        let subject = self.subject.as_ref().filter(|_| cutoff > 0)?;
+        let length = subject.line.trim_end_matches(['\r', '\n']).chars().count();
+        let label = match length.checked_sub(cutoff as usize) {
+            Some(over) if over > 0 => format!("{over} over"),
+            _ => format!("{} left", cutoff as usize - length),
+        };
+        Some(lsp_types::InlayHint {
+            position: lsp_types::Position {
+                line: subject.line_number as u32,
+                character: length as u32,
+            },
+            label: lsp_types::InlayHintLabel::String(label),
+            kind: None,
+            text_edits: None,
+            tooltip: None,
+            padding_left: Some(true),
+            padding_right: None,
+            data: None,
+        })
+    }
+    /// a lens above the subject showing the release the change calls for, given each
+    /// non-breaking type's bump; none if the subject has no type
+    pub(crate) fn semver_lenses(
+        &self,
+        bump_for_type: impl Fn(&str) -> crate::config::SemverBump,
+    ) -> Vec<lsp_types::CodeLens> {
+        let Some(subject) = &self.subject else {
+            return vec![];
+        };
+        let type_ = subject.type_text().trim();
+        if type_.is_empty() {
+            return vec![];
+        }
+        let bump = if self.is_breaking() {
+            crate::config::SemverBump::Major
+        } else {
+            bump_for_type(type_)
+        };
+        let start = lsp_types::Position {
+            line: subject.line_number as u32,
+            character: 0,
+        };
+        vec![lsp_types::CodeLens {
+            range: lsp_types::Range { start, end: start },
+            // display-only: there's nothing to run
+            command: Some(lsp_types::Command {
+                title: bump.to_string(),
+                command: String::new(),
+                arguments: None,
+            }),
+            data: None,
+        }]
+    }
     pub(crate) fn get_links(&self) -> Vec<lsp_types::DocumentLink> {
         let mut cursor = tree_sitter::QueryCursor::new();
         let matches = cursor.matches(
@@ -243,8 +982,12 @@ impl GitCommitDocument {
             |node: tree_sitter::Node<'_>| self.slice_of(node).chunks().map(|s| s.as_bytes()),
         );
         let mut result = vec![];
+        let scissors = self.scissors_line().unwrap_or(usize::MAX);
         for m in matches {
             for c in m.captures {
+                if c.node.start_position().row >= scissors {
+                    continue;
+                }
                 let text = self.slice_of(c.node).to_string();
                 let path = self
                     .worktree_root
@@ -275,8 +1018,54 @@ impl GitCommitDocument {
                 })
             }
         }
+        result.extend(self.get_reference_links());
         result
     }
+    /// links for issue, PR, and user references in the body, e.g. `#123` or `@user`, whose
+    /// targets are left for `resolve_link` since finding them runs git
+    fn get_reference_links(&self) -> Vec<lsp_types::DocumentLink> {
+        let mut links = vec![];
+        for (line_number, line) in self.get_body() {
+            let line = line.to_string();
+            for captures in REFERENCE.captures_iter(&line) {
+                let reference = captures.name("reference").unwrap();
+                let start = line[..reference.start()].chars().count() as u32;
+                let end = start + reference.as_str().chars().count() as u32;
+                links.push(lsp_types::DocumentLink {
+                    range: lsp_types::Range {
+                        start: lsp_types::Position {
+                            line: line_number as u32,
+                            character: start,
+                        },
+                        end: lsp_types::Position {
+                            line: line_number as u32,
+                            character: end,
+                        },
+                    },
+                    target: None,
+                    tooltip: None,
+                    data: Some(serde_json::json!({ "reference": reference.as_str() })),
+                });
+            }
+        }
+        links
+    }
+    /// fill in the target of a reference link from `get_links`, per the `origin` remote
+    pub(crate) fn resolve_link(
+        &self,
+        mut link: lsp_types::DocumentLink,
+    ) -> lsp_types::DocumentLink {
+        let reference = link
+            .data
+            .as_ref()
+            .and_then(|data| data.get("reference")?.as_str());
+        if let Some(reference) = reference {
+            link.target = crate::git::remote_url(self.worktree_root.clone())
+                .and_then(|url| crate::git::WebRemote::parse(&url))
+                .and_then(|remote| reference_target(&remote, reference));
+        }
+        link
+    }
 }
 
 /// linting
@@ -290,6 +1079,7 @@ impl GitCommitDocument {
         };
         log_debug!("linting trailers");
         lints.extend(self.check_trailers());
+        lints.extend(linting::default::check_conflict_markers(self));
         // IDEA: check for common trailer misspellings, e.g. lowercasing of "breaking change:",
         // "signed-off-by:", etc.
         lints
@@ -322,6 +1112,33 @@ impl GitCommitDocument {
         None
     }
 
+    /// returns the 0-indexed line number of the first comment line that follows message content
+    pub(crate) fn get_comment_block_start(&self) -> Option<usize> {
+        let mut seen_content = false;
+        for (number, line) in self.code.lines().enumerate() {
+            if self.is_comment(&line) {
+                if seen_content {
+                    return Some(number);
+                }
+            } else if line.chars().any(|c| !c.is_whitespace()) {
+                seen_content = true;
+            }
+        }
+        None
+    }
+
+    /// check there's a blank line before the comment block; return the 0-indexed line number
+    /// of the comment block's first line if not
+    pub(crate) fn get_missing_comment_padding_line(&self) -> Option<usize> {
+        let comment_start = self.get_comment_block_start()?;
+        let previous_line = self.code.line(comment_start - 1);
+        if previous_line.chars().any(|c| !c.is_whitespace()) {
+            Some(comment_start)
+        } else {
+            None
+        }
+    }
+
     fn check_trailers(&self) -> Vec<lsp_types::Diagnostic> {
         let mut lints = vec![];
         if self.get_trailers_lines().is_empty() {
@@ -367,7 +1184,7 @@ impl GitCommitDocument {
 }
 
 impl GitCommitDocument {
-    pub(crate) fn format(&self) -> Vec<lsp_types::TextEdit> {
+    pub(crate) fn format(&self, options: &FormatOptions) -> Vec<lsp_types::TextEdit> {
         let mut fixes = Vec::<lsp_types::TextEdit>::new();
         if let Some(subject) = &self.subject {
             // always auto-format the subject line, if any
@@ -382,40 +1199,1116 @@ impl GitCommitDocument {
                         character: subject.line.chars().count() as u32,
                     },
                 },
-                new_text: subject.auto_format(),
+                new_text: {
+                    let parenthesize = options.parenthesize_bracket_scopes;
+                    let formatted = subject.auto_format(parenthesize);
+                    let (open, close) = if subject.scope_is_bracketed() && !parenthesize {
+                        ('[', ']')
+                    } else {
+                        ('(', ')')
+                    };
+                    let formatted = match self.canonical_scope(&options.scope_aliases) {
+                        Some((alias, canonical)) => formatted.replacen(
+                            &format!("{open}{alias}{close}"),
+                            &format!("{open}{canonical}{close}"),
+                            1,
+                        ),
+                        None => formatted,
+                    };
+                    formatted
+                        .trim_end_matches(|c| options.subject_full_stops.contains(c))
+                        .to_string()
+                },
             });
-            if let Some(missing_subject_padding_line) = self.get_missing_padding_line_number() {
-                fixes.push(lsp_types::TextEdit {
-                    range: lsp_types::Range {
-                        start: lsp_types::Position {
-                            line: missing_subject_padding_line as u32,
-                            character: 0,
-                        },
-                        end: lsp_types::Position {
-                            line: missing_subject_padding_line as u32,
-                            character: 0,
-                        },
+            fixes.extend(self.subject_padding_fix());
+        };
+        let moved_trailers = self.misplaced_trailers_fix();
+        if moved_trailers.is_empty() {
+            fixes.extend(self.trailer_padding_fix());
+            fixes.extend(self.format_trailers(options));
+        } else {
+            // the other trailer fixes would overlap the moved lines; they wait for the next format
+            fixes.extend(moved_trailers);
+        }
+        if options.comment_leading_blank {
+            fixes.extend(self.comment_padding_fix());
+        }
+        if options.body_wrap > 0 {
+            fixes.extend(self.body_wrap_fix(options.body_wrap as usize));
+        }
+        let newline = self.newline(options.line_ending);
+        for fix in fixes.iter_mut() {
+            fix.new_text = convert_newlines(&fix.new_text, newline);
+        }
+        fixes
+    }
+
+    /// the subject's scope, as formatted, and the canonical scope it's an alias of, if any
+    fn canonical_scope<'a>(
+        &self,
+        aliases: &'a HashMap<String, String>,
+    ) -> Option<(String, &'a str)> {
+        let subject = self.subject.as_ref()?;
+        let scope: String = subject
+            .scope_name()
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        let canonical = aliases.get(&scope)?;
+        Some((scope, canonical))
+    }
+
+    /// insert a blank line before line `line_number`
+    fn insert_blank_line(line_number: usize) -> lsp_types::TextEdit {
+        let position = lsp_types::Position {
+            line: line_number as u32,
+            character: 0,
+        };
+        lsp_types::TextEdit {
+            range: lsp_types::Range {
+                start: position,
+                end: position,
+            },
+            new_text: "\n".into(),
+        }
+    }
+    /// the blank line missing between the subject and body, if any
+    pub(crate) fn subject_padding_fix(&self) -> Option<lsp_types::TextEdit> {
+        self.get_missing_padding_line_number()
+            .map(Self::insert_blank_line)
+    }
+    /// the blank line missing before the trailers, if any
+    pub(crate) fn trailer_padding_fix(&self) -> Option<lsp_types::TextEdit> {
+        self.get_missing_trailer_padding_line()
+            .map(|line| Self::insert_blank_line(line + 1))
+    }
+    /// the blank line missing before git's trailing comments, if any
+    pub(crate) fn comment_padding_fix(&self) -> Option<lsp_types::TextEdit> {
+        self.get_missing_comment_padding_line()
+            .map(Self::insert_blank_line)
+    }
+
+    /// move trailers that precede body content to the end of the message, keeping their order.
+    /// Returns a deletion for each run of misplaced trailer lines and one insertion after the
+    /// last line of the body; nothing if the trailers are already at the end.
+    fn misplaced_trailers_fix(&self) -> Vec<lsp_types::TextEdit> {
+        let is_blank = |n: usize| self.code.line(n).chars().all(|c| c.is_whitespace());
+        let n_lines = self.code.line_len();
+        // each trailer's lines, including indented continuation lines
+        let mut trailer_lines = Vec::<usize>::new();
+        for start in self.get_trailers_lines() {
+            let mut n = start as usize;
+            trailer_lines.push(n);
+            while n + 1 < n_lines
+                && !is_blank(n + 1)
+                && self
+                    .code
+                    .line(n + 1)
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c == ' ' || c == '\t')
+            {
+                n += 1;
+                trailer_lines.push(n);
+            }
+        }
+        let Some(last_content) = self
+            .get_body()
+            .map(|(n, _)| n)
+            .filter(|n| !is_blank(*n) && !trailer_lines.contains(n))
+            .last()
+        else {
+            return vec![];
+        };
+        let (misplaced, placed): (Vec<usize>, Vec<usize>) =
+            trailer_lines.into_iter().partition(|n| *n < last_content);
+        if misplaced.is_empty() {
+            return vec![];
+        }
+        let line_start = |line: usize| lsp_types::Position {
+            line: line as u32,
+            character: 0,
+        };
+        let mut edits = vec![];
+        // delete each contiguous run of misplaced lines
+        let mut runs: Vec<(usize, usize)> = vec![];
+        for &n in &misplaced {
+            match runs.last_mut() {
+                Some((_, end)) if *end + 1 == n => *end = n,
+                _ => runs.push((n, n)),
+            }
+        }
+        for (start, end) in runs {
+            // don't leave two blank lines where the run was
+            let end = if start > 0 && is_blank(start - 1) && is_blank(end + 1) {
+                end + 1
+            } else {
+                end
+            };
+            edits.push(lsp_types::TextEdit {
+                range: lsp_types::Range {
+                    start: line_start(start),
+                    end: line_start(end + 1),
+                },
+                new_text: String::new(),
+            });
+        }
+        let moved = misplaced
+            .iter()
+            .map(|n| self.code.line(*n).to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        edits.push(match placed.first() {
+            // join the trailers already at the end
+            Some(&first) => lsp_types::TextEdit {
+                range: lsp_types::Range {
+                    start: line_start(first),
+                    end: line_start(first),
+                },
+                new_text: if is_blank(first - 1) {
+                    format!("{moved}\n")
+                } else {
+                    format!("\n{moved}\n")
+                },
+            },
+            None => {
+                let end = lsp_types::Position {
+                    line: last_content as u32,
+                    character: self.code.line(last_content).chars().count() as u32,
+                };
+                lsp_types::TextEdit {
+                    range: lsp_types::Range { start: end, end },
+                    new_text: format!("\n\n{moved}"),
+                }
+            }
+        });
+        edits
+    }
+
+    /// re-wrap each body paragraph with a line longer than `width` characters at word boundaries.
+    /// List items keep their markers and hang their continuation lines; fenced or indented code
+    /// and trailers are left alone.
+    fn body_wrap_fix(&self, width: usize) -> Vec<lsp_types::TextEdit> {
+        struct Paragraph {
+            start: usize,
+            end: usize,
+            first_prefix: String,
+            rest_prefix: String,
+            words: Vec<String>,
+            too_long: bool,
+        }
+        let close = |paragraph: Option<Paragraph>, edits: &mut Vec<lsp_types::TextEdit>| {
+            let Some(p) = paragraph.filter(|p| p.too_long) else {
+                return;
+            };
+            let mut lines = vec![];
+            let mut line = p.first_prefix.clone();
+            let mut empty = true;
+            for word in &p.words {
+                if !empty && line.chars().count() + 1 + word.chars().count() > width {
+                    lines.push(std::mem::replace(&mut line, p.rest_prefix.clone()));
+                    empty = true;
+                }
+                if !empty {
+                    line.push(' ');
+                }
+                line.push_str(word);
+                empty = false;
+            }
+            lines.push(line);
+            edits.push(lsp_types::TextEdit {
+                range: lsp_types::Range {
+                    start: lsp_types::Position {
+                        line: p.start as u32,
+                        character: 0,
                     },
-                    new_text: "\n".into(),
-                })
+                    end: lsp_types::Position {
+                        line: p.end as u32,
+                        character: self.code.line(p.end).chars().count() as u32,
+                    },
+                },
+                new_text: lines.join("\n"),
+            });
+        };
+        let trailers: Vec<usize> = self
+            .get_trailers_lines()
+            .into_iter()
+            .map(|n| n as usize)
+            .collect();
+        let mut edits = vec![];
+        let mut paragraph: Option<Paragraph> = None;
+        let (mut in_fence, mut in_trailer) = (false, false);
+        for (n, line) in self.get_body() {
+            let line = line.to_string();
+            let indented = line.starts_with([' ', '\t']);
+            let fence = line.trim_start().starts_with("```");
+            in_fence ^= fence;
+            in_trailer = trailers.contains(&n) || (in_trailer && indented);
+            if fence || in_fence || in_trailer || line.trim().is_empty() {
+                close(paragraph.take(), &mut edits);
+                continue;
+            }
+            let words = line.split_whitespace().map(str::to_owned);
+            let too_long = line.chars().count() > width;
+            let contiguous = paragraph.as_ref().is_some_and(|p| p.end + 1 == n);
+            if let Some(marker) = LIST_ITEM.find(&line) {
+                close(paragraph.take(), &mut edits);
+                let first_prefix = marker.as_str().trim_end().to_owned() + " ";
+                paragraph = Some(Paragraph {
+                    start: n,
+                    end: n,
+                    rest_prefix: " ".repeat(first_prefix.chars().count()),
+                    first_prefix,
+                    words: line[marker.end()..]
+                        .split_whitespace()
+                        .map(str::to_owned)
+                        .collect(),
+                    too_long,
+                });
+            } else if let (true, Some(p)) = (contiguous, paragraph.as_mut()) {
+                p.end = n;
+                p.words.extend(words);
+                p.too_long |= too_long;
+            } else {
+                close(paragraph.take(), &mut edits);
+                let indent: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+                if indent.chars().count() >= 4 || indent.contains('\t') {
+                    continue; // an indented code block
+                }
+                paragraph = Some(Paragraph {
+                    start: n,
+                    end: n,
+                    first_prefix: indent.clone(),
+                    rest_prefix: indent,
+                    words: words.collect(),
+                    too_long,
+                });
             }
-            if let Some(missing_trailer_padding_line) = self.get_missing_trailer_padding_line() {
-                fixes.push(lsp_types::TextEdit {
+        }
+        close(paragraph.take(), &mut edits);
+        edits
+    }
+
+    /// the edits from `format` that intersect `range`; none for an empty range
+    pub(crate) fn format_range(
+        &self,
+        options: &FormatOptions,
+        range: &lsp_types::Range,
+    ) -> Vec<lsp_types::TextEdit> {
+        if range.start == range.end {
+            return vec![];
+        }
+        self.format(options)
+            .into_iter()
+            // insertions at the range's start are in it; those at its end are not
+            .filter(|edit| edit.range.start < range.end && edit.range.end >= range.start)
+            .collect()
+    }
+
+    /// normalize trailer keys' casing and/or sort the trailer block, per `options`
+    fn format_trailers(&self, options: &FormatOptions) -> Vec<lsp_types::TextEdit> {
+        let mut edits = vec![];
+        let keys = self.get_trailer_keys();
+        if keys.is_empty() || (!options.normalize_trailer_keys && options.trailer_order.is_empty())
+        {
+            return edits;
+        }
+        let canonical = |key: &str| -> String {
+            options
+                .trailer_keys
+                .iter()
+                .map(|k| k.as_str())
+                .chain(CANONICAL_TRAILER_KEYS.iter().copied())
+                .find(|k| options.normalize_trailer_keys && k.eq_ignore_ascii_case(key))
+                .unwrap_or(key)
+                .to_owned()
+        };
+        let mut lines: Vec<(usize, String, String)> = keys
+            .iter()
+            .map(|(range, key)| {
+                let line = self.code.line(range.start.line as usize).to_string();
+                let rest = line[key.len()..].to_owned();
+                (range.start.line as usize, canonical(key), rest)
+            })
+            .collect();
+        let (first, last) = (lines[0].0, lines[lines.len() - 1].0);
+        // only sort a contiguous block; multi-line trailers can't be moved line-by-line
+        if !options.trailer_order.is_empty() && last - first + 1 == lines.len() {
+            let rank = |key: &str| {
+                options
+                    .trailer_order
+                    .iter()
+                    .position(|k| k.eq_ignore_ascii_case(key))
+                    .unwrap_or(usize::MAX)
+            };
+            // a stable sort preserves the relative order of repeated keys
+            lines.sort_by_key(|(_, key, _)| rank(key));
+            let new_text = lines
+                .iter()
+                .map(|(_, key, rest)| format!("{key}{rest}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let old_text = (first..=last)
+                .map(|n| self.code.line(n).to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            if new_text != old_text {
+                edits.push(lsp_types::TextEdit {
                     range: lsp_types::Range {
                         start: lsp_types::Position {
-                            line: (missing_trailer_padding_line + 1) as u32,
+                            line: first as u32,
                             character: 0,
                         },
                         end: lsp_types::Position {
-                            line: (missing_trailer_padding_line + 1) as u32,
-                            character: 0,
+                            line: last as u32,
+                            character: self.code.line(last).chars().count() as u32,
                         },
                     },
-                    new_text: "\n".into(),
-                })
+                    new_text,
+                });
+            }
+            return edits;
+        }
+        for ((range, old_key), (_, new_key, _)) in keys.iter().zip(lines) {
+            if *old_key != new_key {
+                edits.push(lsp_types::TextEdit {
+                    range: *range,
+                    new_text: new_key,
+                });
             }
+        }
+        edits
+    }
+}
+
+#[test]
+fn test_rename_trailer_key() {
+    let doc = GitCommitDocument::new().with_text(
+        "feat: x\n\nbody\n\nReviewed-by: a <a@example.com>\nSigned-off-by: b <b@example.com>\nreviewed-by: c <c@example.com>\n"
+            .into(),
+    );
+    let on_key = lsp_types::Position {
+        line: 4,
+        character: 3,
+    };
+    assert!(doc.trailer_key_at(&on_key).is_some());
+    let on_value = lsp_types::Position {
+        line: 4,
+        character: 16,
+    };
+    assert!(doc.trailer_key_at(&on_value).is_none());
+    let edits = doc.rename_trailer_key(&on_key, "Acked-by").unwrap();
+    assert_eq!(
+        edits.iter().map(|e| e.range.start.line).collect::<Vec<_>>(),
+        vec![4, 6]
+    );
+}
+
+#[test]
+fn test_scope_highlights() {
+    let doc = GitCommitDocument::new().with_text(
+        "fix(parser): handle CRLF\n\nThe parser and parser-utils now agree.\n# parser\n".into(),
+    );
+    let on_scope = lsp_types::Position {
+        line: 0,
+        character: 6,
+    };
+    let ranges: Vec<_> = doc
+        .scope_highlights(&on_scope)
+        .iter()
+        .map(|h| (h.range.start.line, h.range.start.character))
+        .collect();
+    assert_eq!(ranges, vec![(0, 4), (2, 4)]);
+    let on_message = lsp_types::Position {
+        line: 0,
+        character: 15,
+    };
+    assert!(doc.scope_highlights(&on_message).is_empty());
+}
+
+#[test]
+fn test_selection_range() {
+    let doc = GitCommitDocument::new()
+        .with_text("feat(api)!: add a thing\n\nsome body\n\nRefs: #1\n".into());
+    // (start, end) columns from the innermost range outward
+    let chain = |line: u32, character: u32| -> Vec<(u32, u32, u32)> {
+        let mut ranges = vec![];
+        let mut selection = Some(doc.selection_range(&lsp_types::Position { line, character }));
+        while let Some(s) = selection {
+            ranges.push((
+                s.range.start.line,
+                s.range.start.character,
+                s.range.end.character,
+            ));
+            selection = s.parent.map(|parent| *parent);
+        }
+        ranges
+    };
+    let (type_, scope, prefix, line) = ((0, 0, 4), (0, 4, 9), (0, 0, 11), (0, 0, 23));
+    assert_eq!(chain(0, 2), vec![type_, (0, 0, 9), prefix, line]);
+    assert_eq!(chain(0, 6), vec![scope, (0, 0, 9), prefix, line]);
+    assert_eq!(chain(0, 10), vec![(0, 9, 11), prefix, line]);
+    assert_eq!(chain(0, 15), vec![(0, 11, 23), line]);
+    assert_eq!(chain(4, 3), vec![(4, 0, 8)]);
+    assert_eq!(chain(2, 3), vec![(2, 3, 3)]);
+    // without a scope, the type and type+scope ranges coincide
+    let doc = GitCommitDocument::new().with_text("fix: x\n".into());
+    let selection = doc.selection_range(&lsp_types::Position {
+        line: 0,
+        character: 1,
+    });
+    assert_eq!(selection.range.end.character, 3);
+    assert_eq!(selection.parent.unwrap().range.end.character, 4);
+}
+
+#[test]
+#[allow(clippy::arc_with_non_send_sync)] // lint fns never leave the test's thread
+fn test_body_edit_reuses_subject_lints() {
+    use linting::{default::TYPE_ENUM, LintConfig, LintFn};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    struct CountingConfig(HashMap<&'static str, Arc<LintFn<'static>>>);
+    impl LintConfig for CountingConfig {
+        fn worktree_root(&self) -> Option<PathBuf> {
+            None
+        }
+        fn enabled_lint_codes(&self) -> Vec<&str> {
+            vec![TYPE_ENUM, linting::default::BODY_LEADING_BLANK]
+        }
+        fn get_test(&self, code: &str) -> Option<&Arc<LintFn<'_>>> {
+            self.0.get(code)
+        }
+    }
+    let runs = Arc::new(AtomicUsize::new(0));
+    let mut tests = linting::utils::construct_default_lint_tests_map(50);
+    let type_enum = tests[TYPE_ENUM].clone();
+    let counter = runs.clone();
+    tests.insert(
+        TYPE_ENUM,
+        Arc::new(move |doc| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            type_enum(doc)
+        }),
+    );
+    let cfg = CountingConfig(tests);
+    let mut doc = GitCommitDocument::new().with_text("bad: x\n\nbody\n".into());
+    let first = cfg.lint(&doc);
+    let insert = |line: u32, text: &str| lsp_types::TextDocumentContentChangeEvent {
+        range: Some(lsp_types::Range {
+            start: lsp_types::Position { line, character: 0 },
+            end: lsp_types::Position { line, character: 0 },
+        }),
+        range_length: None,
+        text: text.into(),
+    };
+    doc.edit(&[insert(2, "more ")]);
+    assert_eq!(cfg.lint(&doc), first);
+    assert_eq!(
+        runs.load(Ordering::Relaxed),
+        1,
+        "a body-only edit shouldn't rerun subject lints"
+    );
+    // without an edit in between, e.g. after a config change, everything reruns
+    cfg.lint(&doc);
+    assert_eq!(runs.load(Ordering::Relaxed), 2);
+    doc.edit(&[insert(0, "x")]);
+    let lints = cfg.lint(&doc);
+    assert_eq!(runs.load(Ordering::Relaxed), 3);
+    assert_eq!(lints[0].range.end.character, 4, "{lints:?}");
+}
+
+#[test]
+fn test_body_edit_reruns_subject_url() {
+    use linting::{default::SUBJECT_URL, utils::get_fix, LintConfig, LintFn};
+    use std::sync::Arc;
+    struct UrlConfig(HashMap<&'static str, Arc<LintFn<'static>>>);
+    impl LintConfig for UrlConfig {
+        fn worktree_root(&self) -> Option<PathBuf> {
+            None
+        }
+        fn enabled_lint_codes(&self) -> Vec<&str> {
+            vec![SUBJECT_URL]
+        }
+        fn get_test(&self, code: &str) -> Option<&Arc<LintFn<'_>>> {
+            self.0.get(code)
+        }
+    }
+    let mut tests = linting::utils::construct_default_lint_tests_map(50);
+    tests.insert(
+        SUBJECT_URL,
+        Arc::new(|doc| linting::default::check_subject_url(doc, SUBJECT_URL)),
+    );
+    let cfg = UrlConfig(tests);
+    let mut doc =
+        GitCommitDocument::new().with_text("fix: see https://example.com/x\n\nbody\n".into());
+    cfg.lint(&doc);
+    doc.edit(&[lsp_types::TextDocumentContentChangeEvent {
+        range: Some(lsp_types::Range {
+            start: lsp_types::Position {
+                line: 2,
+                character: 4,
+            },
+            end: lsp_types::Position {
+                line: 2,
+                character: 4,
+            },
+        }),
+        range_length: None,
+        text: "\nmore body".into(),
+    }]);
+    let lints = cfg.lint(&doc);
+    let fix = get_fix(&lints[0]).unwrap();
+    let (fixed, _) = apply_edits(&doc.code.to_string(), &fix);
+    assert_eq!(
+        fixed,
+        "fix: see\n\nbody\nmore body\n\nRefs: https://example.com/x\n"
+    );
+}
+
+#[test]
+fn test_multibyte_edits() {
+    let change = |start: (u32, u32), end: (u32, u32), text: &str| {
+        lsp_types::TextDocumentContentChangeEvent {
+            range: Some(lsp_types::Range {
+                start: lsp_types::Position {
+                    line: start.0,
+                    character: start.1,
+                },
+                end: lsp_types::Position {
+                    line: end.0,
+                    character: end.1,
+                },
+            }),
+            range_length: None,
+            text: text.into(),
+        }
+    };
+    let mut doc = GitCommitDocument::new().with_text("feat: 🎉 party\n\nbody ✨\n".into());
+    doc.edit(&[
+        change((0, 6), (0, 6), "🎈"),
+        change((0, 7), (0, 9), "🥳 "),
+        change((2, 5), (2, 6), "🌟\nmore 🍰"),
+        change((1, 0), (1, 0), "\n"),
+        change((3, 0), (4, 0), ""),
+        change((0, 4), (0, 4), "(é)"),
+    ]);
+    let expected = "feat(é): 🎈🥳 party\n\n\nmore 🍰\n";
+    assert_eq!(doc.code.to_string(), expected);
+    let fresh = GitCommitDocument::new().with_text(expected.into());
+    assert_eq!(
+        doc.syntax_tree.root_node().to_sexp(),
+        fresh.syntax_tree.root_node().to_sexp()
+    );
+    assert_eq!(
+        doc.subject.as_ref().map(|s| s.scope_text().to_owned()),
+        Some("(é)".to_owned())
+    );
+}
+
+#[test]
+fn test_document_symbols() {
+    let doc = GitCommitDocument::new().with_text(
+        [
+            "feat(api)!: add a thing",  // 0
+            "",                         // 1
+            "first paragraph",          // 2
+            "",                         // 3
+            "second paragraph",         // 4
+            "",                         // 5
+            "Refs: #1",                 // 6
+            "Signed-off-by: A <a@b.c>", // 7
+            "# a comment",
+        ]
+        .join("\n"),
+    );
+    // `name "detail" range [children]` for each symbol
+    fn outline(symbols: &[lsp_types::DocumentSymbol]) -> Vec<String> {
+        symbols
+            .iter()
+            .map(|s| {
+                let children = outline(s.children.as_deref().unwrap_or_default());
+                format!(
+                    "{} {:?} {}:{}-{}:{} [{}]",
+                    s.name,
+                    s.detail.as_deref().unwrap_or_default(),
+                    s.range.start.line,
+                    s.range.start.character,
+                    s.range.end.line,
+                    s.range.end.character,
+                    children.join(", ")
+                )
+            })
+            .collect()
+    }
+    assert_eq!(
+        outline(&doc.document_symbols()),
+        vec![
+            r#"Subject "feat(api)!: add a thing" 0:0-0:23 [type "feat" 0:0-0:4 [], scope "(api)" 0:4-0:9 [], description "add a thing" 0:12-0:23 []]"#,
+            r#"Body "" 2:0-4:16 []"#,
+            r##"Trailers "" 6:0-7:24 [Refs "#1" 6:0-6:8 [], Signed-off-by "A <a@b.c>" 7:0-7:24 []]"##,
+        ]
+    );
+    // no scope, body, or trailers
+    let doc = GitCommitDocument::new().with_text("fix: x\n".into());
+    assert_eq!(
+        outline(&doc.document_symbols()),
+        vec![r#"Subject "fix: x" 0:0-0:6 [type "fix" 0:0-0:3 [], description "x" 0:5-0:6 []]"#]
+    );
+}
+
+#[test]
+fn test_scissors() {
+    use linting::default;
+    let long = "x".repeat(120);
+    let text = [
+        "feat: add a thing",
+        "",
+        "see src/lib.rs",
+        "",
+        "# Please enter the commit message for your changes.",
+        SCISSORS,
+        "# Do not modify or remove the line above.",
+        "# Everything below it will be ignored.",
+        "diff --git a/src/lib.rs b/src/lib.rs",
+        "+Signed-off-by: someone",
+        &format!("+{long} #123"),
+        "<<<<<<< HEAD",
+    ]
+    .join("\n");
+    let doc = GitCommitDocument::new().with_text(text);
+    assert_eq!(doc.scissors_line(), Some(5));
+    let body: Vec<usize> = doc.get_body().map(|(n, _)| n).collect();
+    assert_eq!(body, vec![1, 2, 3]);
+    assert!(default::check_body_line_length(&doc, default::BODY_MAX_LINE_LENGTH, 100).is_empty());
+    assert!(default::check_total_length(&doc, "total", 50).is_empty());
+    assert!(doc.get_links().iter().all(|link| link.range.start.line < 5));
+    assert!(doc.get_trailers().is_empty());
+    // nothing above the scissors line means there's no subject, not that the diff is the subject
+    let doc = GitCommitDocument::new().with_text(format!("\n{SCISSORS}\ndiff --git a/x b/x\n"));
+    assert!(doc.subject.is_none());
+    assert_eq!(doc.get_body().count(), 1);
+}
+
+#[test]
+fn test_subject_length_hint() {
+    let hint = |text: &str, cutoff: u16| {
+        GitCommitDocument::new()
+            .with_text(text.into())
+            .subject_length_hint(cutoff)
+            .map(|hint| {
+                let lsp_types::InlayHintLabel::String(label) = hint.label else {
+                    unreachable!()
+                };
+                (hint.position.line, hint.position.character, label)
+            })
+    };
+    let near = "feat: add a thing"; // 17 chars
+    assert_eq!(hint(near, 20), Some((0, 17, "3 left".into())));
+    assert_eq!(
+        hint(&format!("{near}\n\nbody"), 17),
+        Some((0, 17, "0 left".into()))
+    );
+    assert_eq!(
+        hint(&format!("# comment\n{near}\n"), 12),
+        Some((1, 17, "5 over".into()))
+    );
+    assert_eq!(hint(near, 0), None);
+    assert_eq!(hint("\n# only comments\n", 50), None);
+}
+
+#[test]
+fn test_folding_ranges() {
+    let doc = GitCommitDocument::new().with_text(
+        [
+            "feat: x",                  // 0
+            "",                         // 1
+            "first paragraph",          // 2
+            "wraps here",               // 3
+            "",                         // 4
+            "one-liner",                // 5
+            "",                         // 6
+            "second paragraph",         // 7
+            "# a comment",              // 8
+            "after the comment",        // 9
+            "and more",                 // 10
+            "",                         // 11
+            "Refs: #1",                 // 12
+            "Signed-off-by: A <a@b.c>", // 13
+            "",
+        ]
+        .join("\n"),
+    );
+    let ranges: Vec<_> = doc
+        .folding_ranges()
+        .iter()
+        .map(|range| (range.start_line, range.end_line))
+        .collect();
+    assert_eq!(ranges, vec![(2, 3), (9, 10), (12, 13)]);
+}
+
+#[test]
+fn test_semver_lenses() {
+    let lens = |text: &str| -> Vec<String> {
+        GitCommitDocument::new()
+            .with_text(text.into())
+            .semver_lenses(crate::config::SemverBump::for_type)
+            .into_iter()
+            .map(|lens| lens.command.unwrap().title)
+            .collect()
+    };
+    assert_eq!(lens("feat!: x"), vec!["⬆ major release"]);
+    assert_eq!(lens("feat: x"), vec!["⬆ minor release"]);
+    assert_eq!(lens("fix: x"), vec!["⬆ patch release"]);
+    assert_eq!(lens("docs: x"), vec!["no release"]);
+    assert_eq!(
+        lens("fix: x\n\nBREAKING CHANGE: y\n"),
+        vec!["⬆ major release"]
+    );
+    assert!(lens("").is_empty());
+}
+
+#[test]
+fn test_format_range() {
+    let doc = GitCommitDocument::new()
+        .with_text("feat:add a thing\n\nbody\nSigned-off-by: A <a@b.c>\n".into());
+    let options = FormatOptions::default();
+    let lines = |edits: Vec<lsp_types::TextEdit>| -> Vec<u32> {
+        edits.iter().map(|edit| edit.range.start.line).collect()
+    };
+    assert_eq!(lines(doc.format(&options)), vec![0, 3]);
+    let subject = lsp_types::Range {
+        start: lsp_types::Position {
+            line: 0,
+            character: 0,
+        },
+        end: lsp_types::Position {
+            line: 0,
+            character: 16,
+        },
+    };
+    assert_eq!(lines(doc.format_range(&options, &subject)), vec![0]);
+    let empty = lsp_types::Range {
+        start: subject.start,
+        end: subject.start,
+    };
+    assert!(doc.format_range(&options, &empty).is_empty());
+}
+
+#[test]
+fn test_grammar_error() {
+    // e.g. a grammar generated by an incompatible tree-sitter CLI
+    let version = tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION - 1;
+    let err = check_grammar_version(version).err().unwrap();
+    assert_eq!(err, GrammarError { version });
+    assert!(
+        err.to_string().contains(&format!("ABI version {version}")),
+        "{err}"
+    );
+    assert!(check_grammar_version(tree_sitter::LANGUAGE_VERSION + 1).is_err());
+    assert!(check_grammar_version(tree_sitter::LANGUAGE_VERSION).is_ok());
+    assert!(GitCommitDocument::try_new().is_ok());
+}
+
+#[test]
+fn test_format_line_ending() {
+    let inserted = |text: &str, line_ending: LineEnding| -> Vec<String> {
+        let doc = GitCommitDocument::new().with_text(text.into());
+        let options = FormatOptions {
+            line_ending,
+            ..Default::default()
         };
-        // TODO: ensure trailers are at the end of the commit message
-        fixes
+        doc.format(&options)
+            .into_iter()
+            .skip(1) // the subject
+            .map(|edit| edit.new_text)
+            .collect()
+    };
+    let crlf = "feat: x\r\nbody\r\n";
+    assert_eq!(inserted(crlf, LineEnding::Auto), vec!["\r\n"]);
+    assert_eq!(inserted(crlf, LineEnding::Lf), vec!["\n"]);
+    assert_eq!(inserted("feat: x\nbody\n", LineEnding::Auto), vec!["\n"]);
+    assert_eq!(inserted("feat: x\nbody\n", LineEnding::Crlf), vec!["\r\n"]);
+}
+
+#[test]
+fn test_is_breaking() {
+    let is_breaking = |text: &str| {
+        GitCommitDocument::new()
+            .with_text(text.into())
+            .is_breaking()
+    };
+    assert!(is_breaking("feat!: x"));
+    assert!(is_breaking("feat(api)!: x"));
+    assert!(is_breaking("feat: x\n\nBREAKING CHANGE: y\n"));
+    assert!(!is_breaking("feat: x!"));
+    assert!(!is_breaking("feat: x\n\nbreaking changes are fun\n"));
+}
+
+#[test]
+fn test_format_scope_aliases() {
+    let options = FormatOptions {
+        scope_aliases: [("fe".to_owned(), "ui".to_owned())].into(),
+        ..Default::default()
+    };
+    let format = |text: &str| {
+        let doc = GitCommitDocument::new().with_text(text.into());
+        doc.format(&options)[0].new_text.clone()
+    };
+    assert_eq!(format("feat(fe): x\n"), "feat(ui): x");
+    assert_eq!(format("feat[fe]!: x\n"), "feat[ui]!: x");
+    assert_eq!(format("feat(ui): x\n"), "feat(ui): x");
+    assert_eq!(format("feat(fed): x\n"), "feat(fed): x");
+}
+
+#[test]
+fn test_reference_links() {
+    let doc = GitCommitDocument::new().with_text(
+        "feat: x\n\nThanks @alice, see #12 and org/lib#3.\nMail a@example.com\n\nRefs: GH-4\n# #5\n"
+            .into(),
+    );
+    let references: Vec<(u32, u32, u32, String)> = doc
+        .get_reference_links()
+        .into_iter()
+        .map(|link| {
+            let reference = link.data.unwrap()["reference"].as_str().unwrap().to_owned();
+            let range = link.range;
+            (
+                range.start.line,
+                range.start.character,
+                range.end.character,
+                reference,
+            )
+        })
+        .collect();
+    assert_eq!(
+        references,
+        vec![
+            (2, 7, 13, "@alice".to_owned()),
+            (2, 19, 22, "#12".to_owned()),
+            (2, 27, 36, "org/lib#3".to_owned()),
+            (5, 6, 10, "GH-4".to_owned()),
+        ]
+    );
+    let target = |remote: &str, reference: &str| {
+        let remote = crate::git::WebRemote::parse(remote).unwrap();
+        reference_target(&remote, reference).map(|url| url.to_string())
+    };
+    let github = "git@github.com:owner/repo.git";
+    let gitlab = "https://gitlab.com/group/repo.git";
+    let bitbucket = "git@bitbucket.org:owner/repo.git";
+    assert_eq!(
+        target(github, "#12").as_deref(),
+        Some("https://github.com/owner/repo/issues/12")
+    );
+    assert_eq!(
+        target(github, "GH-4").as_deref(),
+        Some("https://github.com/owner/repo/issues/4")
+    );
+    assert_eq!(
+        target(github, "org/lib#3").as_deref(),
+        Some("https://github.com/org/lib/issues/3")
+    );
+    assert_eq!(
+        target(github, "@alice").as_deref(),
+        Some("https://github.com/alice")
+    );
+    assert_eq!(
+        target(gitlab, "#12").as_deref(),
+        Some("https://gitlab.com/group/repo/-/issues/12")
+    );
+    assert_eq!(target(gitlab, "GH-4"), None);
+    assert_eq!(
+        target(bitbucket, "#12").as_deref(),
+        Some("https://bitbucket.org/owner/repo/issues/12")
+    );
+}
+
+#[test]
+fn test_get_trailers() {
+    let doc = GitCommitDocument::new()
+        .with_text("feat: x\n\nbody\n\nSigned-off-by: A U Thor <a@example.com>\nRefs: #1\n".into());
+    assert_eq!(
+        doc.get_trailers(),
+        vec![
+            (
+                "Signed-off-by".to_owned(),
+                "A U Thor <a@example.com>".to_owned()
+            ),
+            ("Refs".to_owned(), "#1".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn test_completion_preview() {
+    let doc = GitCommitDocument::new().with_text("fix (parser) !:handle CRLF\n".into());
+    assert_eq!(
+        doc.completion_preview("type", "feat").as_deref(),
+        Some("feat(parser)!: handle CRLF")
+    );
+    assert_eq!(
+        doc.completion_preview("scope", "lexer").as_deref(),
+        Some("fix(lexer)!: handle CRLF")
+    );
+    let unscoped = GitCommitDocument::new().with_text("fix: handle CRLF\n".into());
+    assert_eq!(
+        unscoped.completion_preview("scope", "lexer").as_deref(),
+        Some("fix(lexer): handle CRLF")
+    );
+    assert_eq!(unscoped.completion_preview("message", "x"), None);
+}
+
+#[test]
+fn test_profile() {
+    let profile = |text: &str| GitCommitDocument::new().with_text(text.into()).profile();
+    assert_eq!(
+        profile("# cconvention-profile: release\nchore: release v1.2.3\n").as_deref(),
+        Some("release")
+    );
+    assert_eq!(
+        profile("feat: x\n\n#cconvention-profile:release\n# Please enter...\n").as_deref(),
+        Some("release")
+    );
+    // only the first comment line counts
+    assert_eq!(
+        profile("feat: x\n\n# Please enter...\n# cconvention-profile: release\n"),
+        None
+    );
+    assert_eq!(profile("feat: x\n\n# cconvention-profile:\n"), None);
+    assert_eq!(profile("feat: x\n"), None);
+}
+
+#[test]
+fn test_comments_are_content() {
+    let text = "# heading\n\n# not a comment\n";
+    let doc = GitCommitDocument::new().with_text(text.into());
+    assert!(doc.subject.is_none());
+    assert_eq!(doc.get_body().count(), 1); // just the blank line
+
+    let doc = GitCommitDocument::new()
+        .with_comments_are_content(true)
+        .with_text(text.into());
+    assert_eq!(doc.subject.as_ref().unwrap().line_number, 0);
+    assert_eq!(doc.get_body().count(), 2);
+    assert!(doc.get_comment_block_start().is_none());
+}
+
+#[test]
+fn test_parse_timeout() {
+    let text = "feat: x\n\n".to_string() + &"a long body line\n".repeat(10_000);
+    let doc = GitCommitDocument::new()
+        .with_parse_timeout(1)
+        .with_text(text.clone());
+    // the message is unparsed, but still usable
+    assert_eq!(doc.syntax_tree.root_node().end_byte(), 0);
+    let _ = doc.get_mandatory_lints();
+    let _ = doc.format(&FormatOptions::default());
+
+    let doc = GitCommitDocument::new().with_text(text);
+    assert!(doc.syntax_tree.root_node().end_byte() > 0);
+}
+
+#[test]
+fn test_format_trailers() {
+    let doc = GitCommitDocument::new().with_text(
+        "feat: x\n\nbody\n\nsigned-off-by: a <a@example.com>\nRefs: #1\nco-authored-by: b <b@example.com>\nrefs: #2\n"
+            .into(),
+    );
+    let normalize = FormatOptions {
+        normalize_trailer_keys: true,
+        ..Default::default()
+    };
+    let edits = doc.format_trailers(&normalize);
+    assert_eq!(
+        edits
+            .iter()
+            .map(|e| (e.range.start.line, e.new_text.as_str()))
+            .collect::<Vec<_>>(),
+        vec![(4, "Signed-off-by"), (6, "Co-authored-by"), (7, "Refs")]
+    );
+
+    let sort = FormatOptions {
+        normalize_trailer_keys: true,
+        trailer_order: vec!["refs".into(), "Co-authored-by".into()],
+        ..Default::default()
+    };
+    let edits = doc.format_trailers(&sort);
+    assert_eq!(edits.len(), 1);
+    assert_eq!(
+        edits[0].new_text,
+        "Refs: #1\nRefs: #2\nCo-authored-by: b <b@example.com>\nSigned-off-by: a <a@example.com>"
+    );
+    assert_eq!((edits[0].range.start.line, edits[0].range.end.line), (4, 7));
+    assert!(doc.format_trailers(&Default::default()).is_empty());
+}
+
+#[test]
+fn test_move_trailers_to_end() {
+    let format = |text: &str| {
+        let doc = GitCommitDocument::new().with_text(text.into());
+        let edits = doc.misplaced_trailers_fix();
+        apply_edits(text, &edits).0
+    };
+    assert_eq!(
+        format("feat: x\n\nbody\n\nRefs: #1\n\nmore body\n"),
+        "feat: x\n\nbody\n\nmore body\n\nRefs: #1\n"
+    );
+    assert_eq!(
+        format("feat: x\n\nRefs: #1\nmore body\n\nSigned-off-by: a <a@b.c>\n# comment\n"),
+        "feat: x\n\nmore body\n\nRefs: #1\nSigned-off-by: a <a@b.c>\n# comment\n"
+    );
+    // continuation lines move with their trailer
+    assert_eq!(
+        format("feat: x\n\nbody\n\nRefs: #1,\n  #2\n\nmore body\n"),
+        "feat: x\n\nbody\n\nmore body\n\nRefs: #1,\n  #2\n"
+    );
+    for placed in [
+        "feat: x\n\nbody\n\nRefs: #1\nSigned-off-by: a <a@b.c>\n# comment\n",
+        "feat: x\n\nbody\n",
+        "feat: x\n",
+    ] {
+        let doc = GitCommitDocument::new().with_text(placed.into());
+        assert_eq!(doc.misplaced_trailers_fix(), vec![], "{placed:?}");
+    }
+    // formatting applies the move without overlapping edits
+    let doc =
+        GitCommitDocument::new().with_text("feat: x\n\nbody\n\nrefs: #1\n\nmore body\n".into());
+    let edits = doc.format(&FormatOptions {
+        normalize_trailer_keys: true,
+        ..Default::default()
+    });
+    assert_eq!(
+        apply_edits(&doc.code.to_string(), &edits).0,
+        "feat: x\n\nbody\n\nmore body\n\nrefs: #1\n"
+    );
+}
+
+#[test]
+fn test_body_wrap() {
+    let wrap = |text: &str| {
+        let doc = GitCommitDocument::new().with_text(text.into());
+        apply_edits(text, &doc.body_wrap_fix(30)).0
+    };
+    assert_eq!(
+        wrap("feat: x\n\nthis prose paragraph runs well past the thirty column limit\nand continues\n\nshort\n"),
+        "feat: x\n\nthis prose paragraph runs well\npast the thirty column limit\nand continues\n\nshort\n"
+    );
+    assert_eq!(
+        wrap("feat: x\n\n- a bulleted item that is much too long to fit\n- short\n10. a numbered item that is also too long\n"),
+        "feat: x\n\n- a bulleted item that is much\n  too long to fit\n- short\n10. a numbered item that is\n    also too long\n"
+    );
+    let unwrapped = [
+        "feat: x\n\n```\nlet fenced = \"code that is far longer than thirty columns\";\n```\n",
+        "feat: x\n\n    let indented = \"code that is far longer than thirty columns\";\n",
+        "feat: x\n\nbody\n\nCo-authored-by: Somebody With A Long Name <somebody@example.com>\n",
+        "feat: x\n\nshort lines\nstay as they are\n",
+    ];
+    for text in unwrapped {
+        assert_eq!(wrap(text), text);
     }
+    // off by default
+    let doc = GitCommitDocument::new().with_text(format!("feat: x\n\n{}\n", "word ".repeat(30)));
+    assert_eq!(doc.format(&FormatOptions::default()).len(), 1);
 }
@@ -39,12 +39,18 @@ impl PrefixLengths {
             Done,
         }
         let mut state = State::Type;
+        // legacy Angular-style messages sometimes use `type[scope]: `
+        let mut closer = ')';
         let mut cursor = 0u8; // the byte offset of the current character
         let mut chars = line.chars();
         while let Some(c) = chars.next() {
             state = match state {
                 State::Type => match c {
                     '(' => State::Scope,
+                    '[' if cursor > 0 => {
+                        closer = ']';
+                        State::Scope
+                    }
                     ')' => State::ScopeDone, // unexpected, but continue anyway
                     '!' => State::Rest,
                     ':' => State::Done,
@@ -53,6 +59,10 @@ impl PrefixLengths {
                 },
                 State::TypeRecovery(n) => match c {
                     '(' => State::Scope,
+                    '[' => {
+                        closer = ']';
+                        State::Scope
+                    }
                     ')' => {
                         // we probably just finished the scope
                         offsets.type_ = n;
@@ -72,11 +82,11 @@ impl PrefixLengths {
                     _ => state, // keep scanning for the end of the type
                 },
                 State::Scope => match c {
-                    ')' => State::ScopeDone,
+                    c if c == closer => State::ScopeDone,
                     '!' | ':' | ' ' | '\t' => {
                         let candidate_terminator = line[cursor as usize + 1..]
                             .chars()
-                            .any(|t| t == ':' || t == '!' || t == ')');
+                            .any(|t| t == ':' || t == '!' || t == closer);
                         if !candidate_terminator && (c == ':' || c == '!') {
                             State::Rest
                         } else {
@@ -105,7 +115,7 @@ impl PrefixLengths {
                 //     _ => state,
                 // },
                 State::ScopeRecovery(n) => match c {
-                    ')' => State::ScopeDone,
+                    c if c == closer => State::ScopeDone,
                     '(' => state, // unexpected, keep scanning in hope of seeing the end of the scope
                     '!' | ':' => {
                         match &line[n as usize..(n + 1) as usize] {
@@ -236,9 +246,9 @@ fn test_subject_lexing() {
                 "test case failed: ./subject_test_cases.txt:{}",
                 &test_cases[i].0 .0 + 1
             );
-            println!("input:    {}", &test_cases[i].0 .1);
+            println!("input:    {}", test_cases[i].0 .1);
             println!("actual:   {}", result);
-            println!("expected: {}\n", &test_cases[i].1);
+            println!("expected: {}\n", test_cases[i].1);
         }
     }
     assert!(results.iter().all(|(_, ok)| *ok));
@@ -276,12 +286,29 @@ impl Subject {
     pub(crate) fn rest_text(&self) -> &str {
         &self.line[self.offsets.rest_byte_range()]
     }
-    pub(crate) fn prefix_text(&self) -> &str {
+    pub fn prefix_text(&self) -> &str {
         &self.line[self.offsets.prefix_byte_range()]
     }
-    pub(crate) fn message_text(&self) -> &str {
+    pub fn message_text(&self) -> &str {
         &self.line[self.offsets.prefix_end_byte_offset()..]
     }
+    /// the scope without its `()` or `[]`, e.g. `api` for `feat(api): `
+    pub fn scope_name(&self) -> &str {
+        self.scope_text()
+            .trim_start_matches(['(', '['])
+            .trim_end_matches([')', ']'])
+    }
+    /// whether the scope ends with its closing `)` or `]`
+    pub fn scope_is_closed(&self) -> bool {
+        let scope_text = self.scope_text();
+        (scope_text.starts_with('(') && scope_text.ends_with(')'))
+            || (scope_text.starts_with('[') && scope_text.ends_with(']'))
+    }
+    /// whether the scope is wrapped in `[]` rather than `()`
+    pub fn scope_is_bracketed(&self) -> bool {
+        let scope_text = self.scope_text();
+        scope_text.starts_with('[') && scope_text.ends_with(']')
+    }
 }
 
 // lookaround & ranges
@@ -335,8 +362,10 @@ impl Subject {
         }
         let start = self.type_text().chars().count();
         let end = start + scope_text.chars().count();
+        // brackets are reported and fixed by the `bracket_scope` lint
+        let bracketed = self.scope_is_bracketed();
         if let Some(open) = scope_text.chars().next() {
-            if open != '(' {
+            if open != '(' && !bracketed {
                 let mut lint = utils::make_line_diagnostic(
                     "Scope should start with '('.".into(),
                     self.line_number as usize,
@@ -349,7 +378,7 @@ impl Subject {
             }
         }
         if let Some(close) = scope_text.chars().last() {
-            if close != ')' {
+            if close != ')' && !bracketed {
                 let mut lint = utils::make_line_diagnostic(
                     "Scope should end with ')'".into(),
                     self.line_number as usize,
@@ -364,7 +393,7 @@ impl Subject {
         }
         if !scope_text
             .chars()
-            .any(|c| !c.is_whitespace() && c != '(' && c != ')')
+            .any(|c| !c.is_whitespace() && !"()[]".contains(c))
         {
             let mut lint = utils::make_line_diagnostic(
                 "Missing scope text.".into(),
@@ -454,7 +483,9 @@ impl Subject {
         lints
     }
 
-    pub(crate) fn auto_format(&self) -> String {
+    /// the canonical form of the subject line; `parenthesize_brackets` rewrites `[scope]` to
+    /// `(scope)`
+    pub(crate) fn auto_format(&self, parenthesize_brackets: bool) -> String {
         let mut formatted = String::with_capacity(self.line.len());
         for c in self.type_text().chars() {
             if !c.is_whitespace() && !":!()".contains(c) {
@@ -462,8 +493,14 @@ impl Subject {
             }
         }
         let scope_text = self.scope_text();
+        let bracketed = parenthesize_brackets && self.scope_is_bracketed();
         if !scope_text.is_empty() {
             for c in scope_text.chars() {
+                let c = match c {
+                    '[' if bracketed => '(',
+                    ']' if bracketed => ')',
+                    c => c,
+                };
                 if !c.is_whitespace() && !":!".contains(c) {
                     formatted.write_char(c).unwrap();
                 }
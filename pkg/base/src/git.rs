@@ -92,6 +92,145 @@ pub fn get_worktree_root(path: &Path) -> Result<PathBuf, Box<dyn std::error::Err
         )
 }
 
+/// the URL of the `origin` remote, if any
+pub fn remote_url(cwd: Option<PathBuf>) -> Option<String> {
+    git(&["config", "--get", "remote.origin.url"], cwd)
+        .ok() // fail silently: a repo needn't have a remote
+        .map(|url| url.trim().to_owned())
+        .filter(|url| !url.is_empty())
+}
+
+/// the forges whose web UIs issue and PR references can link to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Forge {
+    GitHub,
+    GitLab,
+    Bitbucket,
+}
+
+/// where a remote repository lives on the web, e.g. `github.com` and `owner/repo`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebRemote {
+    pub forge: Forge,
+    pub host: String,
+    pub repo: String,
+}
+
+impl WebRemote {
+    /// normalize an SSH or HTTPS remote URL like `git@github.com:owner/repo.git` or
+    /// `https://user@bitbucket.org/owner/repo.git`; `None` for other forges and local paths
+    pub fn parse(url: &str) -> Option<Self> {
+        let url = url.trim();
+        let (authority, path) = match url.split_once("://") {
+            Some((_, rest)) => rest.split_once('/')?,
+            None => url.split_once(':')?, // scp-like: `user@host:owner/repo`
+        };
+        let host = authority.rsplit('@').next()?.split(':').next()?; // drop the user and port
+        let host = host.to_ascii_lowercase();
+        let forge = if host.contains("github") {
+            Forge::GitHub
+        } else if host.contains("gitlab") {
+            Forge::GitLab
+        } else if host.contains("bitbucket") {
+            Forge::Bitbucket
+        } else {
+            return None;
+        };
+        let repo = path.trim_matches('/');
+        let repo = repo.strip_suffix(".git").unwrap_or(repo);
+        if !repo.contains('/') {
+            return None;
+        }
+        Some(WebRemote {
+            forge,
+            host,
+            repo: repo.to_owned(),
+        })
+    }
+    /// the web URL of issue or PR `number` in `repo`, defaulting to this remote's repo
+    pub fn issue_url(&self, repo: Option<&str>, number: u64) -> String {
+        let issues = match self.forge {
+            Forge::GitLab => "-/issues",
+            Forge::GitHub | Forge::Bitbucket => "issues",
+        };
+        let repo = repo.unwrap_or(&self.repo);
+        format!("https://{}/{repo}/{issues}/{number}", self.host)
+    }
+    /// the web URL of `user`'s profile
+    pub fn user_url(&self, user: &str) -> String {
+        format!("https://{}/{user}", self.host)
+    }
+}
+
+#[test]
+fn test_web_remote() {
+    let parse = |url: &str| WebRemote::parse(url).map(|r| (r.forge, r.host, r.repo));
+    let remote = |forge, host: &str, repo: &str| Some((forge, host.to_owned(), repo.to_owned()));
+    assert_eq!(
+        parse("git@github.com:owner/repo.git"),
+        remote(Forge::GitHub, "github.com", "owner/repo")
+    );
+    assert_eq!(
+        parse("https://github.com/owner/repo"),
+        remote(Forge::GitHub, "github.com", "owner/repo")
+    );
+    assert_eq!(
+        parse("ssh://git@gitlab.example.com:2222/group/sub/repo.git"),
+        remote(Forge::GitLab, "gitlab.example.com", "group/sub/repo")
+    );
+    assert_eq!(
+        parse("https://user@bitbucket.org/owner/repo.git\n"),
+        remote(Forge::Bitbucket, "bitbucket.org", "owner/repo")
+    );
+    assert_eq!(parse("git@example.com:owner/repo.git"), None);
+    assert_eq!(parse("/srv/git/repo.git"), None);
+}
+
+/// the commit hash of HEAD, if any
+pub fn head(cwd: Option<PathBuf>) -> Option<String> {
+    git(&["rev-parse", "--verify", "--quiet", "HEAD"], cwd)
+        .ok() // fail silently: a fresh repo has no HEAD
+        .map(|hash| hash.trim().to_owned())
+        .filter(|hash| !hash.is_empty())
+}
+
+/// the best common ancestor of `reference` and HEAD
+pub fn merge_base(
+    reference: &str,
+    cwd: Option<PathBuf>,
+) -> Result<String, Box<dyn std::error::Error + Sync + Send>> {
+    let hash = git(&["merge-base", reference, "HEAD"], cwd)?;
+    let hash = hash.trim();
+    if hash.is_empty() {
+        return Err(format!("no merge base between {reference} and HEAD").into());
+    }
+    Ok(hash.to_owned())
+}
+
+/// the short name of the currently checked-out branch, if any
+pub fn current_branch(cwd: Option<PathBuf>) -> Option<String> {
+    git(&["symbolic-ref", "--quiet", "--short", "HEAD"], cwd)
+        .ok() // fail silently: a detached HEAD has no branch
+        .map(|branch| branch.trim().to_owned())
+        .filter(|branch| !branch.is_empty())
+}
+
+/// the email git will record for new commits, per `git config user.email`
+pub fn user_email(cwd: Option<PathBuf>) -> Option<String> {
+    git(&["config", "user.email"], cwd)
+        .ok() // fail silently: the email may be unset
+        .map(|email| email.trim().to_owned())
+        .filter(|email| !email.is_empty())
+}
+
+/// the author email of `revision`
+pub fn author_email(revision: &str, cwd: Option<PathBuf>) -> Option<String> {
+    git(&["show", "--no-patch", "--format=%ae", revision], cwd)
+        .ok()
+        .map(|email| email.trim().to_owned())
+        .filter(|email| !email.is_empty())
+}
+
 pub fn staged_files(cwd: Option<PathBuf>) -> Vec<String> {
     git(
             &["diff", "--name-only", "--cached"],
@@ -105,13 +244,154 @@ pub fn staged_files(cwd: Option<PathBuf>) -> Vec<String> {
         .collect()
 }
 
-pub fn related_commits(staged_files: &[String], cwd: Option<PathBuf>) -> Vec<String> {
-    let mut args = vec!["log", "--format=%s", "--max-count=1000", "--"];
-    args.extend(staged_files.iter().map(|s| s.as_str()));
-    git(args.as_slice(), cwd).unwrap_or(String::new()) // fail silently, returning an empty string if git fails
+/// the files changed by `revision`
+pub fn changed_files(revision: &str, cwd: Option<PathBuf>) -> Vec<String> {
+    git(&["show", "--name-only", "--format=", revision], cwd)
+        .unwrap_or_default() // fail silently, returning an empty string if git fails
         .lines()
         .map(|line| line.trim())
         .filter(|line| !line.is_empty())
-        .map(|s| s.to_owned())
+        .map(|line| line.to_owned())
         .collect()
 }
+
+/// sum the added and deleted lines in `--numstat` output, skipping binary files
+fn sum_numstat(numstat: &str) -> usize {
+    numstat
+        .lines()
+        .flat_map(|line| line.split('\t').take(2))
+        .filter_map(|count| count.parse::<usize>().ok()) // binary files' counts are `-`
+        .sum()
+}
+
+/// the number of lines added or deleted in the staged changes
+pub fn staged_line_count(cwd: Option<PathBuf>) -> usize {
+    // fail silently, counting 0 lines if git fails
+    sum_numstat(&git(&["diff", "--cached", "--numstat"], cwd).unwrap_or_default())
+}
+
+/// the number of lines added or deleted by `revision`
+pub fn changed_line_count(revision: &str, cwd: Option<PathBuf>) -> usize {
+    // fail silently, counting 0 lines if git fails
+    sum_numstat(&git(&["show", "--numstat", "--format=", revision], cwd).unwrap_or_default())
+}
+
+/// the subjects of the `count` commits before `revision`, or before the commit being written
+/// if `revision` is `None`, most recent first
+pub fn recent_subjects(revision: Option<&str>, count: usize, cwd: Option<PathBuf>) -> Vec<String> {
+    let max_count = format!("--max-count={count}");
+    let mut args = vec!["log", "--format=%s", max_count.as_str()];
+    if let Some(revision) = revision {
+        args.extend(["--skip=1", revision]);
+    }
+    git(&args, cwd)
+        .unwrap_or_default() // fail silently: a fresh repo has no history
+        .lines()
+        .map(|line| line.trim().to_owned())
+        .collect()
+}
+
+/// `Name <email>` for each distinct author of the last 1000 commits, most recent first
+pub fn recent_authors(cwd: Option<PathBuf>) -> Vec<String> {
+    let mut authors: Vec<String> = vec![];
+    for author in git(&["log", "--format=%an <%ae>", "--max-count=1000"], cwd)
+        .unwrap_or_default() // fail silently: a fresh repo has no history
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+    {
+        if !authors.iter().any(|a| a == author) {
+            authors.push(author.to_owned());
+        }
+    }
+    authors
+}
+
+/// the subject and changed files of each of the last `limit` commits touching `paths`
+pub fn related_commits(
+    paths: &[String],
+    limit: usize,
+    cwd: Option<PathBuf>,
+) -> Vec<(String, Vec<String>)> {
+    let max_count = format!("--max-count={limit}");
+    let mut args = vec!["log", "--format=%x00%s", "--name-only", &max_count, "--"];
+    args.extend(paths.iter().map(|s| s.as_str()));
+    git(args.as_slice(), cwd)
+        .unwrap_or_default() // fail silently, returning an empty string if git fails
+        .split('\0')
+        .filter_map(|commit| {
+            let mut lines = commit.lines().map(|line| line.trim());
+            let subject = lines.next().filter(|subject| !subject.is_empty())?;
+            let files = lines
+                .filter(|line| !line.is_empty())
+                .map(|line| line.to_owned())
+                .collect();
+            Some((subject.to_owned(), files))
+        })
+        .collect()
+}
+
+#[test]
+fn test_sum_numstat() {
+    let numstat = "10\t2\tsrc/lib.rs\n-\t-\ticon.png\n0\t7\tREADME.md\n";
+    assert_eq!(sum_numstat(numstat), 19);
+    assert_eq!(sum_numstat(""), 0);
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+pub use temp_repo::TempRepo;
+
+#[cfg(any(test, feature = "test-utils"))]
+mod temp_repo {
+    use super::git;
+    use std::path::{Path, PathBuf};
+
+    /// A fresh git repository under the system's temp dir, for tests. It's deleted when dropped, even
+    /// if the test panics, and every instance gets its own directory so parallel tests don't collide.
+    pub struct TempRepo(PathBuf);
+
+    impl TempRepo {
+        /// create and `git init` a repository named after `label`
+        pub fn new(label: &str) -> Self {
+            static COUNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+            let n = COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            let dir = std::env::temp_dir()
+                .join(format!("cconvention-{label}-{}-{n}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir); // left over from a killed run
+            std::fs::create_dir_all(&dir).unwrap();
+            let repo = TempRepo(dir);
+            repo.git(&["init", "--quiet"]);
+            repo
+        }
+        /// run `git` in the repository, panicking if it fails
+        pub fn git(&self, args: &[&str]) -> String {
+            git(args, Some(self.0.clone())).unwrap()
+        }
+        /// `git commit --quiet` with a throwaway identity, plus `args`
+        pub fn commit(&self, args: &[&str]) -> String {
+            let identity = ["-c", "user.name=A", "-c", "user.email=a@example.com"];
+            let commit = ["commit", "--quiet"];
+            self.git(&[&identity[..], &commit, args].concat())
+        }
+        /// write `contents` to `path` relative to the repository, creating parent directories
+        pub fn write(&self, path: &str, contents: &str) -> PathBuf {
+            let path = self.0.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, contents).unwrap();
+            path
+        }
+    }
+
+    impl std::ops::Deref for TempRepo {
+        type Target = Path;
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempRepo {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+}
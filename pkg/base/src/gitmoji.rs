@@ -0,0 +1,216 @@
+// © Steven Kalt
+// SPDX-License-Identifier: APACHE-2.0
+
+//! [gitmoji](https://gitmoji.dev): emoji prefixing a commit's description, written either as the
+//! emoji itself or as its `:shortcode:`.
+use std::collections::HashMap;
+
+/// `(shortcode, emoji, description)` for each gitmoji
+const TABLE: &[(&str, &str, &str)] = &[
+    (":art:", "🎨", "Improve structure / format of the code."),
+    (":zap:", "⚡️", "Improve performance."),
+    (":fire:", "🔥", "Remove code or files."),
+    (":bug:", "🐛", "Fix a bug."),
+    (":ambulance:", "🚑️", "Critical hotfix."),
+    (":sparkles:", "✨", "Introduce new features."),
+    (":memo:", "📝", "Add or update documentation."),
+    (":rocket:", "🚀", "Deploy stuff."),
+    (":lipstick:", "💄", "Add or update the UI and style files."),
+    (":tada:", "🎉", "Begin a project."),
+    (":white_check_mark:", "✅", "Add, update, or pass tests."),
+    (":lock:", "🔒️", "Fix security or privacy issues."),
+    (":closed_lock_with_key:", "🔐", "Add or update secrets."),
+    (":bookmark:", "🔖", "Release / Version tags."),
+    (":rotating_light:", "🚨", "Fix compiler / linter warnings."),
+    (":construction:", "🚧", "Work in progress."),
+    (":green_heart:", "💚", "Fix CI Build."),
+    (":arrow_down:", "⬇️", "Downgrade dependencies."),
+    (":arrow_up:", "⬆️", "Upgrade dependencies."),
+    (":pushpin:", "📌", "Pin dependencies to specific versions."),
+    (
+        ":construction_worker:",
+        "👷",
+        "Add or update CI build system.",
+    ),
+    (
+        ":chart_with_upwards_trend:",
+        "📈",
+        "Add or update analytics or track code.",
+    ),
+    (":recycle:", "♻️", "Refactor code."),
+    (":heavy_plus_sign:", "➕", "Add a dependency."),
+    (":heavy_minus_sign:", "➖", "Remove a dependency."),
+    (":wrench:", "🔧", "Add or update configuration files."),
+    (":hammer:", "🔨", "Add or update development scripts."),
+    (
+        ":globe_with_meridians:",
+        "🌐",
+        "Internationalization and localization.",
+    ),
+    (":pencil2:", "✏️", "Fix typos."),
+    (":poop:", "💩", "Write bad code that needs to be improved."),
+    (":rewind:", "⏪️", "Revert changes."),
+    (":twisted_rightwards_arrows:", "🔀", "Merge branches."),
+    (
+        ":package:",
+        "📦️",
+        "Add or update compiled files or packages.",
+    ),
+    (":alien:", "👽️", "Update code due to external API changes."),
+    (
+        ":truck:",
+        "🚚",
+        "Move or rename resources (e.g.: files, paths, routes).",
+    ),
+    (":page_facing_up:", "📄", "Add or update license."),
+    (":boom:", "💥", "Introduce breaking changes."),
+    (":bento:", "🍱", "Add or update assets."),
+    (":wheelchair:", "♿️", "Improve accessibility."),
+    (":bulb:", "💡", "Add or update comments in source code."),
+    (":beers:", "🍻", "Write code drunkenly."),
+    (":speech_balloon:", "💬", "Add or update text and literals."),
+    (":card_file_box:", "🗃️", "Perform database related changes."),
+    (":loud_sound:", "🔊", "Add or update logs."),
+    (":mute:", "🔇", "Remove logs."),
+    (
+        ":busts_in_silhouette:",
+        "👥",
+        "Add or update contributor(s).",
+    ),
+    (
+        ":children_crossing:",
+        "🚸",
+        "Improve user experience / usability.",
+    ),
+    (
+        ":building_construction:",
+        "🏗️",
+        "Make architectural changes.",
+    ),
+    (":iphone:", "📱", "Work on responsive design."),
+    (":clown_face:", "🤡", "Mock things."),
+    (":egg:", "🥚", "Add or update an easter egg."),
+    (":see_no_evil:", "🙈", "Add or update a .gitignore file."),
+    (":camera_flash:", "📸", "Add or update snapshots."),
+    (":alembic:", "⚗️", "Perform experiments."),
+    (":mag:", "🔍️", "Improve SEO."),
+    (":label:", "🏷️", "Add or update types."),
+    (":seedling:", "🌱", "Add or update seed files."),
+    (
+        ":triangular_flag_on_post:",
+        "🚩",
+        "Add, update, or remove feature flags.",
+    ),
+    (":goal_net:", "🥅", "Catch errors."),
+    (":dizzy:", "💫", "Add or update animations and transitions."),
+    (
+        ":wastebasket:",
+        "🗑️",
+        "Deprecate code that needs to be cleaned up.",
+    ),
+    (
+        ":passport_control:",
+        "🛂",
+        "Work on code related to authorization, roles and permissions.",
+    ),
+    (
+        ":adhesive_bandage:",
+        "🩹",
+        "Simple fix for a non-critical issue.",
+    ),
+    (":monocle_face:", "🧐", "Data exploration/inspection."),
+    (":coffin:", "⚰️", "Remove dead code."),
+    (":test_tube:", "🧪", "Add a failing test."),
+    (":necktie:", "👔", "Add or update business logic."),
+    (":stethoscope:", "🩺", "Add or update healthcheck."),
+    (":bricks:", "🧱", "Infrastructure related changes."),
+    (":technologist:", "🧑‍💻", "Improve developer experience."),
+    (
+        ":money_with_wings:",
+        "💸",
+        "Add sponsorships or money related infrastructure.",
+    ),
+    (
+        ":thread:",
+        "🧵",
+        "Add or update code related to multithreading or concurrency.",
+    ),
+    (
+        ":safety_vest:",
+        "🦺",
+        "Add or update code related to validation.",
+    ),
+];
+
+/// emoji presentation selector, which some tools append and others drop
+const VARIATION_SELECTOR: char = '\u{fe0f}';
+
+lazy_static! {
+    /// shortcode => (emoji, description)
+    pub static ref GITMOJI: HashMap<&'static str, (&'static str, &'static str)> = TABLE
+        .iter()
+        .map(|(code, emoji, description)| (*code, (*emoji, *description)))
+        .collect();
+    /// emoji, without any variation selector => shortcode
+    static ref SHORTCODES: HashMap<String, &'static str> = TABLE
+        .iter()
+        .map(|(code, emoji, _)| (emoji.replace(VARIATION_SELECTOR, ""), *code))
+        .collect();
+}
+
+/// the shortcode of the gitmoji `text` starts with, and the byte length of the gitmoji in `text`
+pub fn leading(text: &str) -> Option<(&'static str, usize)> {
+    if let Some(rest) = text.strip_prefix(':') {
+        let end = rest.find(':')? + 2;
+        return GITMOJI
+            .get_key_value(&text[..end])
+            .map(|(code, _)| (*code, end));
+    }
+    // prefer the longest match, e.g. a ZWJ sequence over its first emoji
+    let mut stripped = String::new();
+    let mut found = None;
+    for (i, c) in text.char_indices() {
+        if c == VARIATION_SELECTOR {
+            if let Some((code, _)) = found {
+                found = Some((code, i + c.len_utf8()));
+            }
+            continue;
+        }
+        stripped.push(c);
+        if let Some(code) = SHORTCODES.get(&stripped) {
+            found = Some((*code, i + c.len_utf8()));
+        }
+        if stripped.chars().count() >= 4 {
+            break; // longer than any gitmoji
+        }
+    }
+    found
+}
+
+/// completions for each gitmoji's shortcode
+pub fn as_completion() -> Vec<lsp_types::CompletionItem> {
+    TABLE
+        .iter()
+        .map(|(code, emoji, description)| {
+            let mut item = lsp_types::CompletionItem::new_simple(
+                code.to_string(),
+                format!("{emoji} {description}"),
+            );
+            item.kind = Some(lsp_types::CompletionItemKind::ENUM_MEMBER);
+            item
+        })
+        .collect()
+}
+
+#[test]
+fn test_leading() {
+    assert_eq!(leading(":sparkles: add a thing"), Some((":sparkles:", 10)));
+    assert_eq!(leading("✨ add a thing"), Some((":sparkles:", "✨".len())));
+    // with and without the variation selector
+    assert_eq!(leading("⚡️ faster"), Some((":zap:", "⚡️".len())));
+    assert_eq!(leading("⚡ faster"), Some((":zap:", "⚡".len())));
+    assert_eq!(leading("🧑‍💻 dx"), Some((":technologist:", "🧑‍💻".len())));
+    assert_eq!(leading(":not_a_gitmoji: x"), None);
+    assert_eq!(leading("add a thing"), None);
+    assert_eq!(leading(""), None);
+}
@@ -1,6 +1,25 @@
 // © Steven Kalt
 // SPDX-License-Identifier: APACHE-2.0
 
+//! A language server and linter for conventional commit messages.
+//!
+//! Lints report [`lsp_types::Diagnostic`]s; use [`document::linting::codes::code_of`] to get a
+//! typed [`LintCode`](document::linting::codes::LintCode) back from one:
+//!
+//! ```
+//! use base::document::{
+//!     linting::{codes::{code_of, LintCode}, default},
+//!     GitCommitDocument,
+//! };
+//! let doc = GitCommitDocument::new().with_text("feat: add a\nwrapped subject".into());
+//! for diagnostic in default::check_subject_wrapped(&doc, default::SUBJECT_WRAPPED) {
+//!     assert_eq!(code_of(&diagnostic), Some(LintCode::SubjectWrapped));
+//! }
+//! ```
+//!
+//! Codes from configs or plugins are represented as [`LintCode::Other`](document::linting::codes::LintCode::Other);
+//! see [`document::linting::codes::all_lint_codes`] for the built-in ones.
+
 #[macro_use]
 extern crate lazy_static;
 
@@ -34,6 +53,7 @@ pub mod cli;
 pub mod config;
 pub mod document;
 pub mod git;
+pub mod gitmoji;
 pub mod server;
 pub mod syntax_token_scopes; // for convenience
 extern crate serde_json;
@@ -1,10 +1,10 @@
 // © Steven Kalt
 // SPDX-License-Identifier: APACHE-2.0
 use crate::{
-    config::{self, ConfigStore},
-    document::GitCommitDocument,
+    config::{self, ClientSettings, ConfigStore, Features},
+    document::{linting::utils, GitCommitDocument},
     git::to_path,
-    syntax_token_scopes,
+    gitmoji, syntax_token_scopes,
 };
 use anyhow::anyhow;
 use core::panic;
@@ -15,8 +15,12 @@ use lsp_types::{
 };
 use lsp_types::{notification::Notification as NotificationTrait, InitializeParams};
 use lsp_types::{DidChangeTextDocumentParams, ServerCapabilities};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+
+/// a custom notification summarizing each published lint, e.g. for a status bar.
+/// Only sent to clients that set `"cconvention/status": true` in their experimental capabilities.
+pub const STATUS_NOTIFICATION: &str = "cconvention/status";
 lazy_static! {
     pub static ref CAPABILITIES: lsp_types::ServerCapabilities = {
         lsp_types::ServerCapabilities {
@@ -35,10 +39,11 @@ lazy_static! {
                 },
             )),
             hover_provider: Some(lsp_types::HoverProviderCapability::Simple(true)),
-            // TODO: provide selection range
-            // selection_range_provider: Some(lsp_types::SelectionRangeProviderCapability::Simple(true)),
+            selection_range_provider: Some(lsp_types::SelectionRangeProviderCapability::Simple(
+                true,
+            )),
             completion_provider: Some(lsp_types::CompletionOptions {
-                resolve_provider: None,
+                resolve_provider: Some(true), // to preview the formatted subject
                 trigger_characters: None,
                 all_commit_characters: None,
                 work_done_progress_options: lsp_types::WorkDoneProgressOptions {
@@ -46,25 +51,23 @@ lazy_static! {
                 },
                 completion_item: None,
             }),
-            // TODO: provide code actions?
-            // code_action_provider: Some(lsp_types::CodeActionProviderCapability::Options(
-            //     lsp_types::CodeActionOptions {
-            //         code_action_kinds: Some(vec![
-            //             CodeActionKind::EMPTY,
-            //             CodeActionKind::QUICKFIX,
-            //             CodeActionKind::REFACTOR,
-            //             CodeActionKind::SOURCE_FIX_ALL,
-            //         ]),
-            //         work_done_progress_options: lsp_types::WorkDoneProgressOptions {
-            //             work_done_progress: None,
-            //         },
-            //         resolve_provider: None,
-            //     },
-            // )),
+            // offer lints' fixes as quick fixes, and all of them at once
+            code_action_provider: Some(lsp_types::CodeActionProviderCapability::Options(
+                lsp_types::CodeActionOptions {
+                    code_action_kinds: Some(vec![
+                        lsp_types::CodeActionKind::QUICKFIX,
+                        lsp_types::CodeActionKind::SOURCE_FIX_ALL,
+                    ]),
+                    work_done_progress_options: lsp_types::WorkDoneProgressOptions {
+                        work_done_progress: None,
+                    },
+                    resolve_provider: None,
+                },
+            )),
             // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#textDocument_formatting
             document_formatting_provider: Some(lsp_types::OneOf::Left(true)),
             // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#textDocument_rangeFormatting
-            document_range_formatting_provider: None,
+            document_range_formatting_provider: Some(lsp_types::OneOf::Left(true)),
             // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#textDocument_onTypeFormatting
             document_on_type_formatting_provider: Some(lsp_types::DocumentOnTypeFormattingOptions {
                 first_trigger_character: "(".to_string(),
@@ -77,10 +80,26 @@ lazy_static! {
                     work_done_progress: None,
                 },
             }),
-            folding_range_provider: None, // TODO: actually do this though
-            // TODO: jump from type/scope -> definition in config
+            // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#textDocument_rename
+            rename_provider: Some(lsp_types::OneOf::Right(lsp_types::RenameOptions {
+                prepare_provider: Some(true),
+                work_done_progress_options: lsp_types::WorkDoneProgressOptions {
+                    work_done_progress: None,
+                },
+            })),
+            document_highlight_provider: Some(lsp_types::OneOf::Left(true)),
+            // show the SemVer release a commit calls for above its subject
+            code_lens_provider: Some(lsp_types::CodeLensOptions {
+                resolve_provider: Some(false),
+            }),
+            folding_range_provider: Some(lsp_types::FoldingRangeProviderCapability::Simple(true)),
+            // count down the characters left on the subject line
+            inlay_hint_provider: Some(lsp_types::OneOf::Left(true)),
+            // an outline of the subject's parts, the body, and the trailers
+            document_symbol_provider: Some(lsp_types::OneOf::Left(true)),
+            // jumping from a type/scope to its declaration needs a config file, so servers whose
+            // configs implement `Config::type_definition` advertise `definition_provider` themselves
             // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#textDocument_definition
-            // definition_provider: None,
             declaration_provider: None, // maybe later, for jumping to configuration
             execute_command_provider: None, // maybe later for executing code blocks
             workspace: None,
@@ -96,21 +115,111 @@ lazy_static! {
                                 .iter()
                                 .map(|tag| lsp_types::SemanticTokenType::new(tag))
                                 .collect(),
-                            token_modifiers: vec![
-                            // lsp_types::SemanticTokenModifier
-                            ],
+                            token_modifiers: syntax_token_scopes::SYNTAX_TOKEN_MODIFIERS.clone(),
                         },
                         range: None, // TODO: injection ranges?
-                        full: Some(lsp_types::SemanticTokensFullOptions::Bool(true)),
+                        full: Some(lsp_types::SemanticTokensFullOptions::Delta {
+                            delta: Some(true),
+                        }),
                     },
                 ),
             ),
-            // useless implementation commented :/
-            // selection_range_provider: Some(lsp_types::SelectionRangeProviderCapability::Simple(true)),
+            experimental: Some(serde_json::json!({ STATUS_NOTIFICATION: true })),
             ..Default::default()
         }
     };
 }
+/// `capabilities` without the providers of disabled `features`
+pub fn advertised_capabilities(
+    capabilities: &ServerCapabilities,
+    features: &Features,
+) -> ServerCapabilities {
+    let mut capabilities = capabilities.clone();
+    if !features.completion {
+        capabilities.completion_provider = None;
+    }
+    if !features.hover {
+        capabilities.hover_provider = None;
+    }
+    if !features.semantic_tokens {
+        capabilities.semantic_tokens_provider = None;
+    }
+    if !features.formatting {
+        capabilities.document_formatting_provider = None;
+        capabilities.document_range_formatting_provider = None;
+        capabilities.document_on_type_formatting_provider = None;
+    }
+    if !features.document_links {
+        capabilities.document_link_provider = None;
+    }
+    capabilities
+}
+
+/// completions inserted at `position` for whatever the subject's `rest` lacks: a `!` marking a
+/// breaking change, or the colon ending the prefix
+fn separator_completions(
+    position: &lsp_types::Position,
+    rest: &str,
+) -> Vec<lsp_types::CompletionItem> {
+    let mut items = vec![];
+    let mut push = |label: &str, new_text: &str, detail: &str, docs: &str| {
+        items.push(lsp_types::CompletionItem {
+            label: label.to_owned(),
+            kind: Some(lsp_types::CompletionItemKind::OPERATOR),
+            detail: Some(detail.to_owned()),
+            documentation: Some(lsp_types::Documentation::MarkupContent(
+                lsp_types::MarkupContent {
+                    kind: lsp_types::MarkupKind::Markdown,
+                    value: docs.to_owned(),
+                },
+            )),
+            text_edit: Some(lsp_types::CompletionTextEdit::Edit(lsp_types::TextEdit {
+                range: lsp_types::Range {
+                    start: *position,
+                    end: *position,
+                },
+                new_text: new_text.to_owned(),
+            })),
+            ..Default::default()
+        })
+    };
+    if !rest.contains('!') {
+        push(
+            "!",
+            "!",
+            "Breaking change",
+            "Marks the commit as introducing a breaking change, which calls for a major release, \
+            e.g. `feat(api)!: drop v1 routes`.",
+        );
+    }
+    if !rest.contains(':') {
+        push(
+            ":",
+            ": ",
+            "End the prefix",
+            "Separates the type and optional scope from the description, e.g. `fix(ui): align buttons`.",
+        );
+    }
+    items
+}
+
+/// whether `features` allow handling requests for `method`
+fn is_enabled(features: &Features, method: &str) -> bool {
+    use lsp_types::request::*;
+    match method {
+        Completion::METHOD | ResolveCompletionItem::METHOD => features.completion,
+        HoverRequest::METHOD => features.hover,
+        SemanticTokensFullRequest::METHOD | SemanticTokensFullDeltaRequest::METHOD => {
+            features.semantic_tokens
+        }
+        Formatting::METHOD | RangeFormatting::METHOD | OnTypeFormatting::METHOD => {
+            features.formatting
+        }
+        DocumentLinkRequest::METHOD | DocumentLinkResolve::METHOD => features.document_links,
+        _ => true,
+    }
+}
+
 /// a Server instance owns a `lsp_server::Connection` instance and a mutable
 /// syntax tree, representing an actively edited .git/GIT_COMMIT_EDITMSG file.
 pub struct Server<Cfg: ConfigStore> {
@@ -118,6 +227,16 @@ pub struct Server<Cfg: ConfigStore> {
     commits: HashMap<lsp_types::Url, GitCommitDocument>,
     connection: lsp_server::Connection,
     client_capabilities: lsp_types::ClientCapabilities,
+    /// config files with published `config::ConfigFileError` diagnostics
+    config_errors: HashSet<Url>,
+    /// the features advertised to the client
+    features: Features,
+    /// the last semantic tokens sent for each document, with their `result_id`
+    semantic_tokens: HashMap<Url, (u32, Vec<lsp_types::SemanticToken>)>,
+    /// the id of the next request sent to the client
+    next_request_id: i32,
+    /// the id of the `workspace/configuration` request awaiting the client's settings, if any
+    pending_configuration: Option<RequestId>,
 }
 
 pub enum ServerLoopAction {
@@ -168,8 +287,18 @@ impl<Cfg: ConfigStore> Server<Cfg> {
         let (id, init_params) = self.connection.initialize_start()?;
         let _init_params: InitializeParams = serde_json::from_value(init_params)?;
         self.client_capabilities = _init_params.capabilities;
+        #[allow(deprecated)] // clients still send rootUri
+        let worktree_root = _init_params
+            .root_uri
+            .as_ref()
+            .and_then(|uri| to_path(uri).ok())
+            .and_then(|path| crate::git::get_worktree_root(&path).ok());
+        // config errors get reported once a document is opened
+        if let Ok(cfg) = self.config.get(worktree_root) {
+            self.features = self.features.intersect(cfg.features());
+        }
         let response = InitializeResult {
-            capabilities: cap.clone(),
+            capabilities: advertised_capabilities(cap, &self.features),
             server_info: Some(ServerInfo {
                 name: "cconvention".to_owned(),
                 // https://doc.rust-lang.org/cargo/reference/environment-variables.html#environment-variables-cargo-sets-for-crates
@@ -178,21 +307,75 @@ impl<Cfg: ConfigStore> Server<Cfg> {
         };
         self.connection
             .initialize_finish(id, serde_json::json!(response))?;
+        if let Err(err) = GitCommitDocument::try_new() {
+            self.publish_error(err.into()); // rather than crashing once a document is opened
+        }
+        self.request_configuration();
         Ok(self)
     }
 
-    /// create a fresh server with a stdio-based connection.
-    pub fn from_stdio(config: Cfg) -> Self {
-        let (conn, _io) = lsp_server::Connection::stdio();
+    /// ask the client for its `cconvention` settings, if it can answer. The reply is applied in
+    /// `handle_configuration_response`.
+    fn request_configuration(&mut self) {
+        let supported = self
+            .client_capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.configuration)
+            .unwrap_or(false);
+        if !supported {
+            return;
+        }
+        self.next_request_id += 1;
+        let id = RequestId::from(self.next_request_id);
+        let params = lsp_types::ConfigurationParams {
+            items: vec![lsp_types::ConfigurationItem {
+                scope_uri: None,
+                section: Some("cconvention".to_owned()),
+            }],
+        };
+        self.connection
+            .sender
+            .send(Message::Request(Request {
+                id: id.clone(),
+                method: <lsp_types::request::WorkspaceConfiguration as lsp_types::request::Request>::METHOD
+                    .to_owned(),
+                params: serde_json::to_value(params).unwrap(),
+            }))
+            .unwrap();
+        self.pending_configuration = Some(id);
+    }
+
+    /// create a fresh server communicating over the given connection,
+    /// e.g. an in-memory `lsp_server::Connection::memory()` for tests.
+    pub fn from_connection(config: Cfg, connection: lsp_server::Connection) -> Self {
         Server {
             config,
             commits: HashMap::with_capacity(1), // expect that most of the time there will be exactly 1 document
-            connection: conn,
+            connection,
             client_capabilities: Default::default(),
+            config_errors: HashSet::new(),
+            features: Features::default(),
+            semantic_tokens: HashMap::with_capacity(1),
+            next_request_id: 0,
+            pending_configuration: None,
         }
     }
-    pub fn from_tcp(_config: Cfg, _port: u16) -> Self {
-        todo!("tcp connections not yet implemented")
+    /// disable features regardless of the configuration, e.g. from CLI flags
+    pub fn with_features(mut self, features: Features) -> Self {
+        self.features = features;
+        self
+    }
+    /// create a fresh server with a stdio-based connection.
+    pub fn from_stdio(config: Cfg) -> Self {
+        let (conn, _io) = lsp_server::Connection::stdio();
+        Self::from_connection(config, conn)
+    }
+    /// create a fresh server listening on `port` on localhost, waiting for a client to connect.
+    pub fn from_tcp(config: Cfg, port: u16) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        log_info!("listening on 127.0.0.1:{port}");
+        let (conn, _io) = lsp_server::Connection::listen(("127.0.0.1", port))?;
+        Ok(Self::from_connection(config, conn))
     }
     pub fn serve(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
         log_info!("starting server loop");
@@ -227,7 +410,12 @@ impl<Cfg: ConfigStore> Server<Cfg> {
                 self.respond(response);
                 Ok(ServerLoopAction::Continue)
             }
-            Message::Response(_resp) => Ok(ServerLoopAction::Continue),
+            Message::Response(response) => {
+                if let Err(err) = self.handle_response(response) {
+                    self.publish_error(err);
+                }
+                Ok(ServerLoopAction::Continue)
+            }
             Message::Notification(notification) => self.handle_notification(notification),
         }
     }
@@ -265,23 +453,78 @@ impl<Cfg: ConfigStore> Server<Cfg> {
 
         Ok(ServerLoopAction::Continue)
     }
-    fn publish_diagnostics(&self, uri: Url, diagnostics: Vec<lsp_types::Diagnostic>) {
+    /// `version` lets the client discard diagnostics for stale versions of the document
+    fn publish_diagnostics(
+        &self,
+        uri: Url,
+        diagnostics: Vec<lsp_types::Diagnostic>,
+        version: Option<i32>,
+    ) {
         span!(tracing::Level::INFO, "publish_diagnostics");
         let params = lsp_types::PublishDiagnosticsParams {
             uri,
             diagnostics,
-            version: None,
+            version,
         };
         self.connection
             .sender
             .send(Message::Notification(Notification {
                 method: <lsp_types::notification::PublishDiagnostics as lsp_types::notification::Notification>::METHOD.to_owned(),
-                params: serde_json::to_value(params).unwrap(),
+                params: serde_json::to_value(&params).unwrap(),
+            }))
+            .unwrap();
+        if self.commits.contains_key(&params.uri) {
+            self.publish_status(&params.uri, &params.diagnostics);
+        }
+    }
+    /// summarize a document's diagnostics for clients that opted into `STATUS_NOTIFICATION`
+    fn publish_status(&self, uri: &Url, diagnostics: &[lsp_types::Diagnostic]) {
+        let opted_in = self
+            .client_capabilities
+            .experimental
+            .as_ref()
+            .and_then(|experimental| experimental.get(STATUS_NOTIFICATION))
+            .and_then(|enabled| enabled.as_bool())
+            .unwrap_or(false);
+        if !opted_in {
+            return;
+        }
+        let count = |severity| {
+            diagnostics
+                .iter()
+                .filter(|d| d.severity == Some(severity))
+                .count()
+        };
+        self.connection
+            .sender
+            .send(Message::Notification(Notification {
+                method: STATUS_NOTIFICATION.to_owned(),
+                params: serde_json::json!({
+                    "uri": uri,
+                    "errors": count(lsp_types::DiagnosticSeverity::ERROR),
+                    "warnings": count(lsp_types::DiagnosticSeverity::WARNING),
+                    "hints": count(lsp_types::DiagnosticSeverity::HINT),
+                }),
             }))
             .unwrap();
     }
-    /// Send an error-message notification to the client.
-    fn publish_error(&self, err: Box<dyn std::error::Error + Send + Sync>) {
+    /// Send an error-message notification to the client, or a diagnostic if the error is located
+    /// within a config file.
+    fn publish_error(&mut self, err: Box<dyn std::error::Error + Send + Sync>) {
+        if let Some(err) = err.downcast_ref::<config::ConfigFileError>() {
+            if let Ok(uri) = Url::from_file_path(&err.path) {
+                let diagnostic = lsp_types::Diagnostic {
+                    range: err.range,
+                    severity: Some(lsp_types::DiagnosticSeverity::ERROR),
+                    source: Some(crate::document::linting::default::ID.to_string()),
+                    message: err.message.clone(),
+                    ..Default::default()
+                };
+                self.publish_diagnostics(uri.clone(), vec![diagnostic], None);
+                self.config_errors.insert(uri);
+                return;
+            }
+        }
         // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#window_showMessageRequest
         self.connection
             .sender
@@ -296,6 +539,13 @@ impl<Cfg: ConfigStore> Server<Cfg> {
             .unwrap()
     }
 
+    /// publish the problems `cfg` worked around while loading, e.g. a malformed config file
+    fn publish_load_errors(&mut self, cfg: &dyn config::Config) {
+        for err in cfg.load_errors() {
+            self.publish_error(Box::new(err));
+        }
+    }
+
     // TODO: remove; it's unused
     pub fn watch_files(&self, files: Vec<GlobPattern>) {
         // see https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#didChangeWatchedFilesRegistrationOptions
@@ -324,13 +574,18 @@ impl<Cfg: ConfigStore> Server<Cfg> {
         params: DidOpenTextDocumentParams,
     ) -> Result<ServerLoopAction, Box<dyn Error + Send + Sync>> {
         let uri = params.text_document.uri;
-        let doc = GitCommitDocument::new()
-            .with_text(params.text_document.text)
-            .with_url(&uri);
+        let mut doc = GitCommitDocument::try_new()?.with_url(&uri);
+        doc.version = Some(params.text_document.version);
+        let cfg = self.config.get(doc.worktree_root.clone())?;
+        self.publish_load_errors(cfg.as_ref());
+        let doc = doc
+            .with_parse_timeout(cfg.parse_timeout_micros())
+            .with_comments_are_content(cfg.comments_are_content())
+            .with_text(params.text_document.text);
         self.commits.insert(uri.clone(), doc);
         let commit = self.commits.get(&uri).unwrap();
-        let cfg = self.config.get(commit.worktree_root.clone())?;
-        self.publish_diagnostics(uri, cfg.lint(commit));
+        let diagnostics = self.config.get_for(commit)?.lint(commit);
+        self.publish_diagnostics(uri, diagnostics, commit.version);
         Ok(ServerLoopAction::Continue)
     }
     fn handle_close(
@@ -340,7 +595,8 @@ impl<Cfg: ConfigStore> Server<Cfg> {
         // clear the diagnostics for the document
         let uri = params.text_document.uri;
         self.commits.remove(&uri);
-        self.publish_diagnostics(uri, vec![]);
+        self.semantic_tokens.remove(&uri);
+        self.publish_diagnostics(uri, vec![], None);
         // TODO: shut down the server if 0 documents are open. Unfortunately,
         // the client has to tell the server to exit.
         if self.commits.is_empty() {
@@ -354,15 +610,17 @@ impl<Cfg: ConfigStore> Server<Cfg> {
         params: DidChangeTextDocumentParams,
     ) -> Result<ServerLoopAction, Box<dyn Error + Send + Sync>> {
         let uri = params.text_document.uri;
-        let diagnostics = {
+        let (diagnostics, version) = {
             let commit = self
                 .commits
                 .get_mut(&uri)
                 .ok_or(format!("No document {uri}"))?;
+            commit.version = Some(params.text_document.version);
             commit.edit(&params.content_changes);
-            self.config.get(commit.worktree_root.clone())?.lint(commit)
+            let cfg = self.config.get_for(commit)?;
+            (cfg.lint(commit), commit.version)
         };
-        self.publish_diagnostics(uri, diagnostics);
+        self.publish_diagnostics(uri, diagnostics, version);
         Ok(ServerLoopAction::Continue)
     }
     fn handle_save(
@@ -375,19 +633,59 @@ impl<Cfg: ConfigStore> Server<Cfg> {
             let commit = self.commits.get_mut(&uri).unwrap();
             log_debug!("refreshing syntax tree");
             commit.set_text(text);
-            let diagnostics = self.config.get(commit.worktree_root.clone())?.lint(commit);
-            self.publish_diagnostics(uri.clone(), diagnostics);
+            let diagnostics = self.config.get_for(commit)?.lint(commit);
+            let version = commit.version;
+            self.publish_diagnostics(uri.clone(), diagnostics, version);
         }
         Ok(ServerLoopAction::Continue)
     }
-    #[allow(unused_variables)]
     fn handle_config_change(
         &mut self,
         params: lsp_types::DidChangeConfigurationParams,
     ) -> Result<ServerLoopAction, Box<dyn Error + Send + Sync>> {
         log_debug!("{:?}", params);
+        // other extensions' settings may be pushed too; only ours can change the lints
+        match params.settings.get("cconvention") {
+            Some(section) => self.apply_settings(section.clone())?,
+            // clients that expect settings to be pulled send an empty notification
+            None => self.request_configuration(),
+        }
         Ok(ServerLoopAction::Continue)
     }
+    /// apply the client's `cconvention` settings, re-linting every open document
+    fn apply_settings(
+        &mut self,
+        section: serde_json::Value,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if section.is_null() {
+            return Ok(()); // the client has no settings for us
+        }
+        let settings: ClientSettings = serde_json::from_value(section)?;
+        self.config.set_settings(settings);
+        for (url, commit) in self.commits.iter() {
+            let diagnostics = self.config.get_for(commit)?.lint(commit);
+            self.publish_diagnostics(url.clone(), diagnostics, commit.version);
+        }
+        Ok(())
+    }
+    /// handle the client's replies to the server's requests
+    fn handle_response(&mut self, response: Response) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.pending_configuration.as_ref() != Some(&response.id) {
+            return Ok(());
+        }
+        self.pending_configuration = None;
+        if let Some(_err) = response.error {
+            log_debug!("workspace/configuration failed: {:?}", _err);
+            return Ok(());
+        }
+        // one value per requested section
+        let sections: Vec<serde_json::Value> =
+            serde_json::from_value(response.result.unwrap_or_default())?;
+        if let Some(section) = sections.into_iter().next() {
+            self.apply_settings(section)?;
+        }
+        Ok(())
+    }
     fn handle_exit(&mut self, _: ()) -> Result<ServerLoopAction, Box<dyn Error + Send + Sync>> {
         Ok(ServerLoopAction::Break)
     }
@@ -401,13 +699,27 @@ impl<Cfg: ConfigStore> Server<Cfg> {
             paths.push(to_path(&change.uri)?);
         }
         for path in self.config.set_dirty(paths) {
+            // clear stale config errors; any that persist get re-published on the next lookup
+            let stale: Vec<Url> = self
+                .config_errors
+                .iter()
+                .filter(|uri| to_path(uri).map(|p| p.starts_with(&path)).unwrap_or(false))
+                .cloned()
+                .collect();
+            for uri in stale {
+                self.config_errors.remove(&uri);
+                self.publish_diagnostics(uri, vec![], None);
+            }
             // HACK: inefficient lookup of the commits associated with this config
             // in practice, I'd only ever expect one commit to be associated with a server,
             // so this shouldn't be a big deal.
+            if let Ok(cfg) = self.config.get(Some(path.clone())) {
+                self.publish_load_errors(cfg.as_ref());
+            }
             for (url, commit) in self.commits.iter() {
                 if commit.worktree_root == Some(path.clone()) {
-                    let diagnostics = self.config.get(commit.worktree_root.clone())?.lint(commit);
-                    self.publish_diagnostics(url.clone(), diagnostics);
+                    let diagnostics = self.config.get_for(commit)?.lint(commit);
+                    self.publish_diagnostics(url.clone(), diagnostics, commit.version);
                     break;
                 }
             }
@@ -424,6 +736,13 @@ impl<Cfg: ConfigStore> Server<Cfg> {
     ) -> Result<Response, Box<dyn Error + Send + Sync>> {
         span!(tracing::Level::INFO, "handle_request");
         use lsp_types::request::*;
+        if !is_enabled(&self.features, &request.method) {
+            return Ok(Response {
+                id: request.id,
+                result: Some(serde_json::Value::Null),
+                error: None,
+            });
+        }
 
         macro_rules! handle {
             ($method:ty => $handler:ident) => {
@@ -444,24 +763,33 @@ impl<Cfg: ConfigStore> Server<Cfg> {
             };
         }
         handle!(SemanticTokensFullRequest => handle_token_full);
-        // handle!(SemanticTokensFullDeltaRequest => handle_token_full_delta);
+        handle!(SemanticTokensFullDeltaRequest => handle_token_full_delta);
         // handle!(SemanticTokensRangeRequest => handle_token_range);
         // handle!(SemanticTokensRefresh => handle_token_refresh);
 
         handle!(Completion => handle_completion);
         handle!(Formatting => handle_formatting);
         handle!(DocumentLinkRequest => handle_doc_link_request);
+        handle!(DocumentLinkResolve => handle_doc_link_resolve);
         // sent from the client to the server to compute commands for a given text document and range.
         // The request is triggered when the user moves the cursor into a problem marker
         // TODO: figure out how to resolve commit, issue/PR, and mention links
         // on GitHub, BitBucket, GitLab, etc.
-        // handle!(CodeActionRequest => handle_code_action);
+        handle!(CodeActionRequest => handle_code_action);
         // sent from the client to the server to compute completion items at a given cursor position
         handle!(HoverRequest => handle_hover);
-        // handle!(RangeFormatting => handle_range_formatting);
-        // handle!(ResolveCompletionItem => handle_resolving_completion_item);
-        // handle!(SelectionRangeRequest => handle_selection_range_request);
+        handle!(RangeFormatting => handle_range_formatting);
+        handle!(ResolveCompletionItem => handle_resolving_completion_item);
+        handle!(SelectionRangeRequest => handle_selection_range_request);
         handle!(OnTypeFormatting => handle_on_type_formatting);
+        handle!(PrepareRenameRequest => handle_prepare_rename);
+        handle!(Rename => handle_rename);
+        handle!(DocumentHighlightRequest => handle_document_highlight);
+        handle!(FoldingRangeRequest => handle_folding_range);
+        handle!(DocumentSymbolRequest => handle_document_symbol);
+        handle!(CodeLensRequest => handle_code_lens);
+        handle!(InlayHintRequest => handle_inlay_hint);
+        handle!(GotoDefinition => handle_definition);
 
         let response = Response {
             id: request.id,
@@ -475,16 +803,17 @@ impl<Cfg: ConfigStore> Server<Cfg> {
         Ok(response)
     }
     fn handle_formatting(
-        &self,
+        &mut self,
         id: &RequestId,
         params: lsp_types::DocumentFormattingParams,
     ) -> Result<Response, Box<dyn Error + Send + Sync>> {
         span!(tracing::Level::INFO, "handle_formatting");
         let uri = params.text_document.uri;
         if let Some(commit) = self.commits.get(&uri) {
+            let options = self.config.get_for(commit)?.format_options();
             let response = Response {
                 id: id.clone(),
-                result: Some(serde_json::to_value(commit.format()).unwrap()),
+                result: Some(serde_json::to_value(commit.format(&options)).unwrap()),
                 error: None,
             };
             Ok(response)
@@ -500,7 +829,7 @@ impl<Cfg: ConfigStore> Server<Cfg> {
     ) -> Result<Response, Box<dyn Error + Send + Sync>> {
         span!(tracing::Level::INFO, "handle_completion");
         let uri = params.text_document_position.text_document.uri;
-        if self.commits.get(&uri).is_none() {
+        if !self.commits.contains_key(&uri) {
             panic!("no such document {uri}")
         }
         let commit = self.commits.get(&uri).unwrap();
@@ -516,40 +845,111 @@ impl<Cfg: ConfigStore> Server<Cfg> {
 
         let mut result = vec![];
         let character_index = position.character as usize;
-        if let Some(subject) = &commit.subject {
-            if position.line == subject.line_number as u32 {
-                // consider completions for the cc type, scope
-                log_debug!("\t{}", subject.debug_ranges());
-                // Using <= since the cursor should still trigger completions if it's at the end of a range
-                let type_len = subject.type_text().chars().count();
-                let scope_len = subject.scope_text().chars().count();
-                if character_index <= type_len {
-                    // handle type completions
-                    result.extend(config::as_completion(
-                        &self
-                            .config
-                            .get(commit.worktree_root.clone())?
-                            .type_suggestions(),
-                    ));
-                } else if character_index <= scope_len + type_len {
-                    result.extend(config::as_completion(
-                        &self
-                            .config
-                            .get(commit.worktree_root.clone())?
-                            .scope_suggestions(),
-                    ));
-                    if let Some(first) = result.first_mut() {
-                        first.preselect = Some(true);
-                    }
-                } else {
-                    // in the subject message; no completions
-                    // TODO: suggest either a bang or a colon if character_index <= rest_len + scope_len + type_len
+        // the subject line gets type and scope completions; the rest, trailer completions
+        let subject = commit
+            .subject
+            .as_ref()
+            .filter(|subject| position.line == subject.line_number as u32);
+        if let Some(subject) = subject {
+            // consider completions for the cc type, scope
+            log_debug!("\t{}", subject.debug_ranges());
+            // Using <= since the cursor should still trigger completions if it's at the end of a range
+            let type_len = subject.type_text().chars().count();
+            let scope_len = subject.scope_text().chars().count();
+            // lets `completionItem/resolve` find the subject to preview
+            let data = |part: &str| Some(serde_json::json!({ "uri": uri, "part": part }));
+            if character_index <= type_len {
+                // handle type completions
+                result.extend(config::as_completion(
+                    &self.config.get_for(commit)?.type_suggestions(),
+                ));
+                result.iter_mut().for_each(|item| item.data = data("type"));
+            } else if character_index == scope_len + type_len && subject.scope_is_closed() {
+                // past the scope, where the `!` or colon goes
+                result.extend(separator_completions(position, subject.rest_text()));
+            } else if character_index <= scope_len + type_len {
+                result.extend(config::as_completion(
+                    &self.config.get_for(commit)?.scope_suggestions(),
+                ));
+                result.iter_mut().for_each(|item| item.data = data("scope"));
+                if let Some(first) = result.first_mut() {
+                    first.preselect = Some(true);
+                }
+            } else if subject.rest_text().chars().count() + scope_len + type_len >= character_index
+                && !subject
+                    .rest_text()
+                    .chars()
+                    .take(character_index - scope_len - type_len)
+                    .any(|c| c == ':')
+            {
+                result.extend(separator_completions(position, subject.rest_text()));
+            } else {
+                // in the subject message: offer gitmoji while the first word is being typed
+                let prefix_len = subject.prefix_text().chars().count();
+                let message = subject.message_text();
+                let start = prefix_len + message.chars().take_while(|c| *c == ' ').count();
+                let mut rest = message.chars().skip(start - prefix_len);
+                let in_first_word = character_index >= start
+                    && !rest
+                        .by_ref()
+                        .take(character_index - start.min(character_index))
+                        .any(char::is_whitespace);
+                // separate the gitmoji from any text after the cursor
+                let separator = match rest.next() {
+                    Some(c) if c.is_whitespace() => "",
+                    _ => " ",
+                };
+                if in_first_word && self.config.get_for(commit)?.gitmoji() {
+                    let range = lsp_types::Range {
+                        start: lsp_types::Position {
+                            line: position.line,
+                            character: start as u32,
+                        },
+                        end: *position,
+                    };
+                    result.extend(gitmoji::as_completion().into_iter().map(|mut item| {
+                        item.text_edit =
+                            Some(lsp_types::CompletionTextEdit::Edit(lsp_types::TextEdit {
+                                range,
+                                new_text: format!("{}{separator}", item.label),
+                            }));
+                        item
+                    }));
                 }
             }
         } else {
-            let line = commit.code.line(position.line as usize).to_string(); // panics if line is out of bounds
-            if let Some(c) = line.chars().next() {
-                if c == '#' {
+            let line_slice = commit.code.line(position.line as usize); // panics if line is out of bounds
+            let line = line_slice.to_string();
+            // offer trailer keys while the start of the line could still be one
+            let typed: String = line.chars().take(character_index).collect();
+            if !typed.contains(':') && !commit.is_comment(&line_slice) {
+                for (key, detail) in self.config.get_for(commit)?.trailer_suggestions() {
+                    let matches = key
+                        .get(..typed.len())
+                        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&typed));
+                    if !matches {
+                        continue;
+                    }
+                    result.push(lsp_types::CompletionItem {
+                        label: format!("{key}:"),
+                        kind: Some(lsp_types::CompletionItemKind::KEYWORD),
+                        detail: Some(detail),
+                        text_edit: Some(lsp_types::CompletionTextEdit::Edit(lsp_types::TextEdit {
+                            range: lsp_types::Range {
+                                start: lsp_types::Position {
+                                    line: position.line,
+                                    character: 0,
+                                },
+                                end: *position,
+                            },
+                            new_text: format!("{key}: "),
+                        })),
+                        ..Default::default()
+                    });
+                }
+            }
+            if line.chars().next().is_some() {
+                if commit.is_comment(&line_slice) {
                     // this is a commented line
                     // no completions
                 } else {
@@ -557,7 +957,9 @@ impl<Cfg: ConfigStore> Server<Cfg> {
                     // completions for BREAKING CHANGE:
                     // See https://www.conventionalcommits.org/en/v1.0.0/#specification
                     if character_index >= 1 && character_index <= "BREAKING CHANGE: ".len() {
-                        let prefix = &line.as_str()[0..character_index];
+                        // `character_index` counts chars, so compare what's typed rather than
+                        // slicing `line` by it
+                        let prefix = typed.as_str();
                         let breaking_change_match =
                             if prefix == &"BREAKING-CHANGE: "[0..character_index] {
                                 Some("BREAKING-CHANGE: ")
@@ -588,18 +990,22 @@ impl<Cfg: ConfigStore> Server<Cfg> {
                                 ..Default::default()
                             });
                         }
-                        if character_index >= 1
-                            && character_index < "Signed-off-by".len()
-                            && line.as_str()[..character_index]
-                                == "Signed-off-by"[0..character_index]
+                        let change_id = "Change-Id: ";
+                        if self.config.get_for(commit)?.gerrit()
+                            && character_index >= 1
+                            && character_index < change_id.len()
+                            && typed.eq_ignore_ascii_case(&change_id[..character_index])
                         {
+                            let new_text = format!(
+                                "{change_id}{}",
+                                crate::document::linting::default::generate_change_id(
+                                    &commit.code.to_string()
+                                )
+                            );
                             result.push(lsp_types::CompletionItem {
-                                label: "Signed-off-by:".to_owned(),
+                                label: "Change-Id:".to_owned(),
                                 kind: Some(lsp_types::CompletionItemKind::KEYWORD),
-                                detail: Some(
-                                    "a sign-off (correlating with Signed-off-by in git trailers)"
-                                        .to_owned(),
-                                ),
+                                detail: Some("a fresh Gerrit Change-Id".to_owned()),
                                 text_edit: Some(lsp_types::CompletionTextEdit::Edit(
                                     lsp_types::TextEdit {
                                         range: lsp_types::Range {
@@ -607,19 +1013,52 @@ impl<Cfg: ConfigStore> Server<Cfg> {
                                                 line: position.line,
                                                 character: 0,
                                             },
+                                            end: *position,
+                                        },
+                                        new_text,
+                                    },
+                                )),
+                                ..Default::default()
+                            });
+                        }
+
+                        log_debug!("end of message completions?");
+                    }
+                    // complete a co-author's value from the recent commit authors
+                    let co_author = "Co-authored-by:";
+                    let value_start = line
+                        .get(..co_author.len())
+                        .filter(|key| key.eq_ignore_ascii_case(co_author))
+                        .map(|_| {
+                            let value = &line[co_author.len()..];
+                            co_author.len() + value.len() - value.trim_start().len()
+                        })
+                        .filter(|start| character_index >= *start);
+                    if let Some(start) = value_start {
+                        let end = line.trim_end().chars().count().max(start) as u32;
+                        for author in self.config.recent_authors(commit.worktree_root.clone()) {
+                            result.push(lsp_types::CompletionItem {
+                                label: author.clone(),
+                                kind: Some(lsp_types::CompletionItemKind::VALUE),
+                                detail: Some("a recent commit author".to_owned()),
+                                text_edit: Some(lsp_types::CompletionTextEdit::Edit(
+                                    lsp_types::TextEdit {
+                                        range: lsp_types::Range {
+                                            start: lsp_types::Position {
+                                                line: position.line,
+                                                character: start as u32,
+                                            },
                                             end: lsp_types::Position {
                                                 line: position.line,
-                                                character: "Signed-off-by:".len() as u32,
+                                                character: end,
                                             },
                                         },
-                                        new_text: "Signed-off-by: ".to_owned(),
+                                        new_text: author,
                                     },
                                 )),
                                 ..Default::default()
                             });
                         }
-
-                        log_debug!("end of message completions?");
                     }
                 }
             }
@@ -658,7 +1097,7 @@ impl<Cfg: ConfigStore> Server<Cfg> {
                 if _position.character <= _type_len as u32 {
                     if let Some((_, doc)) = self
                         .config
-                        .get(commit.worktree_root.clone())?
+                        .get_for(commit)?
                         .type_suggestions()
                         .iter()
                         .find(|(type_, _doc)| type_.as_str() == _type_text)
@@ -680,8 +1119,28 @@ impl<Cfg: ConfigStore> Server<Cfg> {
                             error: None,
                         });
                     }
-                } else {
-                    // TODO: provide on-hover docs for scopes.
+                } else if _position.character
+                    <= (_type_len + subject.scope_text().chars().count()) as u32
+                {
+                    let scope = subject.scope_name().trim();
+                    if let Some(value) = self.config.get_for(commit)?.scope_hover(scope) {
+                        return Ok(Response {
+                            id: id.clone(),
+                            result: Some(
+                                serde_json::to_value(lsp_types::Hover {
+                                    contents: lsp_types::HoverContents::Markup(
+                                        lsp_types::MarkupContent {
+                                            kind: lsp_types::MarkupKind::Markdown,
+                                            value,
+                                        },
+                                    ),
+                                    range: None,
+                                })
+                                .unwrap(),
+                            ),
+                            error: None,
+                        });
+                    }
                 }
             }
         }
@@ -691,23 +1150,41 @@ impl<Cfg: ConfigStore> Server<Cfg> {
             error: None,
         })
     }
-    fn handle_token_full(
+    /// compute `params`' document's tokens, remembering them under a new `result_id`
+    fn tokenize(
         &mut self,
-        id: &RequestId,
         params: lsp_types::SemanticTokensParams,
-    ) -> Result<Response, Box<dyn Error + Send + Sync>> {
-        span!(tracing::Level::INFO, "handle_token_full");
-        let uri = &params.text_document.uri;
+    ) -> Result<lsp_types::SemanticTokens, Box<dyn Error + Send + Sync>> {
+        let uri = params.text_document.uri.clone();
         let commit = self
             .commits
-            .get(uri)
+            .get(&uri)
             .ok_or(anyhow!("no such document {uri}"))?;
-        let data =
-            syntax_token_scopes::handle_all_tokens(&self.client_capabilities, commit, params)?;
-        let result = lsp_types::SemanticTokensResult::Tokens(lsp_types::SemanticTokens {
-            result_id: None,
+        let deprecated_types = self.config.get_for(commit)?.deprecated_types();
+        let data = syntax_token_scopes::handle_all_tokens(
+            &self.client_capabilities,
+            commit,
+            params,
+            &deprecated_types,
+        )?;
+        let result_id = self
+            .semantic_tokens
+            .get(&uri)
+            .map(|(result_id, _)| result_id + 1)
+            .unwrap_or(0);
+        self.semantic_tokens.insert(uri, (result_id, data.clone()));
+        Ok(lsp_types::SemanticTokens {
+            result_id: Some(result_id.to_string()),
             data,
-        });
+        })
+    }
+    fn handle_token_full(
+        &mut self,
+        id: &RequestId,
+        params: lsp_types::SemanticTokensParams,
+    ) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        span!(tracing::Level::INFO, "handle_token_full");
+        let result = lsp_types::SemanticTokensResult::Tokens(self.tokenize(params)?);
         let result: Response = Response {
             id: id.clone(),
             result: Some(serde_json::to_value(result).unwrap()),
@@ -715,6 +1192,38 @@ impl<Cfg: ConfigStore> Server<Cfg> {
         };
         Ok(result)
     }
+    /// send only what changed since the client's `previous_result_id`, or every token if that's stale
+    fn handle_token_full_delta(
+        &mut self,
+        id: &RequestId,
+        params: lsp_types::SemanticTokensDeltaParams,
+    ) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        span!(tracing::Level::INFO, "handle_token_full_delta");
+        let previous = self
+            .semantic_tokens
+            .get(&params.text_document.uri)
+            .filter(|(result_id, _)| result_id.to_string() == params.previous_result_id)
+            .map(|(_, tokens)| tokens.clone());
+        let tokens = self.tokenize(lsp_types::SemanticTokensParams {
+            work_done_progress_params: params.work_done_progress_params,
+            partial_result_params: params.partial_result_params,
+            text_document: params.text_document,
+        })?;
+        let result = match previous {
+            Some(previous) => lsp_types::SemanticTokensFullDeltaResult::TokensDelta(
+                lsp_types::SemanticTokensDelta {
+                    edits: syntax_token_scopes::diff_tokens(&previous, &tokens.data),
+                    result_id: tokens.result_id,
+                },
+            ),
+            None => lsp_types::SemanticTokensFullDeltaResult::Tokens(tokens),
+        };
+        Ok(Response {
+            id: id.clone(),
+            result: Some(serde_json::to_value(result).unwrap()),
+            error: None,
+        })
+    }
     fn handle_doc_link_request(
         &mut self,
         id: &RequestId,
@@ -727,128 +1236,384 @@ impl<Cfg: ConfigStore> Server<Cfg> {
             panic!("no such document {uri}");
         }
         let commit = commit.unwrap();
+        let mut links = commit.get_links();
+        for link in links.iter_mut() {
+            // note which document an unresolved link came from
+            if let Some(serde_json::Value::Object(data)) = link.data.as_mut() {
+                data.insert("uri".into(), uri.to_string().into());
+            }
+        }
         Ok(lsp_server::Response {
             id: id.clone(),
-            result: Some(serde_json::to_value(commit.get_links()).unwrap()),
+            result: Some(serde_json::to_value(links).unwrap()),
+            error: None,
+        })
+    }
+    fn handle_doc_link_resolve(
+        &mut self,
+        id: &RequestId,
+        link: lsp_types::DocumentLink,
+    ) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        span!(tracing::Level::INFO, "handle_doc_link_resolve");
+        let commit = link.data.as_ref().and_then(|data| {
+            let uri: Url = data.get("uri")?.as_str()?.parse().ok()?;
+            self.commits.get(&uri)
+        });
+        let link = match commit {
+            Some(commit) => commit.resolve_link(link),
+            None => link,
+        };
+        Ok(Response {
+            id: id.clone(),
+            result: Some(serde_json::to_value(link).unwrap()),
+            error: None,
+        })
+    }
+    /// only trailer keys can be renamed
+    fn handle_prepare_rename(
+        &mut self,
+        id: &RequestId,
+        params: lsp_types::TextDocumentPositionParams,
+    ) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        span!(tracing::Level::INFO, "handle_prepare_rename");
+        let uri = &params.text_document.uri;
+        let commit = self
+            .commits
+            .get(uri)
+            .ok_or(anyhow!("no such document {uri}"))?;
+        let range = commit
+            .trailer_key_at(&params.position)
+            .ok_or(anyhow!("only trailer keys can be renamed"))?;
+        Ok(Response {
+            id: id.clone(),
+            result: Some(
+                serde_json::to_value(lsp_types::PrepareRenameResponse::Range(range)).unwrap(),
+            ),
+            error: None,
+        })
+    }
+    /// rename every occurrence of a trailer key, e.g. all `Reviewed-by` to `Acked-by`
+    fn handle_rename(
+        &mut self,
+        id: &RequestId,
+        params: lsp_types::RenameParams,
+    ) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        span!(tracing::Level::INFO, "handle_rename");
+        let uri = &params.text_document_position.text_document.uri;
+        let commit = self
+            .commits
+            .get(uri)
+            .ok_or(anyhow!("no such document {uri}"))?;
+        let new_name = params.new_name.trim();
+        if new_name.is_empty() || !new_name.chars().all(|c| c.is_alphanumeric() || c == '-') {
+            return Err(anyhow!("{new_name:?} is not a valid trailer key").into());
+        }
+        let edits = commit
+            .rename_trailer_key(&params.text_document_position.position, new_name)
+            .ok_or(anyhow!("only trailer keys can be renamed"))?;
+        let edit = lsp_types::WorkspaceEdit {
+            changes: Some(HashMap::from([(uri.clone(), edits)])),
+            ..Default::default()
+        };
+        Ok(Response {
+            id: id.clone(),
+            result: Some(serde_json::to_value(edit).unwrap()),
+            error: None,
+        })
+    }
+    /// highlight occurrences of the scope under the cursor
+    fn handle_document_highlight(
+        &mut self,
+        id: &RequestId,
+        params: lsp_types::DocumentHighlightParams,
+    ) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        span!(tracing::Level::INFO, "handle_document_highlight");
+        let uri = &params.text_document_position_params.text_document.uri;
+        let commit = self
+            .commits
+            .get(uri)
+            .ok_or(anyhow!("no such document {uri}"))?;
+        let highlights = commit.scope_highlights(&params.text_document_position_params.position);
+        Ok(Response {
+            id: id.clone(),
+            result: Some(serde_json::to_value(highlights).unwrap()),
+            error: None,
+        })
+    }
+    /// jump from the subject's type or scope to where the config file declares it
+    fn handle_definition(
+        &mut self,
+        id: &RequestId,
+        params: lsp_types::GotoDefinitionParams,
+    ) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        span!(tracing::Level::INFO, "handle_definition");
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        let commit = self
+            .commits
+            .get(uri)
+            .ok_or(anyhow!("no such document {uri}"))?;
+        let mut location = None;
+        if let Some(subject) = commit
+            .subject
+            .as_ref()
+            .filter(|subject| position.line == subject.line_number as u32)
+        {
+            let type_len = subject.type_text().chars().count() as u32;
+            let scope_len = subject.scope_text().chars().count() as u32;
+            if position.character <= type_len {
+                location = self
+                    .config
+                    .get_for(commit)?
+                    .type_definition(subject.type_text().trim());
+            } else if position.character <= type_len + scope_len {
+                let scope = subject.scope_name().trim();
+                location = self.config.get_for(commit)?.scope_definition(scope);
+            }
+        }
+        Ok(Response {
+            id: id.clone(),
+            result: Some(serde_json::to_value(location).unwrap()),
+            error: None,
+        })
+    }
+    /// show the release the commit calls for above its subject
+    fn handle_code_lens(
+        &mut self,
+        id: &RequestId,
+        params: lsp_types::CodeLensParams,
+    ) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        span!(tracing::Level::INFO, "handle_code_lens");
+        let uri = &params.text_document.uri;
+        let commit = self
+            .commits
+            .get(uri)
+            .ok_or(anyhow!("no such document {uri}"))?;
+        let cfg = self.config.get_for(commit)?;
+        let lenses = commit.semver_lenses(|type_| cfg.semver_bump(type_));
+        Ok(Response {
+            id: id.clone(),
+            result: Some(serde_json::to_value(lenses).unwrap()),
+            error: None,
+        })
+    }
+    /// count the characters left before the subject hits `header_max_line_length`
+    fn handle_inlay_hint(
+        &mut self,
+        id: &RequestId,
+        params: lsp_types::InlayHintParams,
+    ) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        span!(tracing::Level::INFO, "handle_inlay_hint");
+        let uri = &params.text_document.uri;
+        let commit = self
+            .commits
+            .get(uri)
+            .ok_or(anyhow!("no such document {uri}"))?;
+        let cutoff = self.config.get_for(commit)?.max_subject_length();
+        let hints: Vec<lsp_types::InlayHint> = commit
+            .subject_length_hint(cutoff)
+            .filter(|hint| {
+                (params.range.start.line..=params.range.end.line).contains(&hint.position.line)
+            })
+            .into_iter()
+            .collect();
+        Ok(Response {
+            id: id.clone(),
+            result: Some(serde_json::to_value(hints).unwrap()),
+            error: None,
+        })
+    }
+    /// fold the body's paragraphs and the trailer block
+    fn handle_folding_range(
+        &mut self,
+        id: &RequestId,
+        params: lsp_types::FoldingRangeParams,
+    ) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        span!(tracing::Level::INFO, "handle_folding_range");
+        let uri = &params.text_document.uri;
+        let commit = self
+            .commits
+            .get(uri)
+            .ok_or(anyhow!("no such document {uri}"))?;
+        Ok(Response {
+            id: id.clone(),
+            result: Some(serde_json::to_value(commit.folding_ranges()).unwrap()),
+            error: None,
+        })
+    }
+    /// outline the subject's parts, the body, and the trailers
+    fn handle_document_symbol(
+        &mut self,
+        id: &RequestId,
+        params: lsp_types::DocumentSymbolParams,
+    ) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        span!(tracing::Level::INFO, "handle_document_symbol");
+        let uri = &params.text_document.uri;
+        let commit = self
+            .commits
+            .get(uri)
+            .ok_or(anyhow!("no such document {uri}"))?;
+        let symbols = lsp_types::DocumentSymbolResponse::Nested(commit.document_symbols());
+        Ok(Response {
+            id: id.clone(),
+            result: Some(serde_json::to_value(symbols).unwrap()),
+            error: None,
+        })
+    }
+    /// offer each fixable diagnostic in the range as a quick fix, plus one action applying every
+    /// fix in the document that doesn't touch another
+    fn handle_code_action(
+        &mut self,
+        id: &RequestId,
+        params: lsp_types::CodeActionParams,
+    ) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        span!(tracing::Level::INFO, "handle_code_action");
+        let uri = &params.text_document.uri;
+        let commit = self
+            .commits
+            .get(uri)
+            .ok_or(anyhow!("no such document {uri}"))?;
+        let diagnostics = self.config.get_for(commit)?.lint(commit);
+        let wanted = |kind: &lsp_types::CodeActionKind| {
+            params.context.only.as_ref().is_none_or(|only| {
+                only.iter()
+                    .any(|prefix| kind.as_str().starts_with(prefix.as_str()))
+            })
+        };
+        let edit = |edits: Vec<lsp_types::TextEdit>| lsp_types::WorkspaceEdit {
+            changes: Some(HashMap::from([(uri.clone(), edits)])),
+            ..Default::default()
+        };
+        let mut actions = vec![];
+        if wanted(&lsp_types::CodeActionKind::QUICKFIX) {
+            for diagnostic in diagnostics
+                .iter()
+                .filter(|d| d.range.start <= params.range.end && params.range.start <= d.range.end)
+            {
+                if let Some(fix) = utils::get_fix(diagnostic) {
+                    actions.push(lsp_types::CodeActionOrCommand::CodeAction(
+                        lsp_types::CodeAction {
+                            title: format!("Fix: {}", diagnostic.message),
+                            kind: Some(lsp_types::CodeActionKind::QUICKFIX),
+                            diagnostics: Some(vec![diagnostic.clone()]),
+                            edit: Some(edit(fix)),
+                            is_preferred: Some(true),
+                            ..Default::default()
+                        },
+                    ));
+                }
+            }
+        }
+        if wanted(&lsp_types::CodeActionKind::SOURCE_FIX_ALL) {
+            let mut fixes: Vec<lsp_types::TextEdit> = diagnostics
+                .iter()
+                .filter_map(utils::get_fix)
+                .flatten()
+                .collect();
+            fixes.sort_by_key(|fix| (fix.range.start, fix.range.end));
+            fixes.dedup();
+            // edits in one WorkspaceEdit must not overlap, and fixes that touch may contradict
+            // each other (e.g. joining a wrapped subject vs. padding it), so the first one wins
+            let mut merged: Vec<lsp_types::TextEdit> = vec![];
+            for fix in fixes {
+                if merged
+                    .last()
+                    .is_none_or(|previous| previous.range.end < fix.range.start)
+                {
+                    merged.push(fix);
+                }
+            }
+            if !merged.is_empty() {
+                actions.push(lsp_types::CodeActionOrCommand::CodeAction(
+                    lsp_types::CodeAction {
+                        title: "Fix all auto-fixable problems".into(),
+                        kind: Some(lsp_types::CodeActionKind::SOURCE_FIX_ALL),
+                        edit: Some(edit(merged)),
+                        ..Default::default()
+                    },
+                ));
+            }
+        }
+        Ok(Response {
+            id: id.clone(),
+            result: Some(serde_json::to_value(actions).unwrap()),
+            error: None,
+        })
+    }
+    /// format only the selected lines, e.g. just the subject
+    fn handle_range_formatting(
+        &mut self,
+        id: &RequestId,
+        params: lsp_types::DocumentRangeFormattingParams,
+    ) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        span!(tracing::Level::INFO, "handle_range_formatting");
+        let uri = &params.text_document.uri;
+        let commit = self
+            .commits
+            .get(uri)
+            .ok_or(anyhow!("no such document {uri}"))?;
+        let options = self.config.get_for(commit)?.format_options();
+        Ok(Response {
+            id: id.clone(),
+            result: Some(
+                serde_json::to_value(commit.format_range(&options, &params.range)).unwrap(),
+            ),
+            error: None,
+        })
+    }
+    /// attach a preview of the formatted subject to type and scope completions
+    fn handle_resolving_completion_item(
+        &mut self,
+        id: &RequestId,
+        mut item: lsp_types::CompletionItem,
+    ) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        span!(tracing::Level::INFO, "handle_resolving_completion_item");
+        let preview = item.data.as_ref().and_then(|data| {
+            let uri: Url = data.get("uri")?.as_str()?.parse().ok()?;
+            let part = data.get("part")?.as_str()?;
+            self.commits
+                .get(&uri)?
+                .completion_preview(part, &item.label)
+        });
+        if let Some(preview) = preview {
+            item.documentation = Some(lsp_types::Documentation::MarkupContent(
+                lsp_types::MarkupContent {
+                    kind: lsp_types::MarkupKind::Markdown,
+                    value: format!("```git-commit\n{preview}\n```"),
+                },
+            ));
+        }
+        Ok(Response {
+            id: id.clone(),
+            result: Some(serde_json::to_value(item).unwrap()),
             error: None,
         })
     }
-    // fn handle_range_formatting(
-    //     &self,
-    //     id: &RequestId,
-    //     params: DocumentRangeFormattingParams,
-    // ) -> Result<Response, Box<dyn Error + Send + Sync>> {
-    //     todo!("range_formatting")
-    // }
-    // fn handle_resolving_completion_item(
-    //     &self,
-    //     id: &RequestId,
-    //     params: CompletionItem,
-    // ) -> Result<Response, Box<dyn Error + Send + Sync>> {
-    //     todo!("resolving_completion_item")
-    // }
     /// see https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#textDocument_selectionRange
-    // fn handle_selection_range_request(
-    //     &self,
-    //     id: &RequestId,
-    //     params: SelectionRangeParams,
-    // ) -> Result<Response, Box<dyn Error + Send + Sync>> {
-    //     let result: Vec<lsp_types::SelectionRange> = params
-    //         .positions
-    //         .iter()
-    //         .map(|pos| {
-    //             if let Some(subject) = &self.commit.subject {
-    //                 eprintln!("expanding selection range in subject: {:?}", pos);
-    //                 if pos.line == subject.line_number as u32 {
-    //                     let type_len = subject.type_text().chars().count();
-    //                     let scope_len = subject.scope_text().chars().count();
-    //                     let rest_len = subject.rest_text().chars().count();
-    //                     let prefix = lsp_types::SelectionRange {
-    //                         range: lsp_types::Range {
-    //                             start: lsp_types::Position {
-    //                                 line: pos.line,
-    //                                 character: 0,
-    //                             },
-    //                             end: lsp_types::Position {
-    //                                 line: pos.line,
-    //                                 character: type_len as u32,
-    //                             },
-    //                         },
-    //                         parent: None,
-    //                     };
-    //                     let _prefix = Box::new(prefix);
-    //                     if pos.character <= type_len as u32 {
-    //                         return lsp_types::SelectionRange {
-    //                             range: lsp_types::Range {
-    //                                 start: lsp_types::Position {
-    //                                     line: pos.line,
-    //                                     character: 0,
-    //                                 },
-    //                                 end: lsp_types::Position {
-    //                                     line: pos.line,
-    //                                     character: type_len as u32,
-    //                                 },
-    //                             },
-    //                             parent: Some(_prefix),
-    //                         };
-    //                     };
-    //                     if pos.character <= (type_len + scope_len) as u32 {
-    //                         return lsp_types::SelectionRange {
-    //                             range: lsp_types::Range {
-    //                                 start: lsp_types::Position {
-    //                                     line: pos.line,
-    //                                     character: type_len as u32,
-    //                                 },
-    //                                 end: lsp_types::Position {
-    //                                     line: pos.line,
-    //                                     character: (type_len + scope_len) as u32,
-    //                                 },
-    //                             },
-    //                             parent: Some(_prefix),
-    //                         };
-    //                     };
-    //                     if pos.character <= (type_len + scope_len + rest_len) as u32 {
-    //                         return lsp_types::SelectionRange {
-    //                             range: lsp_types::Range {
-    //                                 start: lsp_types::Position {
-    //                                     line: pos.line,
-    //                                     character: (type_len + scope_len) as u32,
-    //                                 },
-    //                                 end: lsp_types::Position {
-    //                                     line: pos.line,
-    //                                     character: (type_len + scope_len + rest_len) as u32,
-    //                                 },
-    //                             },
-    //                             parent: Some(_prefix),
-    //                         };
-    //                     }
-    //                     return lsp_types::SelectionRange {
-    //                         range: lsp_types::Range {
-    //                             start: lsp_types::Position {
-    //                                 line: pos.line,
-    //                                 character: (type_len + scope_len + rest_len) as u32,
-    //                             },
-    //                             end: lsp_types::Position {
-    //                                 line: pos.line,
-    //                                 character: subject.line.chars().count() as u32,
-    //                             },
-    //                         },
-    //                         parent: None,
-    //                     };
-    //                 };
-    //             };
-    //             // TODO: check for belonging to a trailer
-    //             Default::default()
-    //         })
-    //         .collect();
-    //     Ok(Response {
-    //         id: id.clone(),
-    //         result: Some(serde_json::to_value(result).unwrap()),
-    //         error: None,
-    //     })
-    // }
+    fn handle_selection_range_request(
+        &mut self,
+        id: &RequestId,
+        params: lsp_types::SelectionRangeParams,
+    ) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        span!(tracing::Level::INFO, "handle_selection_range_request");
+        let uri = &params.text_document.uri;
+        let commit = self
+            .commits
+            .get(uri)
+            .ok_or(anyhow!("no such document {uri}"))?;
+        let result: Vec<lsp_types::SelectionRange> = params
+            .positions
+            .iter()
+            .map(|position| commit.selection_range(position))
+            .collect();
+        Ok(Response {
+            id: id.clone(),
+            result: Some(serde_json::to_value(result).unwrap()),
+            error: None,
+        })
+    }
     fn handle_on_type_formatting(
-        &self,
+        &mut self,
         id: &RequestId,
         params: DocumentOnTypeFormattingParams,
     ) -> Result<Response, Box<dyn Error + Send + Sync>> {
@@ -860,7 +1625,8 @@ impl<Cfg: ConfigStore> Server<Cfg> {
             panic!("no such document {uri}");
         }
         let commit = commit.unwrap();
-        let result: Vec<lsp_types::TextEdit> = commit.format();
+        let options = self.config.get_for(commit)?.format_options();
+        let result: Vec<lsp_types::TextEdit> = commit.format(&options);
         Ok(Response {
             id: id.clone(),
             result: Some(serde_json::to_value(result).unwrap()),
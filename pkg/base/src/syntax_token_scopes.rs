@@ -24,6 +24,10 @@ lazy_static! {
         "text.uri",
         "text.warning",
     ];
+    pub static ref SYNTAX_TOKEN_MODIFIERS: Vec<lsp_types::SemanticTokenModifier> = vec![
+        lsp_types::SemanticTokenModifier::DEPRECATED,
+        lsp_types::SemanticTokenModifier::READONLY,
+    ];
     pub static ref SYNTAX_TOKEN_SCOPES: HashMap<&'static str, u32> = {
         let mut m = HashMap::new();
         for (i, s) in SYNTAX_TOKEN_LEGEND.iter().enumerate() {
@@ -33,6 +37,15 @@ lazy_static! {
     };
 }
 
+/// the bit for `modifier` in a token's `token_modifiers_bitset`
+fn modifier_bit(modifier: &lsp_types::SemanticTokenModifier) -> u32 {
+    let index = SYNTAX_TOKEN_MODIFIERS
+        .iter()
+        .position(|m| m == modifier)
+        .unwrap();
+    1 << index
+}
+
 struct TokenCapabilities(u8);
 impl TokenCapabilities {
     const MULTILINE: u8 = 0b0000_1111;
@@ -49,10 +62,11 @@ impl TokenCapabilities {
         (self.0 & Self::OVERLAP) != 0
     }
 }
-pub(crate) fn handle_all_tokens(
+pub fn handle_all_tokens(
     _client_capabilities: &lsp_types::ClientCapabilities,
     doc: &crate::document::GitCommitDocument,
     _params: lsp_types::SemanticTokensParams,
+    deprecated_types: &[String],
 ) -> Result<Vec<SemanticToken>, Box<dyn Error + Send + Sync>> {
     let _client = {
         // see https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#semanticTokensClientCapabilities
@@ -115,6 +129,23 @@ pub(crate) fn handle_all_tokens(
             start_col = range.end_point.column as u32;
 
             let token_type: u32 = *SYNTAX_TOKEN_SCOPES.get(capture_name).unwrap();
+            let mut token_modifiers_bitset = 0;
+            if capture.node.kind() == "type"
+                && deprecated_types
+                    .iter()
+                    .any(|t| doc.slice_of(capture.node) == t.as_str())
+            {
+                token_modifiers_bitset |=
+                    modifier_bit(&lsp_types::SemanticTokenModifier::DEPRECATED);
+            }
+            // e.g. the file paths in `# Changes to be committed:`; editing them changes nothing
+            if capture
+                .node
+                .parent()
+                .is_some_and(|p| matches!(p.kind(), "comment" | "generated_comment"))
+            {
+                token_modifiers_bitset |= modifier_bit(&lsp_types::SemanticTokenModifier::READONLY);
+            }
             // See https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#textDocument_semanticTokens
             let token = lsp_types::SemanticToken {
                 delta_line,  // token line number, relative to the previous token
@@ -130,7 +161,7 @@ pub(crate) fn handle_all_tokens(
                     }
                 },
                 token_type,
-                token_modifiers_bitset: 0,
+                token_modifiers_bitset,
             };
 
             tokens.push(token);
@@ -138,3 +169,90 @@ pub(crate) fn handle_all_tokens(
     }
     Ok(tokens)
 }
+
+/// a single edit replacing the tokens between `old` and `new`'s common prefix and suffix, if any
+pub fn diff_tokens(
+    old: &[SemanticToken],
+    new: &[SemanticToken],
+) -> Vec<lsp_types::SemanticTokensEdit> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if prefix + suffix == old.len() && old.len() == new.len() {
+        return vec![];
+    }
+    // edits index into the flattened array, where each token is 5 integers
+    vec![lsp_types::SemanticTokensEdit {
+        start: (prefix * 5) as u32,
+        delete_count: ((old.len() - prefix - suffix) * 5) as u32,
+        data: Some(new[prefix..new.len() - suffix].to_vec()),
+    }]
+}
+
+#[test]
+fn test_deprecated_type_modifier() {
+    let doc = crate::document::GitCommitDocument::new().with_text(
+        "feature(api): x\n\n\
+        # Please enter the commit message for your changes. Lines starting\n\
+        # with '#' will be ignored, and an empty message aborts the commit.\n\
+        #\n\
+        # On branch main\n\
+        # Changes to be committed:\n\
+        #\tmodified:   README.md\n"
+            .into(),
+    );
+    let params = lsp_types::SemanticTokensParams {
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+        text_document: lsp_types::TextDocumentIdentifier {
+            uri: "file:///COMMIT_EDITMSG".parse().unwrap(),
+        },
+    };
+    let tokens = |deprecated: &[String]| {
+        handle_all_tokens(&Default::default(), &doc, params.clone(), deprecated).unwrap()
+    };
+    let deprecated = modifier_bit(&lsp_types::SemanticTokenModifier::DEPRECATED);
+    let readonly = modifier_bit(&lsp_types::SemanticTokenModifier::READONLY);
+    let keyword = SYNTAX_TOKEN_SCOPES["keyword"];
+
+    let marked = tokens(&["feature".into()]);
+    let type_token = marked.iter().find(|t| t.token_type == keyword).unwrap();
+    assert_eq!(type_token.token_modifiers_bitset, deprecated);
+    assert!(marked.iter().any(|t| t.token_modifiers_bitset == readonly));
+    let unmarked = tokens(&[]);
+    let type_token = unmarked.iter().find(|t| t.token_type == keyword).unwrap();
+    assert_eq!(type_token.token_modifiers_bitset, 0);
+}
+
+#[test]
+fn test_diff_tokens() {
+    let tokens = |text: &str| {
+        let doc = crate::document::GitCommitDocument::new().with_text(text.into());
+        let params = lsp_types::SemanticTokensParams {
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            text_document: lsp_types::TextDocumentIdentifier {
+                uri: "file:///COMMIT_EDITMSG".parse().unwrap(),
+            },
+        };
+        handle_all_tokens(&Default::default(), &doc, params, &[]).unwrap()
+    };
+    let old = tokens("feat(api): add a thing\n\nbody\n\nRefs: #1\nSigned-off-by: A <a@b.c>\n");
+    let new = tokens("feat(api): add a thing\n\nbody\n\nRefs: #12\nSigned-off-by: A <a@b.c>\n");
+    assert!(diff_tokens(&old, &old).is_empty());
+    let edits = diff_tokens(&old, &new);
+    assert_eq!(edits.len(), 1);
+    let edit = &edits[0];
+    let (start, deleted) = (edit.start as usize / 5, edit.delete_count as usize / 5);
+    let inserted = edit.data.clone().unwrap();
+    // only the tokens on the edited trailer line change
+    assert!(start > 0 && deleted < old.len(), "{edit:?}");
+    assert_eq!(deleted, inserted.len());
+    let mut patched = old.clone();
+    patched.splice(start..start + deleted, inserted);
+    assert_eq!(patched, new);
+}
@@ -0,0 +1,57 @@
+// © Steven Kalt
+// SPDX-License-Identifier: APACHE-2.0
+
+//! invoke the built `base_language_server` binary
+#![cfg(feature = "cli")]
+use std::process::Command;
+
+const BIN: &str = env!("CARGO_BIN_EXE_base_language_server");
+
+#[test]
+fn test_version() {
+    let output = Command::new(BIN).arg("--version").output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!("base {}\n", env!("CARGO_PKG_VERSION"))
+    );
+}
+
+#[test]
+fn test_help() {
+    let output = Command::new(BIN).arg("--help").output().unwrap();
+    assert!(output.status.success());
+    let help = String::from_utf8(output.stdout).unwrap();
+    for subcommand in ["serve", "check", "lint"] {
+        assert!(
+            help.contains(subcommand),
+            "{subcommand} missing from:\n{help}"
+        );
+    }
+}
+
+#[test]
+fn test_require_config() {
+    // the base server never reads a project config, so it always falls back to the defaults
+    let mut child = Command::new(BIN)
+        .args(["check", "--stdin", "--require-config"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    use std::io::Write;
+    // without a usable config, the check may fail before reading stdin
+    let _ = child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"feat: add a thing\n");
+    let output = child.wait_with_output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("no commit convention config found"),
+        "{stderr}"
+    );
+}
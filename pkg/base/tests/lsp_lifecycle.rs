@@ -0,0 +1,792 @@
+// © Steven Kalt
+// SPDX-License-Identifier: APACHE-2.0
+
+//! Drive a `Server` through an in-memory connection, comparing each response to a JSON snapshot
+//! in `./snapshots/`. Run with `UPDATE_SNAPSHOTS=1` to (re)write the snapshots.
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use base::{
+    config::{ClientSettings, Config, ConfigStore, Features},
+    document::linting::{utils::construct_default_lint_tests_map, LintConfig, LintFn},
+    server::{advertised_capabilities, Server, CAPABILITIES},
+};
+use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+use serde_json::{json, Value};
+
+struct TestConfig {
+    tests: HashMap<&'static str, Arc<LintFn<'static>>>,
+}
+impl LintConfig for TestConfig {
+    fn worktree_root(&self) -> Option<PathBuf> {
+        None
+    }
+    fn get_test(&self, code: &str) -> Option<&Arc<LintFn<'_>>> {
+        self.tests.get(code)
+    }
+}
+impl Config for TestConfig {
+    fn scope_suggestions(&self) -> Vec<(String, String)> {
+        vec![("api".to_owned(), "the public API".to_owned())]
+    }
+    fn gitmoji(&self) -> bool {
+        true
+    }
+    fn type_definition(&self, type_: &str) -> Option<lsp_types::Location> {
+        (type_ == "feat").then(|| declaration(1))
+    }
+    fn scope_definition(&self, scope: &str) -> Option<lsp_types::Location> {
+        (scope == "api").then(|| declaration(4))
+    }
+}
+
+/// a line of a stand-in config file
+fn declaration(line: u32) -> lsp_types::Location {
+    let uri = "file:///tmp/cconvention-test/commit_convention.json";
+    lsp_types::Location::new(
+        uri.parse().unwrap(),
+        lsp_types::Range::new(
+            lsp_types::Position::new(line, 4),
+            lsp_types::Position::new(line, 7),
+        ),
+    )
+}
+
+#[derive(Default)]
+struct TestConfigStore {
+    settings: ClientSettings,
+}
+#[allow(clippy::arc_with_non_send_sync)] // configs never leave the server's thread
+impl ConfigStore for TestConfigStore {
+    fn get(
+        &mut self,
+        _worktree_root: Option<PathBuf>,
+    ) -> Result<Arc<dyn Config>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Arc::new(TestConfig {
+            tests: construct_default_lint_tests_map(self.settings.max_subject_length.unwrap_or(50)),
+        }))
+    }
+    fn set_settings(&mut self, settings: ClientSettings) {
+        self.settings = settings;
+    }
+    fn recent_authors(&mut self, _worktree_root: Option<PathBuf>) -> Vec<String> {
+        vec![
+            "Ada Lovelace <ada@example.com>".to_owned(),
+            "Alan Turing <alan@example.com>".to_owned(),
+        ]
+    }
+}
+
+struct Client {
+    connection: Connection,
+    next_id: i32,
+}
+
+impl Client {
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        self.next_id += 1;
+        let id = RequestId::from(self.next_id);
+        self.connection
+            .sender
+            .send(Message::Request(Request {
+                id: id.clone(),
+                method: method.to_owned(),
+                params,
+            }))
+            .unwrap();
+        match self.connection.receiver.recv().unwrap() {
+            Message::Response(response) => {
+                assert_eq!(response.id, id);
+                assert!(response.error.is_none(), "{:?}", response.error);
+                response.result.unwrap_or(Value::Null)
+            }
+            other => panic!("expected a response to {method}, got {other:?}"),
+        }
+    }
+    fn notify(&mut self, method: &str, params: Value) {
+        self.connection
+            .sender
+            .send(Message::Notification(Notification {
+                method: method.to_owned(),
+                params,
+            }))
+            .unwrap();
+    }
+    /// answer the server's next request, which must be a `method` request
+    fn reply(&mut self, method: &str, result: impl FnOnce(Value) -> Value) {
+        match self.connection.receiver.recv().unwrap() {
+            Message::Request(request) if request.method == method => self
+                .connection
+                .sender
+                .send(Message::Response(Response::new_ok(
+                    request.id,
+                    result(request.params),
+                )))
+                .unwrap(),
+            other => panic!("expected a {method} request, got {other:?}"),
+        }
+    }
+    fn expect_notification(&mut self, method: &str) -> Value {
+        match self.connection.receiver.recv().unwrap() {
+            Message::Notification(notification) if notification.method == method => {
+                notification.params
+            }
+            other => panic!("expected a {method} notification, got {other:?}"),
+        }
+    }
+}
+
+fn assert_snapshot(name: &str, actual: &Value) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.json"));
+    let actual_text = serde_json::to_string_pretty(actual).unwrap() + "\n";
+    if std::env::var("UPDATE_SNAPSHOTS").is_ok() {
+        std::fs::write(&path, actual_text).unwrap();
+        return;
+    }
+    let expected_text = std::fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing snapshot {path:?}; rerun with UPDATE_SNAPSHOTS=1"));
+    assert_eq!(
+        expected_text, actual_text,
+        "snapshot {name} changed; rerun with UPDATE_SNAPSHOTS=1 to accept"
+    );
+}
+
+/// start a server on its own thread, returning the initialized client and the server's capabilities
+fn start(
+    client_capabilities: Value,
+    features: Features,
+) -> (Client, std::thread::JoinHandle<()>, Value) {
+    let (client_connection, server_connection) = Connection::memory();
+    let server = std::thread::spawn(move || {
+        let mut server = Server::from_connection(TestConfigStore::default(), server_connection)
+            .with_features(features);
+        server.init(&CAPABILITIES).unwrap().serve().unwrap();
+    });
+    let mut client = Client {
+        connection: client_connection,
+        next_id: 0,
+    };
+    let init = client.request(
+        "initialize",
+        json!({"processId": null, "rootUri": null, "capabilities": client_capabilities}),
+    );
+    client.notify("initialized", json!({}));
+    (client, server, init["capabilities"].clone())
+}
+
+#[test]
+fn test_lsp_lifecycle() {
+    let (mut client, server, capabilities) = start(json!({}), Features::default());
+    assert_snapshot("initialize", &capabilities);
+
+    let uri = "file:///tmp/cconvention-test/.git/COMMIT_EDITMSG";
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {
+            "uri": uri,
+            "languageId": "git-commit",
+            "version": 1,
+            "text": "feat:add a thing\nwith a wrapped subject\n# a comment\n",
+        }}),
+    );
+    let diagnostics = client.expect_notification("textDocument/publishDiagnostics");
+    assert_snapshot("publish_diagnostics", &diagnostics);
+
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": {"uri": uri, "version": 2},
+            "contentChanges": [{
+                "range": {"start": {"line": 0, "character": 5}, "end": {"line": 0, "character": 5}},
+                "text": " ",
+            }],
+        }),
+    );
+    let diagnostics = client.expect_notification("textDocument/publishDiagnostics");
+    assert_eq!(diagnostics["version"], 2);
+
+    let position = json!({"textDocument": {"uri": uri}, "position": {"line": 0, "character": 2}});
+    let completion = client.request("textDocument/completion", position.clone());
+    assert_snapshot("completion", &completion);
+    let resolved = client.request("completionItem/resolve", completion["items"][1].clone());
+    assert_eq!(
+        resolved["documentation"]["value"],
+        "```git-commit\nfix: add a thing\n```"
+    );
+    assert_snapshot("hover", &client.request("textDocument/hover", position));
+    assert_snapshot(
+        "formatting",
+        &client.request(
+            "textDocument/formatting",
+            json!({
+                "textDocument": {"uri": uri},
+                "options": {"tabSize": 2, "insertSpaces": true},
+            }),
+        ),
+    );
+
+    client.notify("exit", Value::Null);
+    server.join().unwrap();
+}
+
+#[test]
+fn test_code_actions() {
+    let (mut client, server, capabilities) = start(json!({}), Features::default());
+    assert!(capabilities.get("codeActionProvider").is_some());
+    let uri = "file:///tmp/cconvention-test/.git/COMMIT_EDITMSG";
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {
+            "uri": uri,
+            "languageId": "git-commit",
+            "version": 1,
+            "text": "feat:add a thing\nno blank line before this body\n",
+        }}),
+    );
+    client.expect_notification("textDocument/publishDiagnostics");
+    let actions = client.request(
+        "textDocument/codeAction",
+        json!({
+            "textDocument": {"uri": uri},
+            "range": {"start": {"line": 0, "character": 0}, "end": {"line": 1, "character": 0}},
+            "context": {"diagnostics": []},
+        }),
+    );
+    assert_snapshot("code_actions", &actions);
+    let kinds: Vec<_> = actions
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|action| action["kind"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        kinds,
+        vec!["quickfix", "quickfix", "quickfix", "source.fixAll"]
+    );
+    let only_fix_all = client.request(
+        "textDocument/codeAction",
+        json!({
+            "textDocument": {"uri": uri},
+            "range": {"start": {"line": 0, "character": 0}, "end": {"line": 0, "character": 0}},
+            "context": {"diagnostics": [], "only": ["source.fixAll"]},
+        }),
+    );
+    assert_eq!(only_fix_all, json!([actions[3]]));
+
+    client.notify("exit", Value::Null);
+    server.join().unwrap();
+}
+
+#[test]
+fn test_status_notification() {
+    let (mut client, server, capabilities) = start(
+        json!({"experimental": {"cconvention/status": true}}),
+        Features::default(),
+    );
+    assert_eq!(capabilities["experimental"]["cconvention/status"], true);
+    let uri = "file:///tmp/cconvention-test/.git/COMMIT_EDITMSG";
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {
+            "uri": uri,
+            "languageId": "git-commit",
+            "version": 1,
+            "text": "feat:add a thing\nwith a wrapped subject\n# a comment\n",
+        }}),
+    );
+    let diagnostics = client.expect_notification("textDocument/publishDiagnostics");
+    let status = client.expect_notification("cconvention/status");
+    let count = |severity: u64| {
+        diagnostics["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|d| d["severity"] == severity)
+            .count()
+    };
+    assert_eq!(status["uri"], uri);
+    assert_eq!(status["errors"], count(1));
+    assert_eq!(status["warnings"], count(2));
+    assert_eq!(status["hints"], count(4));
+    assert!(count(2) > 0);
+
+    client.notify("exit", Value::Null);
+    server.join().unwrap();
+}
+
+#[test]
+fn test_every_capability_is_gated_or_always_on() {
+    // providers that don't belong to a toggleable feature. A new capability must either be listed
+    // here or be dropped by `advertised_capabilities` when its feature is disabled.
+    const ALWAYS_ON: &[&str] = &[
+        "textDocumentSync",
+        "selectionRangeProvider",
+        "codeActionProvider",
+        "renameProvider",
+        "documentHighlightProvider",
+        "codeLensProvider",
+        "foldingRangeProvider",
+        "inlayHintProvider",
+        "documentSymbolProvider",
+        "experimental",
+    ];
+    let none = Features {
+        completion: false,
+        hover: false,
+        semantic_tokens: false,
+        formatting: false,
+        document_links: false,
+    };
+    let all = serde_json::to_value(&*CAPABILITIES).unwrap();
+    let advertised = serde_json::to_value(advertised_capabilities(&CAPABILITIES, &none)).unwrap();
+    for key in all.as_object().unwrap().keys() {
+        let gated = advertised.get(key).is_none();
+        assert_ne!(
+            gated,
+            ALWAYS_ON.contains(&key.as_str()),
+            "{key} must be either gated by a feature or listed as always on"
+        );
+    }
+}
+
+#[test]
+fn test_disabled_features() {
+    let features = Features {
+        hover: false,
+        ..Default::default()
+    };
+    let (mut client, server, capabilities) = start(json!({}), features);
+    assert!(capabilities.get("hoverProvider").is_none());
+    assert!(capabilities.get("completionProvider").is_some());
+    let uri = "file:///tmp/cconvention-test/.git/COMMIT_EDITMSG";
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {
+            "uri": uri,
+            "languageId": "git-commit",
+            "version": 1,
+            "text": "feat: add a thing\n",
+        }}),
+    );
+    client.expect_notification("textDocument/publishDiagnostics");
+    let position = json!({"textDocument": {"uri": uri}, "position": {"line": 0, "character": 2}});
+    assert_eq!(client.request("textDocument/hover", position), Value::Null);
+
+    client.notify("exit", Value::Null);
+    server.join().unwrap();
+}
+
+#[test]
+fn test_co_author_completion() {
+    let (mut client, server, _) = start(json!({}), Features::default());
+    let uri = "file:///tmp/cconvention-test/.git/COMMIT_EDITMSG";
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {
+            "uri": uri,
+            "languageId": "git-commit",
+            "version": 1,
+            "text": "feat: add a thing\n\nCo-authored-by: \nCo-a\n",
+        }}),
+    );
+    client.expect_notification("textDocument/publishDiagnostics");
+    let completion = client.request(
+        "textDocument/completion",
+        json!({"textDocument": {"uri": uri}, "position": {"line": 2, "character": 16}}),
+    );
+    let items = completion["items"].as_array().unwrap();
+    let labels: Vec<_> = items.iter().map(|item| &item["label"]).collect();
+    assert_eq!(
+        labels,
+        vec![
+            "Ada Lovelace <ada@example.com>",
+            "Alan Turing <alan@example.com>"
+        ]
+    );
+    assert_eq!(
+        items[0]["textEdit"]["range"],
+        json!({"start": {"line": 2, "character": 16}, "end": {"line": 2, "character": 16}})
+    );
+
+    let completion = client.request(
+        "textDocument/completion",
+        json!({"textDocument": {"uri": uri}, "position": {"line": 3, "character": 4}}),
+    );
+    let labels: Vec<_> = completion["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| &item["label"])
+        .collect();
+    assert_eq!(labels, vec!["Co-authored-by:"]);
+
+    client.notify("exit", Value::Null);
+    server.join().unwrap();
+}
+
+#[test]
+fn test_scope_hover() {
+    let (mut client, server, _) = start(json!({}), Features::default());
+    let uri = "file:///tmp/cconvention-test/.git/COMMIT_EDITMSG";
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {
+            "uri": uri,
+            "languageId": "git-commit",
+            "version": 1,
+            "text": "feat(api): add a thing\n\nfix(cli): add another\n",
+        }}),
+    );
+    client.expect_notification("textDocument/publishDiagnostics");
+    let hover = |client: &mut Client, line: u32, character: u32| {
+        client.request(
+            "textDocument/hover",
+            json!({"textDocument": {"uri": uri}, "position": {"line": line, "character": character}}),
+        )
+    };
+    assert_eq!(
+        hover(&mut client, 0, 6)["contents"]["value"],
+        "the public API"
+    );
+    assert_eq!(hover(&mut client, 0, 12), Value::Null);
+
+    let other = "file:///tmp/cconvention-test/.git/MERGE_MSG";
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {
+            "uri": other,
+            "languageId": "git-commit",
+            "version": 1,
+            "text": "feat(cli): add a thing\n",
+        }}),
+    );
+    client.expect_notification("textDocument/publishDiagnostics");
+    let position = json!({"textDocument": {"uri": other}, "position": {"line": 0, "character": 6}});
+    assert_eq!(client.request("textDocument/hover", position), Value::Null);
+
+    let bracketed = "file:///tmp/cconvention-test/.git/SQUASH_MSG";
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {
+            "uri": bracketed,
+            "languageId": "git-commit",
+            "version": 1,
+            "text": "feat[api]: add a thing\n",
+        }}),
+    );
+    client.expect_notification("textDocument/publishDiagnostics");
+    let position =
+        json!({"textDocument": {"uri": bracketed}, "position": {"line": 0, "character": 6}});
+    assert_eq!(
+        client.request("textDocument/hover", position)["contents"]["value"],
+        "the public API"
+    );
+
+    client.notify("exit", Value::Null);
+    server.join().unwrap();
+}
+
+#[test]
+fn test_definition() {
+    let (mut client, server, _) = start(json!({}), Features::default());
+    let uri = "file:///tmp/cconvention-test/.git/COMMIT_EDITMSG";
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {
+            "uri": uri,
+            "languageId": "git-commit",
+            "version": 1,
+            "text": "feat(api): add a thing\n",
+        }}),
+    );
+    client.expect_notification("textDocument/publishDiagnostics");
+    let mut definition = |character: u32| {
+        client.request(
+            "textDocument/definition",
+            json!({"textDocument": {"uri": uri}, "position": {"line": 0, "character": character}}),
+        )
+    };
+    assert_eq!(definition(2), serde_json::to_value(declaration(1)).unwrap());
+    assert_eq!(definition(6), serde_json::to_value(declaration(4)).unwrap());
+    assert_eq!(definition(14), Value::Null);
+
+    client.notify("exit", Value::Null);
+    server.join().unwrap();
+}
+
+#[test]
+fn test_config_change() {
+    let (mut client, server, _) = start(json!({}), Features::default());
+    let uri = "file:///tmp/cconvention-test/.git/COMMIT_EDITMSG";
+    let subject = format!("feat: {}", "a".repeat(54));
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {
+            "uri": uri,
+            "languageId": "git-commit",
+            "version": 1,
+            "text": format!("{subject}\n"),
+        }}),
+    );
+    let too_long = |diagnostics: &Value| {
+        diagnostics["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|d| d["code"] == "header_max_line_length")
+    };
+    assert!(too_long(
+        &client.expect_notification("textDocument/publishDiagnostics")
+    ));
+
+    client.notify(
+        "workspace/didChangeConfiguration",
+        json!({"settings": {"cconvention": {"max_subject_length": 72}}}),
+    );
+    let diagnostics = client.expect_notification("textDocument/publishDiagnostics");
+    assert_eq!(diagnostics["uri"], uri);
+    assert!(!too_long(&diagnostics));
+
+    client.notify(
+        "workspace/didChangeConfiguration",
+        json!({"settings": {"cconvention": {"max_subject_length": 55}}}),
+    );
+    assert!(too_long(
+        &client.expect_notification("textDocument/publishDiagnostics")
+    ));
+
+    client.notify("exit", Value::Null);
+    server.join().unwrap();
+}
+
+#[test]
+fn test_configuration_request() {
+    let (mut client, server, _) = start(
+        json!({"workspace": {"configuration": true}}),
+        Features::default(),
+    );
+    let uri = "file:///tmp/cconvention-test/.git/COMMIT_EDITMSG";
+    let subject = format!("feat: {}", "a".repeat(54));
+    client.reply("workspace/configuration", |params| {
+        assert_eq!(params, json!({"items": [{"section": "cconvention"}]}));
+        json!([{"max_subject_length": 72}])
+    });
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {
+            "uri": uri,
+            "languageId": "git-commit",
+            "version": 1,
+            "text": format!("{subject}\n"),
+        }}),
+    );
+    let diagnostics = client.expect_notification("textDocument/publishDiagnostics");
+    assert!(
+        diagnostics["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|d| d["code"] != "header_max_line_length"),
+        "{diagnostics}"
+    );
+
+    // an empty change notification asks the server to pull the settings again
+    client.notify(
+        "workspace/didChangeConfiguration",
+        json!({"settings": null}),
+    );
+    client.reply("workspace/configuration", |_| json!([null]));
+    client.notify("exit", Value::Null);
+    server.join().unwrap();
+}
+
+#[test]
+fn test_gitmoji_completion() {
+    let (mut client, server, _) = start(json!({}), Features::default());
+    let uri = "file:///tmp/cconvention-test/.git/COMMIT_EDITMSG";
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {
+            "uri": uri,
+            "languageId": "git-commit",
+            "version": 1,
+            "text": "feat: :spa add a thing\n",
+        }}),
+    );
+    client.expect_notification("textDocument/publishDiagnostics");
+    let complete = |client: &mut Client, character: u32| {
+        client.request(
+            "textDocument/completion",
+            json!({"textDocument": {"uri": uri}, "position": {"line": 0, "character": character}}),
+        )
+    };
+    let completion = complete(&mut client, 10);
+    let sparkles = completion["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|item| item["label"] == ":sparkles:")
+        .unwrap();
+    assert_eq!(sparkles["kind"], 20); // CompletionItemKind::ENUM_MEMBER
+    assert_eq!(sparkles["detail"], "✨ Introduce new features.");
+    assert_eq!(
+        sparkles["textEdit"],
+        json!({
+            "range": {"start": {"line": 0, "character": 6}, "end": {"line": 0, "character": 10}},
+            "newText": ":sparkles:",
+        })
+    );
+    // past the first word of the message
+    assert_eq!(complete(&mut client, 14)["items"], json!([]));
+
+    client.notify("exit", Value::Null);
+    server.join().unwrap();
+}
+
+#[test]
+fn test_trailer_key_completion() {
+    let (mut client, server, _) = start(json!({}), Features::default());
+    let uri = "file:///tmp/cconvention-test/.git/COMMIT_EDITMSG";
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {
+            "uri": uri,
+            "languageId": "git-commit",
+            "version": 1,
+            "text": "feat: add a thing\n\nbody\n\nre\nüb\n",
+        }}),
+    );
+    client.expect_notification("textDocument/publishDiagnostics");
+    let mut complete = |line: u32, character: u32| {
+        let completion = client.request(
+            "textDocument/completion",
+            json!({"textDocument": {"uri": uri}, "position": {"line": line, "character": character}}),
+        );
+        completion["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| {
+                assert_eq!(item["kind"], 14); // CompletionItemKind::KEYWORD
+                assert!(item["detail"].is_string());
+                (
+                    item["label"].as_str().unwrap().to_owned(),
+                    item["textEdit"]["newText"].as_str().unwrap().to_owned(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    let all: Vec<_> = base::config::DEFAULT_TRAILERS
+        .iter()
+        .map(|(key, _)| (format!("{key}:"), format!("{key}: ")))
+        .collect();
+    assert_eq!(complete(3, 0), all);
+    assert_eq!(
+        complete(4, 2),
+        vec![
+            ("Reviewed-by:".to_owned(), "Reviewed-by: ".to_owned()),
+            ("Refs:".to_owned(), "Refs: ".to_owned()),
+        ]
+    );
+    // past the key
+    assert_eq!(complete(2, 4), vec![]);
+    // positions count chars, not bytes
+    assert_eq!(complete(5, 1), vec![]);
+
+    client.notify("exit", Value::Null);
+    server.join().unwrap();
+}
+
+#[test]
+fn test_tcp() {
+    // reserve an ephemeral port, then free it for the server to listen on
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let server = std::thread::spawn(move || {
+        Server::from_tcp(TestConfigStore::default(), port)
+            .unwrap()
+            .init(&CAPABILITIES)
+            .unwrap()
+            .serve()
+            .unwrap();
+    });
+    let connection = (0..100)
+        .find_map(|_| {
+            Connection::connect(("127.0.0.1", port))
+                .map(|(connection, _io)| connection)
+                .map_err(|_| std::thread::sleep(std::time::Duration::from_millis(10)))
+                .ok()
+        })
+        .expect("the server never started listening");
+    let mut client = Client {
+        connection,
+        next_id: 0,
+    };
+    let init = client.request(
+        "initialize",
+        json!({"processId": null, "rootUri": null, "capabilities": {}}),
+    );
+    assert_eq!(init["serverInfo"]["name"], "cconvention");
+    client.notify("initialized", json!({}));
+    client.notify("exit", Value::Null);
+    server.join().unwrap();
+}
+
+#[test]
+fn test_separator_completion() {
+    let (mut client, server, _) = start(json!({}), Features::default());
+    let mut complete = |text: &str, character: u32| {
+        let uri = format!("file:///tmp/cconvention-test/.git/{}", client.next_id);
+        client.notify(
+            "textDocument/didOpen",
+            json!({"textDocument": {
+                "uri": uri,
+                "languageId": "git-commit",
+                "version": 1,
+                "text": text,
+            }}),
+        );
+        client.expect_notification("textDocument/publishDiagnostics");
+        let completion = client.request(
+            "textDocument/completion",
+            json!({"textDocument": {"uri": uri}, "position": {"line": 0, "character": character}}),
+        );
+        completion["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| (item["label"].clone(), item["textEdit"].clone()))
+            .collect::<Vec<_>>()
+    };
+    let at = |character: u32, new_text: &str| {
+        json!({
+            "range": {
+                "start": {"line": 0, "character": character},
+                "end": {"line": 0, "character": character},
+            },
+            "newText": new_text,
+        })
+    };
+    // right after the scope
+    assert_eq!(
+        complete("feat(api)\n", 9),
+        vec![(json!("!"), at(9, "!")), (json!(":"), at(9, ": "))]
+    );
+    // after the bang, only the colon is left
+    assert_eq!(
+        complete("feat(api)!\n", 10),
+        vec![(json!(":"), at(10, ": "))]
+    );
+    // a breaking change can still be marked before an existing colon
+    assert_eq!(
+        complete("feat(api): add a thing\n", 9),
+        vec![(json!("!"), at(9, "!"))]
+    );
+
+    client.notify("exit", Value::Null);
+    server.join().unwrap();
+}
@@ -0,0 +1,66 @@
+// © Steven Kalt
+// SPDX-License-Identifier: Polyform-Noncommercial-1.0.0 OR LicenseRef-PolyForm-Free-Trial-1.0.0
+
+//! A sample `cdylib` lint plugin that forbids "WIP" in the commit subject.
+//! Build with `cargo build -p pro --features dylib_plugins --example dylib_plugin`, then
+//! reference the built shared object from your config:
+//!
+//! ```json
+//! {
+//!   "dylib_plugins": {
+//!     "no_wip": {
+//!       "severity": "error",
+//!       "path": "target/debug/examples/libdylib_plugin.so",
+//!       "description": "forbid work-in-progress commits"
+//!     }
+//!   }
+//! }
+//! ```
+use std::ffi::{c_char, CStr, CString};
+
+#[no_mangle]
+pub extern "C" fn cconvention_plugin_abi_version() -> u32 {
+    1
+}
+
+/// # Safety
+/// `document` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cconvention_lint(document: *const c_char) -> *mut c_char {
+    let result = std::panic::catch_unwind(|| {
+        let input = CStr::from_ptr(document).to_str().ok()?;
+        let input: serde_json::Value = serde_json::from_str(input).ok()?;
+        let text = input.get("text")?.as_str()?;
+        let mut diagnostics = vec![];
+        if let Some((line_number, line)) = text
+            .lines()
+            .enumerate()
+            .find(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        {
+            if let Some(start) = line.find("WIP") {
+                let start = line[..start].chars().count();
+                diagnostics.push(serde_json::json!({
+                    "range": {
+                        "start": {"line": line_number, "character": start},
+                        "end": {"line": line_number, "character": start + 3},
+                    },
+                    "message": "Work-in-progress commits aren't allowed.",
+                }));
+            }
+        }
+        CString::new(serde_json::to_string(&diagnostics).ok()?).ok()
+    });
+    match result {
+        Ok(Some(output)) => output.into_raw(),
+        _ => std::ptr::null_mut(),
+    }
+}
+
+/// # Safety
+/// `diagnostics` must have been returned by `cconvention_lint`.
+#[no_mangle]
+pub unsafe extern "C" fn cconvention_free(diagnostics: *mut c_char) {
+    if !diagnostics.is_null() {
+        drop(CString::from_raw(diagnostics));
+    }
+}
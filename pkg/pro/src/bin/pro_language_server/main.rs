@@ -12,16 +12,19 @@ use pro::config::Config;
 fn construct_capabilities() -> lsp_types::ServerCapabilities {
     let mut capabilities = base::server::CAPABILITIES.clone();
     let filter = Some(lsp_types::FileOperationRegistrationOptions {
-        filters: vec![lsp_types::FileOperationFilter {
-            scheme: Some("file".to_string()),
-            pattern: lsp_types::FileOperationPattern {
-                matches: Some(lsp_types::FileOperationPatternKind::File),
-                options: Some(lsp_types::FileOperationPatternOptions {
-                    ignore_case: Some(false),
-                }),
-                glob: CONFIG_FILE_GLOB.clone(), // ..Default::default()
-            },
-        }],
+        filters: [CONFIG_FILE_GLOB.clone(), "**/CODEOWNERS".to_string()]
+            .into_iter()
+            .map(|glob| lsp_types::FileOperationFilter {
+                scheme: Some("file".to_string()),
+                pattern: lsp_types::FileOperationPattern {
+                    matches: Some(lsp_types::FileOperationPatternKind::File),
+                    options: Some(lsp_types::FileOperationPatternOptions {
+                        ignore_case: Some(false),
+                    }),
+                    glob, // ..Default::default()
+                },
+            })
+            .collect(),
     });
     capabilities.workspace = Some(lsp_types::WorkspaceServerCapabilities {
         workspace_folders: None,
@@ -34,6 +37,8 @@ fn construct_capabilities() -> lsp_types::ServerCapabilities {
             will_delete: filter,
         }),
     });
+    // types and scopes declared in a config file can be jumped to
+    capabilities.definition_provider = Some(lsp_types::OneOf::Left(true));
     capabilities
 }
 
@@ -45,21 +50,42 @@ lazy_static! {
         #[cfg(feature = "toml_config")]
         exts.push("toml");
 
-        format!("**/commit_convention.{}", exts.join(","))
+        #[cfg(feature = "yaml_config")]
+        exts.extend(["yaml", "yml"]);
+
+        format!(
+            "**/{{{}}}.{{{}}}",
+            pro::config::CONFIG_FILE_NAMES.join(","),
+            exts.join(",")
+        )
     };
 }
 
 struct ConfigStore_ {
     dirs: HashMap<PathBuf, Arc<dyn base::config::Config>>,
+    authors: HashMap<PathBuf, Vec<String>>,
+    /// overrides from the client, applied to each loaded config
+    settings: base::config::ClientSettings,
 }
 impl ConfigStore_ {
     fn new() -> Self {
         Self {
             dirs: HashMap::new(),
+            authors: HashMap::new(),
+            settings: Default::default(),
         }
     }
+    fn load(
+        &self,
+        worktree_root: &std::path::Path,
+    ) -> Result<Config, Box<dyn std::error::Error + Send + Sync>> {
+        let mut cfg = Config::new(worktree_root)?;
+        cfg.apply_settings(&self.settings);
+        Ok(cfg)
+    }
 }
 
+#[allow(clippy::arc_with_non_send_sync)] // configs never leave the server's thread
 impl base::config::ConfigStore for ConfigStore_ {
     fn get(
         &mut self,
@@ -70,15 +96,29 @@ impl base::config::ConfigStore for ConfigStore_ {
         if let Some(cfg) = self.dirs.get(&worktree_root) {
             Ok(cfg.to_owned())
         } else {
-            let cfg = Arc::new(Config::new(&worktree_root)?);
+            let cfg = Arc::new(self.load(&worktree_root)?);
             self.dirs.insert(worktree_root, cfg.clone());
             Ok(cfg)
         }
     }
+    fn recent_authors(&mut self, worktree_root: Option<PathBuf>) -> Vec<String> {
+        let Some(worktree_root) = worktree_root.or_else(|| std::env::current_dir().ok()) else {
+            return vec![];
+        };
+        self.authors
+            .entry(worktree_root)
+            .or_insert_with_key(|root| base::git::recent_authors(Some(root.clone())))
+            .clone()
+    }
+    fn set_settings(&mut self, settings: base::config::ClientSettings) {
+        self.settings = settings;
+        self.dirs.clear(); // reloaded on next access
+    }
     fn set_dirty(&mut self, paths: Vec<PathBuf>) -> Vec<PathBuf> {
         let mut roots = Vec::with_capacity(paths.len());
         for path in paths {
             // since we're looking in ${root}/.config/ and ${root}, grab the parent and grandparent dirs
+            // (this also covers ${root}/CODEOWNERS and ${root}/.github/CODEOWNERS)
             // shouldn't panic even if the repo root is located in /
             let worktree_root = path.parent().and_then(|parent| {
                 let parent: PathBuf = parent.into();
@@ -101,7 +141,7 @@ impl base::config::ConfigStore for ConfigStore_ {
                     path,
                     worktree_root
                 );
-                if let Ok(cfg) = Config::new(&worktree_root) {
+                if let Ok(cfg) = self.load(&worktree_root) {
                     self.dirs.insert(worktree_root.clone(), Arc::new(cfg));
                 } else {
                     self.dirs.remove(&worktree_root); // handle error on next access
@@ -0,0 +1,119 @@
+// © Steven Kalt
+// SPDX-License-Identifier: Polyform-Noncommercial-1.0.0 OR LicenseRef-PolyForm-Free-Trial-1.0.0
+
+//! Derive candidate scopes from a repo's `CODEOWNERS` file.
+//! See https://docs.github.com/en/repositories/managing-your-repositorys-settings-and-features/customizing-your-repository/about-code-owners
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+/// where GitHub and GitLab look for a CODEOWNERS file, in order of precedence
+const LOCATIONS: &[&str] = &[
+    ".github/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+];
+
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ScopeSource {
+    /// name scopes after the last literal component of each path pattern, e.g. `/pkg/base/` -> `base`
+    #[default]
+    Path,
+    /// name scopes after the owning teams, e.g. `@org/frontend` -> `frontend`
+    Team,
+}
+
+/// the first CODEOWNERS file in the worktree, if any
+pub(crate) fn find(worktree_root: &Path) -> Option<PathBuf> {
+    LOCATIONS
+        .iter()
+        .map(|location| worktree_root.join(location))
+        .find(|path| path.is_file())
+}
+
+fn scope_from_path(pattern: &str) -> Option<String> {
+    pattern
+        .rsplit('/')
+        .filter(|component| !component.is_empty())
+        .find(|component| !component.contains(['*', '?', '[']))
+        .map(|component| component.to_owned())
+}
+
+fn scope_from_team(owner: &str) -> Option<String> {
+    // skip individual users (`@user`) and emails; only `@org/team` names a component
+    let (_org, team) = owner.strip_prefix('@')?.split_once('/')?;
+    Some(team.to_owned())
+}
+
+/// parse CODEOWNERS `text` into scopes, each described by its owners
+pub(crate) fn parse(text: &str, source: ScopeSource) -> IndexMap<String, String> {
+    owners(text, source)
+        .into_iter()
+        .map(|(scope, owners)| (scope, format!("owned by {owners}")))
+        .collect()
+}
+
+/// parse CODEOWNERS `text` into scopes and their space-separated owners
+pub(crate) fn owners(text: &str, source: ScopeSource) -> IndexMap<String, String> {
+    let mut scopes: IndexMap<String, String> = IndexMap::new();
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let mut words = line.split_whitespace();
+        let Some(pattern) = words.next() else {
+            continue;
+        };
+        if pattern.starts_with('[') {
+            continue; // a GitLab section header
+        }
+        let owners: Vec<&str> = words.collect();
+        let names: Vec<String> = match source {
+            ScopeSource::Path => scope_from_path(pattern).into_iter().collect(),
+            ScopeSource::Team => owners.iter().filter_map(|o| scope_from_team(o)).collect(),
+        };
+        for name in names {
+            scopes.entry(name).or_insert_with(|| owners.join(" "));
+        }
+    }
+    scopes
+}
+
+/// read the worktree's CODEOWNERS file into scopes, if it has one
+pub(crate) fn scopes(
+    worktree_root: &Path,
+    source: ScopeSource,
+) -> Result<IndexMap<String, String>, Box<dyn std::error::Error + Send + Sync>> {
+    match find(worktree_root) {
+        Some(path) => Ok(parse(&std::fs::read_to_string(path)?, source)),
+        None => Ok(IndexMap::new()),
+    }
+}
+
+/// read the worktree's CODEOWNERS file into scopes' owners, if it has one
+pub(crate) fn scope_owners(
+    worktree_root: &Path,
+    source: ScopeSource,
+) -> Result<IndexMap<String, String>, Box<dyn std::error::Error + Send + Sync>> {
+    match find(worktree_root) {
+        Some(path) => Ok(owners(&std::fs::read_to_string(path)?, source)),
+        None => Ok(IndexMap::new()),
+    }
+}
+
+#[test]
+fn test_parse_codeowners() {
+    let text = "# comment\n* @org/everyone\n/pkg/base/ @org/core @alice\ndocs/**/*.md @org/docs\n[Section]\n/ci/ @org/infra # trailing\n";
+    let by_path = parse(text, ScopeSource::Path);
+    assert_eq!(
+        by_path.keys().collect::<Vec<_>>(),
+        vec!["base", "docs", "ci"]
+    );
+    assert_eq!(by_path["base"], "owned by @org/core @alice");
+    let by_team = parse(text, ScopeSource::Team);
+    assert_eq!(
+        by_team.keys().collect::<Vec<_>>(),
+        vec!["everyone", "core", "docs", "infra"]
+    );
+}
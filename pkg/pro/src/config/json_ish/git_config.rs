@@ -0,0 +1,107 @@
+// © Steven Kalt
+// SPDX-License-Identifier: Polyform-Noncommercial-1.0.0 OR LicenseRef-PolyForm-Free-Trial-1.0.0
+
+//! Read simple settings from `git config` under the `cconvention.*` namespace, e.g.
+//! ```sh
+//! git config cconvention.maxSubjectLength 72
+//! git config --add cconvention.type "feat a new feature"
+//! ```
+use std::path::Path;
+
+use base::log_debug;
+
+use super::{BuiltinLengthRule, JsonConfig};
+
+fn length_rule(key: &str, value: &str) -> Result<BuiltinLengthRule, String> {
+    let max_length = value
+        .parse()
+        .map_err(|e| format!("invalid cconvention.{key} {value:?}: {e}"))?;
+    Ok(BuiltinLengthRule {
+        severity: None,
+        max_length: Some(max_length),
+    })
+}
+
+/// split a multi-valued `name description...` setting
+fn name_and_description(value: &str) -> (String, String) {
+    let (name, description) = value.split_once(char::is_whitespace).unwrap_or((value, ""));
+    (name.to_string(), description.trim().to_string())
+}
+
+/// parse the output of `git config --get-regexp`, i.e. lines of `key value`
+fn parse(output: &str) -> Result<JsonConfig, Box<dyn std::error::Error + Send + Sync>> {
+    let mut config = JsonConfig::default();
+    for line in output.lines() {
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        // git lowercases the section and key names
+        let key = key.strip_prefix("cconvention.").unwrap_or(key);
+        match key {
+            "maxsubjectlength" => {
+                config.header_line_max_length = Some(length_rule("maxSubjectLength", value)?)
+            }
+            "maxbodylinelength" => {
+                config.body_line_max_length = Some(length_rule("maxBodyLineLength", value)?)
+            }
+            "maxtotallength" => {
+                config.total_max_length = Some(length_rule("maxTotalLength", value)?)
+            }
+            "type" => {
+                let (name, description) = name_and_description(value);
+                config
+                    .types
+                    .get_or_insert_with(Default::default)
+                    .insert(name, description);
+            }
+            "scope" => {
+                let (name, description) = name_and_description(value);
+                config
+                    .scopes
+                    .get_or_insert_with(Default::default)
+                    .insert(name, description);
+            }
+            _ => {
+                log_debug!("ignoring unknown git config key cconvention.{}", key);
+            }
+        }
+    }
+    Ok(config)
+}
+
+/// read `cconvention.*` settings visible from `repo_root`, if any are set
+pub(crate) fn from_git_config(
+    repo_root: &Path,
+) -> Result<Option<JsonConfig>, Box<dyn std::error::Error + Send + Sync>> {
+    match base::git::git(
+        &["config", "--get-regexp", r"^cconvention\."],
+        Some(repo_root.to_path_buf()),
+    ) {
+        Ok(output) => parse(&output).map(Some),
+        // git exits 1 without any message when no keys match
+        Err(e) if e.to_string().trim().is_empty() => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[test]
+fn test_from_git_config() {
+    let dir = base::git::TempRepo::new("git-config");
+    dir.git(&["config", "cconvention.maxSubjectLength", "72"]);
+    dir.git(&["config", "--add", "cconvention.type", "feat a new feature"]);
+    dir.git(&["config", "--add", "cconvention.type", "fix"]);
+    let config = from_git_config(&dir).unwrap().unwrap();
+
+    assert_eq!(config.header_line_max_length.unwrap().max_length, Some(72));
+    let types = config.types.unwrap();
+    assert_eq!(types.get("feat").unwrap(), "a new feature");
+    assert_eq!(types.get("fix").unwrap(), "");
+
+    let mut file = JsonConfig {
+        body_line_max_length: Some(length_rule("maxBodyLineLength", "80").unwrap()),
+        ..Default::default()
+    };
+    file.fill_from(
+        parse("cconvention.maxbodylinelength 60\ncconvention.maxtotallength 1000").unwrap(),
+    );
+    assert_eq!(file.body_line_max_length.unwrap().max_length, Some(80));
+    assert_eq!(file.total_max_length.unwrap().max_length, Some(1000));
+}
@@ -3,13 +3,16 @@
 use super::Severity;
 use base::log_debug;
 use indexmap::IndexMap;
+use regex::Regex;
 use serde::Deserialize;
 use std::{
+    collections::HashMap,
     fs,
     path::{Path, PathBuf},
 };
 #[cfg(feature = "toml_config")]
 use toml;
+pub(crate) mod git_config;
 
 fn get_config_dir(
     repo_root: &Path,
@@ -25,11 +28,15 @@ fn get_config_dir(
     Ok(Some(config_dir))
 }
 
+/// recognized config file names, without extensions, in order of precedence
+pub const CONFIG_FILE_NAMES: &[&str] = &["commit_convention", "cconvention"];
+
 fn get_file(
     config_dir: &Path,
+    name: &str,
     ext: &str,
 ) -> Result<Option<PathBuf>, Box<dyn std::error::Error + Sync + Send>> {
-    let config_file = config_dir.join(format!("commit_convention.{ext}"));
+    let config_file = config_dir.join(format!("{name}.{ext}"));
     if !config_file.exists() {
         log_debug!("{:?} does not exist.", config_file);
         return Ok(None);
@@ -49,12 +56,251 @@ pub(crate) struct Rule {
     pub _description: String, // <- not used except to enforce documentation of rules
     pub message: String,
 }
+/// a lint-fn loaded from a shared object; see `crate::lints::dylib`
+#[cfg_attr(not(feature = "dylib_plugins"), allow(dead_code))]
+#[derive(Deserialize, Clone, Debug)]
+pub(crate) struct DylibRule {
+    pub severity: Severity,
+    /// the path to the shared object, relative to the repo root
+    pub path: PathBuf,
+    #[serde(alias = "description")]
+    pub _description: String, // <- not used except to enforce documentation of rules
+}
+/// check referenced issues exist via a forge's API; see `crate::lints::issues`
+#[cfg_attr(not(feature = "forge_api"), allow(dead_code))]
+#[derive(Deserialize, Clone, Debug)]
+pub(crate) struct ReferenceExistsRule {
+    pub severity: Severity,
+    /// `github` or `gitlab`
+    pub forge: String,
+    /// `owner/name` on GitHub, or the URL-encoded project path on GitLab
+    pub repo: String,
+    /// defaults to the forge's public API
+    pub api_url: Option<String>,
+    /// the environment variable containing an API token, if any
+    pub token_env: Option<String>,
+    /// how long to cache an issue's state on disk; defaults to an hour
+    pub cache_ttl_secs: Option<u64>,
+    /// footer keys referencing issues; defaults to closing keywords
+    pub keys: Option<Vec<String>>,
+}
 #[derive(Deserialize, Debug, Clone)]
 pub(crate) struct BuiltinLengthRule {
     pub severity: Option<Severity>,
     pub max_length: Option<u16>,
 }
 #[derive(Deserialize, Debug, Clone)]
+pub(crate) struct ComponentLengthRule {
+    pub severity: Option<Severity>,
+    pub min_length: Option<u16>,
+    pub max_length: Option<u16>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct SubjectLengthTiersRule {
+    /// warn past this many chars; defaults to 50
+    pub soft_limit: Option<u16>,
+    /// hint past this many chars, where UIs truncate the subject; defaults to 72
+    pub hard_limit: Option<u16>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct ScopeMaxWordsRule {
+    pub severity: Option<Severity>,
+    pub max_words: Option<u16>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct ClosingKeywordsRule {
+    pub severity: Severity,
+    /// glob patterns of branches on which closing keywords are forbidden, e.g. `release/*`
+    pub branches: Vec<String>,
+    /// defaults to GitHub's closing keywords
+    pub keywords: Option<Vec<String>>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct LockfileOnlyRule {
+    pub severity: Severity,
+    /// globs of lockfiles, matched against paths and file names; defaults to common lockfiles
+    pub patterns: Option<Vec<String>>,
+    /// the type that lockfile-only commits must use, e.g. `build` or `chore`
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// the scope that lockfile-only commits must use, e.g. `deps`
+    pub scope: Option<String>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct LargeDiffBodyRule {
+    pub severity: Severity,
+    /// how many lines may be added or deleted without a body; defaults to 200
+    pub max_lines: Option<usize>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct SubjectFirstWordRule {
+    pub severity: Severity,
+    pub max_length: Option<usize>,
+    pub min_length: Option<usize>,
+    /// `lower`, `upper`, or `capitalized`
+    pub case: Option<crate::lints::WordCase>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct SubjectFullStopRule {
+    pub severity: Severity,
+    /// which trailing punctuation to forbid, e.g. `".?!"`; defaults to `"."`
+    pub characters: Option<String>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct CaseRule {
+    pub severity: Severity,
+    /// `lower`, `kebab`, `camel`, `pascal`, or `sentence`
+    pub case: Case,
+}
+/// language-server features to disable; all are enabled by default
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FeatureToggles {
+    pub completion: Option<bool>,
+    pub hover: Option<bool>,
+    pub semantic_tokens: Option<bool>,
+    pub formatting: Option<bool>,
+    pub document_links: Option<bool>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct TrailerValuesRule {
+    pub severity: Severity,
+    /// trailer key => a regex its value must match; extends or overrides the defaults for
+    /// `Signed-off-by`, `Co-authored-by`, `Refs`, `Closes`, and `Fixes`
+    pub patterns: Option<IndexMap<String, String>>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct TrailerExistsRule {
+    pub severity: Severity,
+    /// trailer keys every message must include, e.g. `Signed-off-by`; compared case-insensitively
+    pub keys: Vec<String>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct ReferencesRule {
+    pub severity: Severity,
+    /// a regex matching an issue reference, e.g. `[A-Z]+-\d+` for Jira; defaults to `#\d+`
+    pub pattern: Option<String>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct AuthorDomainRule {
+    pub severity: Severity,
+    /// email domains authors may use, e.g. `example.com`
+    pub domains: Vec<String>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct SubjectDuplicateRecentRule {
+    /// defaults to `hint`
+    pub severity: Option<Severity>,
+    /// how many recent commits to compare against; defaults to 20
+    pub count: Option<usize>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct BodyStyleRule {
+    /// defaults to `hint`
+    pub severity: Option<Severity>,
+    /// words that shouldn't start a paragraph; defaults to `I`, `I'm`, `I've`, `I'd`, and `I'll`
+    pub first_person: Option<Vec<String>>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct SubjectConjunctionRule {
+    /// defaults to `hint`
+    pub severity: Option<Severity>,
+    /// defaults to `and`, `&`, and `,`
+    pub words: Option<Vec<String>>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct ScopeDirectoryRule {
+    /// defaults to `hint`
+    pub severity: Option<Severity>,
+    /// how many leading directories identify a component, e.g. 2 for `apps/web/`; defaults to 1
+    pub depth: Option<usize>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct CodeownersRule {
+    /// the severity of using a scope not derived from CODEOWNERS; `none` only suggests them
+    pub severity: Severity,
+    pub source: Option<super::codeowners::ScopeSource>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct GerritRule {
+    /// the severity of a malformed or missing `Change-Id:` trailer
+    pub severity: Severity,
+    /// whether every message needs a `Change-Id:` trailer
+    pub require_change_id: Option<bool>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct GitmojiRule {
+    /// the severity of a subject that doesn't start with a gitmoji; omit to only offer completions
+    pub severity: Option<Severity>,
+}
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SemverBump {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+impl From<SemverBump> for base::config::SemverBump {
+    fn from(bump: SemverBump) -> Self {
+        match bump {
+            SemverBump::None => Self::None,
+            SemverBump::Patch => Self::Patch,
+            SemverBump::Minor => Self::Minor,
+            SemverBump::Major => Self::Major,
+        }
+    }
+}
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Case {
+    Lower,
+    Kebab,
+    Camel,
+    Pascal,
+    Sentence,
+}
+impl From<Case> for base::document::linting::default::Case {
+    fn from(case: Case) -> Self {
+        match case {
+            Case::Lower => Self::Lower,
+            Case::Kebab => Self::Kebab,
+            Case::Camel => Self::Camel,
+            Case::Pascal => Self::Pascal,
+            Case::Sentence => Self::Sentence,
+        }
+    }
+}
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum LineEnding {
+    Auto,
+    Lf,
+    Crlf,
+}
+impl From<LineEnding> for base::document::LineEnding {
+    fn from(ending: LineEnding) -> Self {
+        match ending {
+            LineEnding::Auto => Self::Auto,
+            LineEnding::Lf => Self::Lf,
+            LineEnding::Crlf => Self::Crlf,
+        }
+    }
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct TrailerFormat {
+    /// rewrite trailer keys to their canonical casing; defaults to true
+    pub normalize_keys: Option<bool>,
+    /// canonical spellings of trailer keys, in addition to the built-in ones
+    pub keys: Option<Vec<String>>,
+    /// sort the trailer block by key in this order; unlisted keys go last
+    pub order: Option<Vec<String>>,
+}
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct OptionalSeverityRule {
+    pub severity: Option<Severity>,
+}
+#[derive(Deserialize, Debug, Clone)]
 pub(crate) struct BuiltinRule {
     pub(crate) severity: Severity,
 }
@@ -63,27 +309,326 @@ pub(crate) struct BuiltinRule {
 pub(crate) struct JsonConfig {
     pub scopes: Option<IndexMap<String, String>>,
     pub types: Option<IndexMap<String, String>>,
+    /// alias => canonical scope, e.g. `{"fe": "ui"}`; aliases pass `scope_enum` and are formatted
+    /// to their canonical scope
+    pub scope_aliases: Option<IndexMap<String, String>>,
+    /// canonical scope vocabulary; unlike `scopes`, scopes outside the glossary are only advised against
+    pub scope_glossary: Option<Vec<String>>,
+    /// types to mark as deprecated when highlighting, e.g. `["feature"]`
+    pub deprecated_types: Option<Vec<String>>,
+    /// the release each type calls for, e.g. `{"refactor": "patch"}`, shown as a lens above the
+    /// subject; unlisted types fall back to `feat`: minor, `fix` and `perf`: patch
+    pub semver: Option<IndexMap<String, SemverBump>>,
+    /// the person or team owning each scope, shown on hover; CODEOWNERS fills in the rest
+    pub scope_owners: Option<IndexMap<String, String>>,
+    /// derive scopes from the repo's CODEOWNERS file
+    pub codeowners: Option<CodeownersRule>,
+    /// lint `#`-prefixed lines as content, for messages not written via `git commit`
+    pub comments_are_content: Option<bool>,
+    /// how long to let tree-sitter parse a message before giving up; defaults to half a second
+    pub parse_timeout_micros: Option<u64>,
+    /// toggle language-server features, e.g. `{"semanticTokens": false}`
+    pub features: Option<FeatureToggles>,
+    /// report every lint as at most information, so editors underline gently instead of in red
+    pub suggestions_only: Option<bool>,
+    /// named configs a message can select with a `# cconvention-profile: <name>` comment, falling
+    /// back to `default`; each replaces, rather than extends, the top-level settings
+    pub profiles: Option<IndexMap<String, JsonConfig>>,
+    /// the newline inserted by formatting and fixes: `lf`, `crlf`, or `auto` (the default)
+    pub line_ending: Option<LineEnding>,
+    /// opt-in formatting of the trailer block
+    pub trailer_format: Option<TrailerFormat>,
+    /// re-wrap body paragraphs with lines longer than this many columns when formatting, e.g. 72;
+    /// 0, the default, disables wrapping
+    pub body_wrap: Option<u16>,
 
     pub header_line_max_length: Option<BuiltinLengthRule>,
     pub body_line_max_length: Option<BuiltinLengthRule>,
+    pub total_max_length: Option<BuiltinLengthRule>,
+    /// warn past a soft limit and hint past a hard one; replaces `header_line_max_length` unless
+    /// that's set
+    pub subject_length_tiers: Option<SubjectLengthTiersRule>,
+    /// bounds on the type's length, in chars
+    pub type_length: Option<ComponentLengthRule>,
+    /// bounds on the scope's length, in chars, not counting its parentheses
+    pub scope_length: Option<ComponentLengthRule>,
+    /// bounds on the length of the message after the `: `, in chars
+    pub subject_length: Option<ComponentLengthRule>,
     // pub body_max_length: Option<BuiltinLengthRule>,
     pub signed_off_by: Option<BuiltinRule>,
     pub body_leading_blank: Option<BuiltinRule>,
     pub footer_leading_blank: Option<BuiltinRule>,
+    pub comment_leading_blank: Option<BuiltinRule>,
+    /// flag trailers that directly follow the subject with no body in between; off by default
+    pub trailer_without_body: Option<BuiltinRule>,
+    /// the severity of a missing scope; unset, scopes are optional
     pub missing_scope: Option<BuiltinRule>,
+    /// `true` requires a scope (as an error unless `missing_scope` says otherwise); `false`
+    /// makes it optional even if `missing_scope` is set. Either way, a scope that is present
+    /// must be one of `scopes`, if any are configured.
+    pub scope_required: Option<bool>,
+    /// `true` requires a scope on breaking changes, marked by `!` or a `BREAKING CHANGE` footer
+    pub scope_required_when_breaking: Option<bool>,
     pub missing_body: Option<BuiltinRule>,
+    pub closing_keywords: Option<ClosingKeywordsRule>,
+    pub lockfile_only: Option<LockfileOnlyRule>,
+    pub large_diff_body: Option<LargeDiffBodyRule>,
+    pub subject_first_word: Option<SubjectFirstWordRule>,
+    /// validate trailers' values per key, e.g. `Signed-off-by: Name <email>`
+    pub trailer_values: Option<TrailerValuesRule>,
+    /// require trailers with the given keys, e.g. `Reviewed-by`
+    pub trailer_exists: Option<TrailerExistsRule>,
+    /// require an issue reference in the body or trailers
+    pub references: Option<ReferencesRule>,
+    /// require the author's email, or `user.email` for an unwritten commit, to use one of the
+    /// given domains
+    pub author_domain: Option<AuthorDomainRule>,
+    /// flag a subject identical to one of the last few commits'
+    pub subject_duplicate_recent: Option<SubjectDuplicateRecentRule>,
+    /// flag git's default `Revert "..."` subjects in favor of the `revert` type; defaults to `warning`
+    pub revert_type: Option<OptionalSeverityRule>,
+    /// flag a type and scope that look swapped, e.g. `api(feat): x`; defaults to `hint`
+    pub type_scope_swap: Option<OptionalSeverityRule>,
+    /// flag bodies written in the first person or that only repeat the subject
+    pub body_style: Option<BodyStyleRule>,
+    /// flag subjects joining several changes, e.g. `add x and fix y`
+    pub subject_conjunction: Option<SubjectConjunctionRule>,
+    /// expect the scope to match the changed files' directory
+    pub scope_directory: Option<ScopeDirectoryRule>,
+    /// rank scope suggestions by the scopes of past commits touching the staged files; defaults
+    /// to true, but scanning `git log` can be slow in large repos
+    pub scope_suggest_from_history: Option<bool>,
+    /// how many past commits to scan for scopes; defaults to 1000
+    pub scope_history_limit: Option<usize>,
+    /// how long to reuse computed scope suggestions while typing, in milliseconds; defaults to 5000
+    pub scope_suggestion_ttl_ms: Option<u64>,
+    pub gerrit: Option<GerritRule>,
+    /// offer https://gitmoji.dev completions at the start of the subject's message
+    pub gitmoji: Option<GitmojiRule>,
+    /// requires the `forge_api` feature
+    pub reference_exists: Option<ReferenceExistsRule>,
     pub subject_empty: Option<BuiltinRule>,
     pub missing_subject_leading_space: Option<BuiltinRule>,
+    pub subject_wrapped: Option<BuiltinRule>,
+    pub breaking_change_case: Option<BuiltinRule>,
+    pub trailer_separator: Option<BuiltinRule>,
+    pub scope_max_words: Option<ScopeMaxWordsRule>,
+    /// flag legacy `type[scope]: ` subjects, and rewrite them to `type(scope): ` when formatting;
+    /// off by default
+    pub bracket_scope: Option<BuiltinRule>,
+    /// forbid trailing punctuation on the subject, and strip it when formatting
+    pub subject_full_stop: Option<SubjectFullStopRule>,
+    /// flag links in the subject, offering to move them to a `Refs:` trailer; off by default
+    pub subject_url: Option<BuiltinRule>,
+    pub type_case: Option<CaseRule>,
+    pub scope_case: Option<CaseRule>,
+    pub subject_case: Option<CaseRule>,
+    /// requires the `dylib_plugins` feature
+    pub dylib_plugins: Option<IndexMap<String, DylibRule>>,
     #[serde(flatten)]
     pub plugins: IndexMap<String, Rule>,
 }
 
+impl JsonConfig {
+    /// fill any settings missing from `self` with those from a lower-priority `fallback`
+    pub(crate) fn fill_from(&mut self, fallback: JsonConfig) {
+        self.scopes = self.scopes.take().or(fallback.scopes);
+        self.types = self.types.take().or(fallback.types);
+        self.header_line_max_length = self
+            .header_line_max_length
+            .take()
+            .or(fallback.header_line_max_length);
+        self.body_line_max_length = self
+            .body_line_max_length
+            .take()
+            .or(fallback.body_line_max_length);
+        self.total_max_length = self.total_max_length.take().or(fallback.total_max_length);
+    }
+}
+
+/// Map a zero-based `(row, column)` within the query of the rule `code` to its position in the
+/// config file's `text`, accounting for escapes within the quoted query string.
+pub(crate) fn locate_query(
+    text: &str,
+    code: &str,
+    row: usize,
+    column: usize,
+) -> Option<lsp_types::Position> {
+    let rule_start = [format!("\"{code}\""), format!("[{code}]")]
+        .iter()
+        .filter_map(|key| text.find(key.as_str()))
+        .min()?;
+    let query_key = rule_start + text[rule_start..].find("query")?;
+    let separator = query_key + text[query_key..].find([':', '='])?;
+    let quote = separator + text[separator..].find(['"', '\''])?;
+    let literal = text[quote..].starts_with('\'');
+    let delimiter = ["\"\"\"", "'''", "\"", "'"]
+        .into_iter()
+        .find(|delimiter| text[quote..].starts_with(delimiter))?;
+    let mut offset = quote + delimiter.len();
+    if delimiter.len() == 3 {
+        // TOML trims a newline immediately following the opening delimiter
+        offset += ["\r\n", "\n"]
+            .iter()
+            .find(|newline| text[offset..].starts_with(*newline))
+            .map(|newline| newline.len())
+            .unwrap_or(0);
+    }
+    let (mut query_row, mut query_column) = (0, 0);
+    let mut chars = text[offset..].char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if (query_row, query_column) >= (row, column) || text[offset + i..].starts_with(delimiter) {
+            offset += i;
+            break;
+        }
+        match c {
+            '\\' if !literal => match chars.next() {
+                Some((_, 'n')) => (query_row, query_column) = (query_row + 1, 0),
+                Some((_, 'u')) => {
+                    (0..4).for_each(|_| {
+                        chars.next();
+                    });
+                    query_column += 1;
+                }
+                _ => query_column += 1,
+            },
+            '\n' => (query_row, query_column) = (query_row + 1, 0),
+            c => query_column += c.len_utf8(),
+        }
+    }
+    let line_start = text[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    Some(lsp_types::Position {
+        line: text[..offset].matches('\n').count() as u32,
+        character: text[line_start..offset].chars().count() as u32,
+    })
+}
+
+#[test]
+fn test_locate_query() {
+    let text = "{\n  \"no_wip\": {\n    \"query\": \"(subject) @s\\n((subject) @x\",\n  }\n}";
+    assert_eq!(
+        locate_query(text, "no_wip", 1, 1),
+        Some(lsp_types::Position {
+            line: 2,
+            character: 29
+        })
+    );
+    let text = "[no_wip]\nquery = '''\n(subject) @s\n  (oops\n'''";
+    assert_eq!(
+        locate_query(text, "no_wip", 1, 2),
+        Some(lsp_types::Position {
+            line: 3,
+            character: 2
+        })
+    );
+    assert_eq!(locate_query(text, "missing", 0, 0), None);
+}
+
+/// Find where each of `keys` is declared within the `section` table of the config file's `text`,
+/// whether it's JSON, TOML, or YAML. Keys that can't be found are left out.
+pub(crate) fn locate_keys<'k>(
+    text: &str,
+    section: &str,
+    keys: impl IntoIterator<Item = &'k String>,
+) -> HashMap<String, lsp_types::Range> {
+    let mut found = HashMap::new();
+    let section = regex::escape(section);
+    let header = Regex::new(&format!(
+        r#"(?m)(?:"{section}"\s*:|^\s*\[{section}\]|^\s*{section}\s*[:=])"#
+    ))
+    .unwrap();
+    let Some(start) = header.find(text).map(|m| m.end()) else {
+        return found;
+    };
+    for key in keys {
+        let pattern = format!(
+            r#"(?m)(?:^|[{{,])\s*["']?({})["']?\s*[:=]"#,
+            regex::escape(key)
+        );
+        let Some(m) = Regex::new(&pattern)
+            .ok()
+            .and_then(|re| re.captures(&text[start..]))
+            .and_then(|captures| captures.get(1))
+        else {
+            continue;
+        };
+        let offset = start + m.start();
+        let line_start = text[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let start = lsp_types::Position {
+            line: text[..offset].matches('\n').count() as u32,
+            character: text[line_start..offset].chars().count() as u32,
+        };
+        let mut end = start;
+        end.character += key.chars().count() as u32;
+        found.insert(key.clone(), lsp_types::Range { start, end });
+    }
+    found
+}
+
+#[test]
+fn test_locate_keys() {
+    let keys = ["feat".to_string(), "fix".to_string(), "nope".to_string()];
+    let at = |line, character| lsp_types::Position { line, character };
+    let json = "{\n  \"scopes\": {\"feat\": \"x\"},\n  \"types\": {\n    \"feat\": \"a\",\n    \"fix\": \"b\"\n  }\n}";
+    let found = locate_keys(json, "types", &keys);
+    assert_eq!(found["feat"].start, at(3, 5));
+    assert_eq!(found["fix"].start, at(4, 5));
+    assert_eq!(found["fix"].end, at(4, 8));
+    assert!(!found.contains_key("nope"));
+    let toml = "[types]\nfeat = \"a\"\nfix = \"b\"\n";
+    assert_eq!(locate_keys(toml, "types", &keys)["fix"].start, at(2, 0));
+    let yaml = "types:\n  feat: a\n  fix: b\n";
+    assert_eq!(locate_keys(yaml, "types", &keys)["fix"].start, at(2, 2));
+}
+
+/// a config file that failed to deserialize at the 1-indexed `line` and `column`
+fn parse_error(
+    config_file: &Path,
+    line: usize,
+    column: usize,
+    message: impl std::fmt::Display,
+) -> Box<dyn std::error::Error + Sync + Send> {
+    let start = lsp_types::Position {
+        line: line.saturating_sub(1) as u32,
+        character: column.saturating_sub(1) as u32,
+    };
+    let mut end = start;
+    end.character += 1;
+    Box::new(base::config::ConfigFileError {
+        path: config_file.to_path_buf(),
+        range: lsp_types::Range { start, end },
+        message: format!("invalid config, using the defaults instead: {message}"),
+    })
+}
+
 #[cfg(feature = "toml_config")]
 fn from_toml(
     config_file: PathBuf,
 ) -> Result<(JsonConfig, PathBuf), Box<dyn std::error::Error + Sync + Send>> {
     let config_string = fs::read_to_string(&config_file)?;
-    let config = toml::from_str(&config_string)?;
+    let config = toml::from_str(&config_string).map_err(|e| {
+        // toml reports a byte range rather than a line and column
+        let offset = e.span().map(|span| span.start).unwrap_or(0);
+        let before = &config_string[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+        parse_error(&config_file, line, column, e.message())
+    })?;
+    Ok((config, config_file))
+}
+
+#[cfg(feature = "yaml_config")]
+fn from_yaml(
+    config_file: PathBuf,
+) -> Result<(JsonConfig, PathBuf), Box<dyn std::error::Error + Sync + Send>> {
+    let config_string = fs::read_to_string(&config_file)?;
+    let config = serde_yaml::from_str(&config_string).map_err(|e| {
+        let (line, column) = e
+            .location()
+            .map(|l| (l.line(), l.column()))
+            .unwrap_or((1, 1));
+        parse_error(&config_file, line, column, e)
+    })?;
     Ok((config, config_file))
 }
 
@@ -91,30 +636,90 @@ fn from_json(
     config_file: PathBuf,
 ) -> Result<(JsonConfig, PathBuf), Box<dyn std::error::Error + Sync + Send>> {
     let config_string = fs::read_to_string(&config_file)?;
-    let config = serde_json::from_str(&config_string)?;
+    let config = serde_json::from_str(&config_string)
+        .map_err(|e| parse_error(&config_file, e.line(), e.column(), e))?;
     Ok((config, config_file))
 }
 
+/// Find and parse the config in `.config/`, then the repo root. Within a directory, names take
+/// precedence in `CONFIG_FILE_NAMES` order; for each name, `.toml` beats `.yaml`, then `.yml`,
+/// then `.json`.
 pub(crate) fn get_config(
     repo_root: &Path,
 ) -> Result<Option<(JsonConfig, PathBuf)>, Box<dyn std::error::Error + Sync + Send>> {
-    if let Some(config_dir) = get_config_dir(repo_root)? {
-        #[cfg(feature = "toml_config")]
-        if let Some(config_file) = get_file(&config_dir, "toml")? {
-            return from_toml(config_file).map(Some);
-        }
-        // TODO: support yaml
-        if let Some(config_file) = get_file(&config_dir, "json")? {
-            return from_json(config_file).map(Some);
+    let dirs = get_config_dir(repo_root)?
+        .into_iter()
+        .chain(std::iter::once(repo_root.to_path_buf()));
+    for dir in dirs {
+        for name in CONFIG_FILE_NAMES {
+            #[cfg(feature = "toml_config")]
+            if let Some(config_file) = get_file(&dir, name, "toml")? {
+                return from_toml(config_file).map(Some);
+            }
+            #[cfg(feature = "yaml_config")]
+            for ext in ["yaml", "yml"] {
+                if let Some(config_file) = get_file(&dir, name, ext)? {
+                    return from_yaml(config_file).map(Some);
+                }
+            }
+            if let Some(config_file) = get_file(&dir, name, "json")? {
+                return from_json(config_file).map(Some);
+            }
         }
     }
-    #[cfg(feature = "toml_config")]
-    if let Some(config_file) = get_file(repo_root, "toml")? {
-        return from_toml(config_file).map(Some);
+    Ok(None)
+}
+
+#[test]
+fn test_config_file_names() {
+    let dir = base::git::TempRepo::new("names");
+    let found = |dir: &Path| get_config(dir).unwrap().map(|(_, file)| file);
+    let tool_named = dir.write(".config/cconvention.json", "{}");
+    let only_tool_named = found(&dir);
+    let original = dir.write(".config/commit_convention.json", "{}");
+    let both = found(&dir);
+
+    assert_eq!(only_tool_named, Some(tool_named));
+    assert_eq!(both, Some(original));
+}
+
+#[test]
+#[cfg(all(feature = "toml_config", feature = "yaml_config"))]
+fn test_config_formats() {
+    let dir = base::git::TempRepo::new("formats");
+    let files = [
+        (
+            "commit_convention.toml",
+            "scopes = { api = \"the API\" }\n[missing_body]\nseverity = \"warning\"\n",
+        ),
+        (
+            "commit_convention.yaml",
+            "scopes:\n  api: the API\nmissing_body:\n  severity: warning\n",
+        ),
+        (
+            "commit_convention.json",
+            r#"{"scopes": {"api": "the API"}, "missing_body": {"severity": "warning"}}"#,
+        ),
+    ];
+    for (name, text) in files {
+        dir.write(name, text);
     }
-    // TODO: support yaml
-    if let Some(config_file) = get_file(repo_root, "json")? {
-        return from_json(config_file).map(Some);
+    // remove each file in order of precedence, loading whichever is left
+    let mut loaded = vec![];
+    for (name, _) in files {
+        let (config, file) = get_config(&dir).unwrap().unwrap();
+        loaded.push((format!("{config:?}"), file));
+        std::fs::remove_file(dir.join(name)).unwrap();
     }
-    Ok(None)
+
+    let names: Vec<PathBuf> = files.iter().map(|(name, _)| dir.join(name)).collect();
+    assert_eq!(
+        loaded
+            .iter()
+            .map(|(_, file)| file.clone())
+            .collect::<Vec<_>>(),
+        names
+    );
+    assert_eq!(loaded[0].0, loaded[1].0);
+    assert_eq!(loaded[1].0, loaded[2].0);
 }
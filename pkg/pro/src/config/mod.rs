@@ -3,22 +3,26 @@
 use base::{
     document::{
         linting::{
-            default::{check_body_line_length, check_subject_line_length},
+            default::{check_body_line_length, check_subject_line_length, check_total_length},
             utils::make_line_diagnostic,
         },
-        GitCommitDocument,
+        FormatOptions, GitCommitDocument,
     },
     log_debug, LANGUAGE,
 };
 use indexmap::IndexMap;
 use serde::Deserialize;
 use std::{
+    cell::RefCell,
     collections::HashMap,
     path::{Path, PathBuf},
     sync::Arc,
+    time::{Duration, Instant},
 };
+pub(crate) mod codeowners;
 // TODO: move json_ish behind a feature flag
 pub(crate) mod json_ish;
+pub use json_ish::CONFIG_FILE_NAMES;
 
 #[derive(Deserialize, Clone, Debug, Default)]
 #[serde(rename_all = "lowercase")]
@@ -43,29 +47,184 @@ impl From<Severity> for Option<lsp_types::DiagnosticSeverity> {
     }
 }
 
+/// when scope suggestions were computed, and what they were
+type CachedScopes = (Instant, Vec<(String, String)>);
+
 #[derive(Default)]
 pub struct Config {
     worktree_root: PathBuf,
-    // TODO: source
+    /// the config file the settings came from, `git config`, or `<default>`
+    source: String,
     types: IndexMap<String, String>,
     scopes: IndexMap<String, String>,
+    /// scopes derived from CODEOWNERS that are suggested but not enforced
+    codeowners_scopes: IndexMap<String, String>,
+    /// scope => owner, from config or CODEOWNERS
+    scope_owners: IndexMap<String, String>,
+    /// if set, suggest the scope implied by the staged files' directory at this depth
+    scope_directory_depth: Option<usize>,
+    /// how many past commits to scan when ranking scope suggestions; 0 skips the scan
+    scope_history_limit: usize,
+    /// the `header_max_line_length` cutoff, for counting down the subject's characters
+    max_subject_length: u16,
+    /// how long to reuse `scope_suggestions`; reloading the config starts over
+    scope_suggestion_ttl: Duration,
+    scope_suggestion_cache: RefCell<Option<CachedScopes>>,
+    /// alias => canonical scope
+    scope_aliases: HashMap<String, String>,
+    /// type => where the config file declares it
+    type_definitions: HashMap<String, lsp_types::Location>,
+    /// scope => where the config file declares it
+    scope_definitions: HashMap<String, lsp_types::Location>,
+    deprecated_types: Vec<String>,
+    /// type => the release it calls for, overriding `SemverBump::for_type`
+    semver: HashMap<String, base::config::SemverBump>,
     severity: HashMap<String, lsp_types::DiagnosticSeverity>,
     enabled_lints: Vec<String>,
+    format_options: FormatOptions,
+    comments_are_content: bool,
+    parse_timeout_micros: Option<u64>,
+    suggestions_only: bool,
+    gerrit: bool,
+    gitmoji: bool,
+    features: base::config::Features,
+    /// named configs selected by a `# cconvention-profile: <name>` comment
+    profiles: HashMap<String, Arc<Config>>,
+    /// problems loading the config file, which was replaced by the defaults
+    load_errors: Vec<base::config::ConfigFileError>,
     // queries: HashMap<String, tree_sitter::Query>,
-    tests: HashMap<String, Arc<dyn Fn(&GitCommitDocument) -> Vec<lsp_types::Diagnostic>>>,
+    tests: HashMap<String, Arc<base::document::linting::LintFn<'static>>>,
 }
 
 const SCOPE_ENUM: &str = "scope_enum";
 const MAX_BODY_LINE_LENGTH: u16 = 100;
+/// long enough to cover typing a scope, short enough to notice newly-staged files
+const DEFAULT_SCOPE_SUGGESTION_TTL_MS: u64 = 5000;
 
 impl Config {
     /// Load a config from the given worktree directory, adding default types, lints, & lint severity.
     pub fn new(worktree_root: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         use base::document::linting;
         // IDEA: draw lint-fn closures from a long-lived default store
-        let (json, src) = json_ish::get_config(worktree_root)?
-            .map(|(json, file)| (json, file.as_os_str().to_string_lossy().to_string()))
-            .unwrap_or((json_ish::JsonConfig::default(), "default".to_string()));
+        let from_git = json_ish::git_config::from_git_config(worktree_root)?;
+        // fall back to the defaults if the config file is malformed, reporting why
+        let mut load_errors = vec![];
+        let found = match json_ish::get_config(worktree_root) {
+            Ok(found) => found,
+            Err(err) => {
+                load_errors.push(*err.downcast::<base::config::ConfigFileError>()?);
+                None
+            }
+        };
+        let (json, src, config_file) = match (found, from_git) {
+            (Some((mut json, file)), from_git) => {
+                if let Some(from_git) = from_git {
+                    json.fill_from(from_git);
+                }
+                (
+                    json,
+                    file.as_os_str().to_string_lossy().to_string(),
+                    Some(file),
+                )
+            }
+            (None, Some(from_git)) => (from_git, "git config".to_string(), None),
+            (None, None) => (
+                json_ish::JsonConfig::default(),
+                linting::DEFAULT_SOURCE.to_string(),
+                None,
+            ),
+        };
+        let mut cfg = Self::from_json(worktree_root, json, src, config_file)?;
+        cfg.load_errors = load_errors;
+        let overrides = base::config::EnvLintOverrides::from_env();
+        cfg.apply_env_overrides(&overrides);
+        for profile in cfg.profiles.values_mut() {
+            if let Some(profile) = Arc::get_mut(profile) {
+                profile.apply_env_overrides(&overrides);
+            }
+        }
+        Ok(cfg)
+    }
+
+    /// layer the environment's lint overrides over the file's and client's settings
+    fn apply_env_overrides(&mut self, overrides: &base::config::EnvLintOverrides) {
+        overrides.apply(&mut self.enabled_lints, &mut self.severity);
+    }
+
+    /// the configured and CODEOWNERS scopes, ranked by their use with the staged files, led by
+    /// the staged files' directory if `scope_directory` is set
+    fn compute_scope_suggestions(&self) -> Vec<(String, String)> {
+        let relevant = if self.scope_history_limit > 0 {
+            base::config::staged_scope_suggestions(
+                Some(self.worktree_root.clone()),
+                self.scope_history_limit,
+            )
+        } else {
+            vec![]
+        };
+        // past commits' aliases count toward their canonical scope
+        let rank = |scope: &str| {
+            relevant.iter().position(|(s, _)| {
+                s == scope || self.scope_aliases.get(s).is_some_and(|c| c == scope)
+            })
+        };
+        let mut suggestions: Vec<(String, String)> = self
+            .scopes
+            .iter()
+            .chain(self.codeowners_scopes.iter())
+            .map(|(scope, doc)| match rank(scope) {
+                Some(i) => (scope.to_owned(), format!("{doc} ({})", relevant[i].1)),
+                None => (scope.to_owned(), doc.to_owned()),
+            })
+            .collect();
+        // boost the scopes used with the staged files
+        suggestions.sort_by_key(|(scope, _)| rank(scope).unwrap_or(usize::MAX));
+        if let Some(depth) = self.scope_directory_depth {
+            let staged = base::git::staged_files(Some(self.worktree_root.clone()));
+            if let Some(scope) = crate::lints::scope_from_directory(&staged, depth) {
+                suggestions.retain(|(s, _)| *s != scope);
+                suggestions.insert(
+                    0,
+                    (scope, "the directory containing all staged files".into()),
+                );
+            }
+        }
+        suggestions
+    }
+
+    /// layer the client's settings over the loaded ones, including each profile's
+    pub fn apply_settings(&mut self, settings: &base::config::ClientSettings) {
+        use base::document::linting;
+        if let Some(cutoff) = settings.max_subject_length {
+            self.max_subject_length = cutoff;
+            let code = linting::default::HEADER_MAX_LINE_LENGTH;
+            self.tests.insert(
+                code.to_string(),
+                Arc::new(move |doc| check_subject_line_length(doc, code, cutoff)),
+            );
+        }
+        if let Some(lints) = &settings.enabled_lints {
+            self.enabled_lints = lints.clone();
+        }
+        // the environment still wins
+        self.apply_env_overrides(&base::config::EnvLintOverrides::from_env());
+        for profile in self.profiles.values_mut() {
+            // profiles are only shared once the config is handed out
+            if let Some(profile) = Arc::get_mut(profile) {
+                profile.apply_settings(settings);
+            }
+        }
+    }
+
+    /// build a config from already-loaded settings, including each of their profiles
+    fn from_json(
+        worktree_root: &Path,
+        mut json: json_ish::JsonConfig,
+        src: String,
+        config_file: Option<PathBuf>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        use base::document::linting;
+        let profiles = json.profiles.take().unwrap_or_default();
         let enabled_lints: Vec<String> = linting::default::ENABLED_LINTS
             .iter()
             .chain(&["body_line_max_length"])
@@ -81,14 +240,103 @@ impl Config {
         } else {
             json.types.clone().unwrap()
         };
-        let scopes = json.scopes.unwrap_or_default();
+        // explicitly-configured scopes are always enforced as errors
+        let mut scope_enum_severity = lsp_types::DiagnosticSeverity::ERROR;
+        let mut scopes = json.scopes.unwrap_or_default();
+        let mut codeowners_scopes = IndexMap::new();
+        let mut scope_owners = IndexMap::new();
+        if let Some(rule) = &json.codeowners {
+            let source = rule.source.unwrap_or_default();
+            scope_owners = codeowners::scope_owners(worktree_root, source)?;
+            let derived = codeowners::scopes(worktree_root, source)?;
+            if let Some(severity) = rule.severity.clone().into() {
+                if scopes.is_empty() {
+                    scope_enum_severity = severity;
+                }
+                // validate scopes against CODEOWNERS via scope_enum
+                for (scope, doc) in derived {
+                    scopes.entry(scope).or_insert(doc);
+                }
+            } else {
+                codeowners_scopes = derived; // only suggest them
+            }
+        }
+        scope_owners.extend(json.scope_owners.unwrap_or_default());
+        let scope_aliases: HashMap<String, String> = json
+            .scope_aliases
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        for (alias, canonical) in &scope_aliases {
+            if scope_aliases.contains_key(canonical) {
+                return Err(format!(
+                    "scope alias {alias:?} points to another alias, {canonical:?}"
+                )
+                .into());
+            }
+            if !scopes.is_empty() && !scopes.contains_key(canonical) {
+                return Err(format!(
+                    "scope alias {alias:?} points to {canonical:?}, which isn't in `scopes`"
+                )
+                .into());
+            }
+        }
         let mut cfg = Config {
             worktree_root: worktree_root.to_path_buf(),
+            source: src.clone(),
             enabled_lints,
             types: types.clone(), // TODO: figure out how to re-use cfg.types in enum-checking lint-fn
             scopes: scopes.clone(), // TODO: figure out how to re-use cfg.scopes in enum-checking lint-fn
+            codeowners_scopes,
+            scope_owners,
+            scope_directory_depth: None,
+            scope_history_limit: if json.scope_suggest_from_history.unwrap_or(true) {
+                json.scope_history_limit
+                    .unwrap_or(base::config::DEFAULT_HISTORY_LIMIT)
+            } else {
+                0
+            },
+            scope_suggestion_ttl: Duration::from_millis(
+                json.scope_suggestion_ttl_ms
+                    .unwrap_or(DEFAULT_SCOPE_SUGGESTION_TTL_MS),
+            ),
+            scope_suggestion_cache: RefCell::new(None),
+            max_subject_length: linting::default::MAX_HEADER_LINE_LENGTH as u16,
+            scope_aliases: scope_aliases.clone(),
+            type_definitions: HashMap::new(),
+            scope_definitions: HashMap::new(),
+            deprecated_types: json.deprecated_types.clone().unwrap_or_default(),
+            semver: json
+                .semver
+                .iter()
+                .flatten()
+                .map(|(type_, bump)| (type_.clone(), (*bump).into()))
+                .collect(),
             severity: HashMap::with_capacity(2),
             tests: HashMap::new(),
+            format_options: FormatOptions {
+                scope_aliases: scope_aliases.clone(),
+                ..Default::default()
+            },
+            comments_are_content: json.comments_are_content.unwrap_or(false),
+            parse_timeout_micros: json.parse_timeout_micros,
+            suggestions_only: json.suggestions_only.unwrap_or(false),
+            gerrit: false,
+            gitmoji: false,
+            profiles: HashMap::with_capacity(profiles.len()),
+            load_errors: vec![],
+            features: {
+                let toggles = json.features.clone().unwrap_or_default();
+                let default = base::config::Features::default();
+                base::config::Features {
+                    completion: toggles.completion.unwrap_or(default.completion),
+                    hover: toggles.hover.unwrap_or(default.hover),
+                    semantic_tokens: toggles.semantic_tokens.unwrap_or(default.semantic_tokens),
+                    formatting: toggles.formatting.unwrap_or(default.formatting),
+                    document_links: toggles.document_links.unwrap_or(default.document_links),
+                }
+            },
         };
         cfg.severity.insert(
             linting::default::TYPE_ENUM.to_string(),
@@ -131,20 +379,22 @@ impl Config {
 
         if !cfg.scopes.is_empty() {
             cfg.enabled_lints.push("scope_enum".to_string());
-            cfg.severity.insert(
-                "scope_enum".to_string(),
-                lsp_types::DiagnosticSeverity::ERROR,
-            );
+            cfg.severity
+                .insert("scope_enum".to_string(), scope_enum_severity);
             cfg.tests.insert(
                 "scope_enum".to_string(),
+                // scopes are optional unless `scope_required`, but must be in the enum if present
                 Arc::new(move |doc| -> Vec<lsp_types::Diagnostic> {
                     let mut lints: Vec<lsp_types::Diagnostic> = vec![];
                     lints.extend(doc.subject.as_ref().and_then(|header| {
-                        let scope_text = header.scope_text();
-                        if scopes.contains_key(scope_text) {
+                        let scope_text = header.scope_name();
+                        if scope_text.trim().is_empty()
+                            || scopes.contains_key(scope_text)
+                            || scope_aliases.contains_key(scope_text)
+                        {
                             None
                         } else {
-                            let start = header.type_text().chars().count();
+                            let start = header.type_text().chars().count() + 1;
                             let end = start + scope_text.chars().count();
                             let mut lint = make_line_diagnostic(
                                 format!(
@@ -170,6 +420,17 @@ impl Config {
             );
         }
 
+        if let Some(glossary) = json.scope_glossary.filter(|g| !g.is_empty()) {
+            let code = crate::lints::SCOPE_GLOSSARY;
+            cfg.enabled_lints.push(code.to_string());
+            cfg.severity
+                .insert(code.to_string(), lsp_types::DiagnosticSeverity::HINT);
+            cfg.tests.insert(
+                code.to_string(),
+                Arc::new(move |doc| crate::lints::check_scope_glossary(doc, code, &glossary)),
+            );
+        }
+
         macro_rules! handle_builtin_length_rule {
             ($code:expr, $id:ident, $f:ident, $cutoff:expr) => {
                 if let Some(rule) = json.$id {
@@ -188,6 +449,14 @@ impl Config {
                 }
             };
         }
+        let header_length_configured = json.header_line_max_length.is_some();
+        if let Some(cutoff) = json
+            .header_line_max_length
+            .as_ref()
+            .and_then(|rule| rule.max_length)
+        {
+            cfg.max_subject_length = cutoff;
+        }
         handle_builtin_length_rule!(
             linting::default::HEADER_MAX_LINE_LENGTH,
             header_line_max_length,
@@ -202,6 +471,117 @@ impl Config {
             MAX_BODY_LINE_LENGTH
         );
 
+        {
+            let code = linting::default::SCOPE_MAX_WORDS;
+            let rule = json.scope_max_words;
+            let max_words = rule
+                .as_ref()
+                .and_then(|rule| rule.max_words)
+                .unwrap_or(linting::default::MAX_SCOPE_WORDS);
+            cfg.tests.insert(
+                code.to_string(),
+                Arc::new(move |doc| linting::default::check_scope_max_words(doc, code, max_words)),
+            );
+            if let Some(severity) = rule.and_then(|rule| rule.severity) {
+                match severity.into() {
+                    Some(severity) => {
+                        cfg.severity.insert(code.to_string(), severity);
+                    }
+                    None => cfg.enabled_lints.retain(|lint| lint != code),
+                }
+            }
+        }
+
+        // opt-in: only enabled when configured
+        if let Some(severity) = json.total_max_length.as_ref().and_then(|rule| {
+            Option::<lsp_types::DiagnosticSeverity>::from(rule.severity.clone().unwrap_or_default())
+        }) {
+            cfg.enabled_lints
+                .push(linting::default::TOTAL_MAX_LENGTH.to_string());
+            cfg.severity
+                .insert(linting::default::TOTAL_MAX_LENGTH.to_string(), severity);
+        }
+        handle_builtin_length_rule!(
+            linting::default::TOTAL_MAX_LENGTH,
+            total_max_length,
+            check_total_length,
+            linting::default::MAX_TOTAL_LENGTH
+        );
+        macro_rules! handle_component_length_rule {
+            ($code:expr, $id:ident, $component:expr) => {
+                // opt-in: only enabled when configured
+                if let Some(rule) = json.$id {
+                    let code = $code;
+                    let (min, max) = (rule.min_length, rule.max_length);
+                    if let (Some(min), Some(max)) = (min, max) {
+                        if min > max {
+                            return Err(format!(
+                                "{code}: min_length {min} exceeds max_length {max}"
+                            )
+                            .into());
+                        }
+                    }
+                    if let Some(severity) = Option::<lsp_types::DiagnosticSeverity>::from(
+                        rule.severity.unwrap_or_default(),
+                    ) {
+                        cfg.enabled_lints.push(code.to_string());
+                        cfg.severity.insert(code.to_string(), severity);
+                        cfg.tests.insert(
+                            code.to_string(),
+                            Arc::new(move |doc| {
+                                linting::default::check_component_length(
+                                    doc, code, $component, min, max,
+                                )
+                            }),
+                        );
+                    }
+                }
+            };
+        }
+        handle_component_length_rule!(
+            linting::default::TYPE_LENGTH,
+            type_length,
+            linting::default::Component::Type
+        );
+        handle_component_length_rule!(
+            linting::default::SCOPE_LENGTH,
+            scope_length,
+            linting::default::Component::Scope
+        );
+        handle_component_length_rule!(
+            linting::default::SUBJECT_LENGTH,
+            subject_length,
+            linting::default::Component::Subject
+        );
+        if let Some(rule) = json.subject_length_tiers {
+            let code = linting::default::SUBJECT_LENGTH_TIERS;
+            let soft = rule
+                .soft_limit
+                .unwrap_or(linting::default::MAX_HEADER_LINE_LENGTH as u16);
+            let hard = rule
+                .hard_limit
+                .unwrap_or(linting::default::HARD_MAX_HEADER_LINE_LENGTH);
+            if soft > hard {
+                return Err(format!(
+                    "subject_length_tiers: soft_limit {soft} exceeds hard_limit {hard}"
+                )
+                .into());
+            }
+            if !header_length_configured {
+                // the soft limit takes over the single cutoff
+                cfg.enabled_lints
+                    .retain(|lint| lint != linting::default::HEADER_MAX_LINE_LENGTH);
+                cfg.max_subject_length = soft;
+            }
+            cfg.enabled_lints.push(code.to_string());
+            cfg.tests.insert(
+                code.to_string(),
+                Arc::new(move |doc| {
+                    linting::default::check_subject_length_tiers(doc, code, soft, hard)
+                }),
+            );
+        }
+
         macro_rules! insert_builtin {
             ($code:expr => $f:expr) => {
                 cfg.tests
@@ -225,11 +605,31 @@ impl Config {
         insert_builtin!(linting::default::FOOTER_LEADING_BLANK => linting::default::check_footer_leading_blank);
         insert_builtin!(linting::default::SUBJECT_EMPTY => linting::default::check_subject_empty);
         insert_builtin!(linting::default::SUBJECT_LEADING_SPACE => linting::default::check_subject_leading_space);
+        insert_builtin!(linting::default::SUBJECT_WRAPPED => linting::default::check_subject_wrapped);
+        insert_builtin!(linting::default::BREAKING_CHANGE_CASE => linting::default::check_breaking_change_case);
+        insert_builtin!(linting::default::TRAILER_SEPARATOR => linting::default::check_trailer_separator);
+        // `scope_required` toggles the presence check; `missing_scope` sets its severity
+        match json.scope_required {
+            Some(false) => json.missing_scope = None,
+            Some(true) if json.missing_scope.is_none() => {
+                json.missing_scope = Some(json_ish::BuiltinRule {
+                    severity: Severity::Error,
+                })
+            }
+            _ => {}
+        }
         insert_optional_builtin!(
             missing_scope,
             crate::lints::MISSING_SCOPE,
             crate::lints::check_scope_present
         );
+        if json.scope_required_when_breaking == Some(true) {
+            let code = crate::lints::BREAKING_SCOPE;
+            cfg.severity
+                .insert(code.to_string(), lsp_types::DiagnosticSeverity::ERROR);
+            cfg.enabled_lints.push(code.to_string());
+            insert_builtin!(code => crate::lints::check_breaking_scope);
+        }
         insert_optional_builtin!(
             signed_off_by,
             crate::lints::MISSING_DCO,
@@ -240,6 +640,364 @@ impl Config {
             crate::lints::MISSING_BODY,
             crate::lints::missing_body
         );
+        if let Some(line_ending) = json.line_ending {
+            cfg.format_options.line_ending = line_ending.into();
+        }
+        cfg.format_options.body_wrap = json.body_wrap.unwrap_or(0);
+        if let Some(trailer_format) = json.trailer_format {
+            cfg.format_options.normalize_trailer_keys =
+                trailer_format.normalize_keys.unwrap_or(true);
+            cfg.format_options.trailer_keys = trailer_format.keys.unwrap_or_default();
+            cfg.format_options.trailer_order = trailer_format.order.unwrap_or_default();
+        }
+        cfg.format_options.comment_leading_blank = json
+            .comment_leading_blank
+            .as_ref()
+            .and_then(|rule| -> Option<lsp_types::DiagnosticSeverity> {
+                rule.severity.clone().into()
+            })
+            .is_some();
+        insert_optional_builtin!(
+            comment_leading_blank,
+            linting::default::COMMENT_LEADING_BLANK,
+            linting::default::check_comment_leading_blank
+        );
+        insert_optional_builtin!(
+            bracket_scope,
+            linting::default::BRACKET_SCOPE,
+            linting::default::check_bracket_scope
+        );
+        insert_optional_builtin!(
+            subject_url,
+            linting::default::SUBJECT_URL,
+            linting::default::check_subject_url
+        );
+        insert_optional_builtin!(
+            trailer_without_body,
+            linting::default::TRAILER_WITHOUT_BODY,
+            linting::default::check_trailer_without_body
+        );
+        if let Some(rule) = json.closing_keywords {
+            let code = crate::lints::CLOSING_KEYWORDS;
+            if let Some(severity) = rule.severity.into() {
+                let keywords = rule.keywords.unwrap_or_else(|| {
+                    crate::lints::DEFAULT_CLOSING_KEYWORDS
+                        .iter()
+                        .map(|k| k.to_string())
+                        .collect()
+                });
+                let branches = rule.branches;
+                cfg.severity.insert(code.to_string(), severity);
+                cfg.enabled_lints.push(code.to_string());
+                cfg.tests.insert(
+                    code.to_string(),
+                    Arc::new(move |doc| {
+                        crate::lints::check_closing_keywords_on_branch(
+                            doc, code, &branches, &keywords,
+                        )
+                    }),
+                );
+            }
+        }
+        if let Some(rule) = json.gerrit {
+            if let Some(severity) = rule.severity.into() {
+                cfg.gerrit = true;
+                let mut codes = vec![linting::default::CHANGE_ID_FORMAT];
+                insert_builtin!(linting::default::CHANGE_ID_FORMAT => linting::default::check_change_id_format);
+                if rule.require_change_id.unwrap_or(false) {
+                    codes.push(linting::default::MISSING_CHANGE_ID);
+                    insert_builtin!(linting::default::MISSING_CHANGE_ID => linting::default::check_missing_change_id);
+                }
+                for code in codes {
+                    cfg.severity.insert(code.to_string(), severity);
+                    cfg.enabled_lints.push(code.to_string());
+                }
+            }
+        }
+        if let Some(rule) = json.gitmoji {
+            cfg.gitmoji = true;
+            if let Some(severity) = rule.severity.and_then(|s| s.into()) {
+                let code = linting::default::SUBJECT_GITMOJI;
+                insert_builtin!(code => linting::default::check_subject_gitmoji);
+                cfg.severity.insert(code.to_string(), severity);
+                cfg.enabled_lints.push(code.to_string());
+            }
+        }
+        if let Some(rule) = json.large_diff_body {
+            let code = crate::lints::LARGE_DIFF_BODY;
+            if let Some(severity) = rule.severity.into() {
+                let max_lines = rule
+                    .max_lines
+                    .unwrap_or(crate::lints::DEFAULT_LARGE_DIFF_LINES);
+                cfg.severity.insert(code.to_string(), severity);
+                cfg.enabled_lints.push(code.to_string());
+                cfg.tests.insert(
+                    code.to_string(),
+                    Arc::new(move |doc| {
+                        crate::lints::check_large_diff_body(
+                            doc,
+                            code,
+                            doc.changed_line_count(),
+                            max_lines,
+                        )
+                    }),
+                );
+            }
+        }
+        if let Some(rule) = json.subject_full_stop {
+            let code = linting::default::SUBJECT_FULL_STOP;
+            if let Some(severity) = rule.severity.into() {
+                let stops = rule
+                    .characters
+                    .unwrap_or_else(|| linting::default::DEFAULT_FULL_STOPS.into());
+                cfg.format_options.subject_full_stops = stops.clone();
+                cfg.severity.insert(code.to_string(), severity);
+                cfg.enabled_lints.push(code.to_string());
+                cfg.tests.insert(
+                    code.to_string(),
+                    Arc::new(move |doc| {
+                        linting::default::check_subject_full_stop(doc, code, &stops)
+                    }),
+                );
+            }
+        }
+        macro_rules! insert_case_rule {
+            ($id:ident, $code:expr, $f:expr) => {
+                if let Some(rule) = json.$id {
+                    let code = $code;
+                    if let Some(severity) = rule.severity.into() {
+                        let case = rule.case.into();
+                        cfg.severity.insert(code.to_string(), severity);
+                        cfg.enabled_lints.push(code.to_string());
+                        cfg.tests
+                            .insert(code.to_string(), Arc::new(move |doc| $f(doc, code, case)));
+                    }
+                }
+            };
+        }
+        insert_case_rule!(
+            type_case,
+            linting::default::TYPE_CASE,
+            linting::default::check_type_case
+        );
+        insert_case_rule!(
+            scope_case,
+            linting::default::SCOPE_CASE,
+            linting::default::check_scope_case
+        );
+        insert_case_rule!(
+            subject_case,
+            linting::default::SUBJECT_CASE,
+            linting::default::check_subject_case
+        );
+        if let Some(rule) = json.subject_first_word {
+            let code = crate::lints::SUBJECT_FIRST_WORD;
+            if let Some(severity) = rule.severity.into() {
+                let constraints = crate::lints::FirstWordConstraints {
+                    max_length: rule.max_length,
+                    min_length: rule.min_length,
+                    case: rule.case,
+                };
+                cfg.severity.insert(code.to_string(), severity);
+                cfg.enabled_lints.push(code.to_string());
+                cfg.tests.insert(
+                    code.to_string(),
+                    Arc::new(move |doc| {
+                        crate::lints::check_subject_first_word(doc, code, &constraints)
+                    }),
+                );
+            }
+        }
+        if let Some(rule) = json.trailer_values {
+            let code = crate::lints::TRAILER_VALUES;
+            if let Some(severity) = rule.severity.into() {
+                let mut sources: IndexMap<String, (String, String)> =
+                    crate::lints::DEFAULT_TRAILER_PATTERNS
+                        .iter()
+                        .map(|(key, pattern, expected)| {
+                            (key.to_string(), (pattern.to_owned(), expected.to_string()))
+                        })
+                        .collect();
+                for (key, pattern) in rule.patterns.unwrap_or_default() {
+                    // replace any default spelled with different casing
+                    sources.retain(|k, _| !k.eq_ignore_ascii_case(&key));
+                    let expected = format!("a value matching `{pattern}`");
+                    sources.insert(key, (pattern, expected));
+                }
+                let mut patterns = Vec::with_capacity(sources.len());
+                for (key, (pattern, expected)) in sources {
+                    let regex = regex::Regex::new(&pattern)
+                        .map_err(|e| format!("invalid pattern for trailer {key:?}: {e}"))?;
+                    patterns.push((key, regex, expected));
+                }
+                cfg.severity.insert(code.to_string(), severity);
+                cfg.enabled_lints.push(code.to_string());
+                cfg.tests.insert(
+                    code.to_string(),
+                    Arc::new(move |doc| crate::lints::check_trailer_values(doc, code, &patterns)),
+                );
+            }
+        }
+        if let Some(rule) = json.trailer_exists {
+            let code = crate::lints::TRAILER_EXISTS;
+            if let Some(severity) = rule.severity.into() {
+                let keys = rule.keys;
+                cfg.severity.insert(code.to_string(), severity);
+                cfg.enabled_lints.push(code.to_string());
+                cfg.tests.insert(
+                    code.to_string(),
+                    Arc::new(move |doc| crate::lints::check_trailer_exists(doc, code, &keys)),
+                );
+            }
+        }
+        if let Some(rule) = json.references {
+            let code = crate::lints::REFERENCES_EMPTY;
+            if let Some(severity) = rule.severity.into() {
+                let pattern = rule
+                    .pattern
+                    .as_deref()
+                    .unwrap_or(crate::lints::DEFAULT_REFERENCE_PATTERN);
+                let pattern = regex::Regex::new(pattern)
+                    .map_err(|e| format!("invalid references pattern {pattern:?}: {e}"))?;
+                cfg.severity.insert(code.to_string(), severity);
+                cfg.enabled_lints.push(code.to_string());
+                cfg.tests.insert(
+                    code.to_string(),
+                    Arc::new(move |doc| crate::lints::check_references_empty(doc, code, &pattern)),
+                );
+            }
+        }
+        if let Some(rule) = json.author_domain {
+            let code = crate::lints::AUTHOR_DOMAIN;
+            if let Some(severity) = rule.severity.into() {
+                let domains = rule.domains;
+                cfg.severity.insert(code.to_string(), severity);
+                cfg.enabled_lints.push(code.to_string());
+                cfg.tests.insert(
+                    code.to_string(),
+                    Arc::new(move |doc| {
+                        let email = doc.author_email();
+                        crate::lints::check_author_domain(code, email.as_deref(), &domains)
+                    }),
+                );
+            }
+        }
+        if let Some(rule) = json.subject_duplicate_recent {
+            let code = crate::lints::SUBJECT_DUPLICATE_RECENT;
+            if let Some(severity) = rule.severity.unwrap_or(Severity::Hint).into() {
+                let count = rule.count.unwrap_or(crate::lints::DEFAULT_RECENT_SUBJECTS);
+                cfg.severity.insert(code.to_string(), severity);
+                cfg.enabled_lints.push(code.to_string());
+                cfg.tests.insert(
+                    code.to_string(),
+                    Arc::new(move |doc| {
+                        let recent = crate::lints::recent_subjects(doc, count);
+                        crate::lints::check_subject_duplicate_recent(doc, code, &recent)
+                    }),
+                );
+            }
+        }
+        if let Some(rule) = json.revert_type {
+            let code = crate::lints::REVERT_TYPE;
+            if let Some(severity) = rule.severity.unwrap_or(Severity::Warning).into() {
+                cfg.severity.insert(code.to_string(), severity);
+                cfg.enabled_lints.push(code.to_string());
+                insert_builtin!(code => crate::lints::check_revert_type);
+            }
+        }
+        if let Some(rule) = json.type_scope_swap {
+            let code = crate::lints::TYPE_SCOPE_SWAP;
+            if let Some(severity) = rule.severity.unwrap_or(Severity::Hint).into() {
+                let types: Vec<String> = cfg.types.keys().cloned().collect();
+                let scopes: Vec<String> = cfg.scopes.keys().cloned().collect();
+                cfg.severity.insert(code.to_string(), severity);
+                cfg.enabled_lints.push(code.to_string());
+                cfg.tests.insert(
+                    code.to_string(),
+                    Arc::new(move |doc| {
+                        crate::lints::check_type_scope_swap(doc, code, &types, &scopes)
+                    }),
+                );
+            }
+        }
+        if let Some(rule) = json.body_style {
+            let code = crate::lints::BODY_STYLE;
+            if let Some(severity) = rule.severity.unwrap_or(Severity::Hint).into() {
+                let words = rule.first_person.unwrap_or_else(|| {
+                    crate::lints::DEFAULT_FIRST_PERSON_WORDS
+                        .iter()
+                        .map(|w| w.to_string())
+                        .collect()
+                });
+                cfg.severity.insert(code.to_string(), severity);
+                cfg.enabled_lints.push(code.to_string());
+                cfg.tests.insert(
+                    code.to_string(),
+                    Arc::new(move |doc| crate::lints::check_body_style(doc, code, &words)),
+                );
+            }
+        }
+        if let Some(rule) = json.subject_conjunction {
+            let code = crate::lints::SUBJECT_CONJUNCTION;
+            if let Some(severity) = rule.severity.unwrap_or(Severity::Hint).into() {
+                let words = rule.words.unwrap_or_else(|| {
+                    crate::lints::DEFAULT_CONJUNCTIONS
+                        .iter()
+                        .map(|w| w.to_string())
+                        .collect()
+                });
+                cfg.severity.insert(code.to_string(), severity);
+                cfg.enabled_lints.push(code.to_string());
+                cfg.tests.insert(
+                    code.to_string(),
+                    Arc::new(move |doc| crate::lints::check_subject_conjunction(doc, code, &words)),
+                );
+            }
+        }
+        if let Some(rule) = json.scope_directory {
+            let code = crate::lints::SCOPE_DIRECTORY;
+            let depth = rule
+                .depth
+                .unwrap_or(crate::lints::DEFAULT_SCOPE_DIRECTORY_DEPTH);
+            cfg.scope_directory_depth = Some(depth);
+            if let Some(severity) = rule.severity.unwrap_or(Severity::Hint).into() {
+                cfg.severity.insert(code.to_string(), severity);
+                cfg.enabled_lints.push(code.to_string());
+                cfg.tests.insert(
+                    code.to_string(),
+                    Arc::new(move |doc| {
+                        crate::lints::check_scope_directory(doc, code, &doc.changed_files(), depth)
+                    }),
+                );
+            }
+        }
+        if let Some(rule) = json.lockfile_only {
+            let code = crate::lints::LOCKFILE_ONLY;
+            if let Some(severity) = rule.severity.into() {
+                let patterns = rule.patterns.unwrap_or_else(|| {
+                    crate::lints::DEFAULT_LOCKFILES
+                        .iter()
+                        .map(|p| p.to_string())
+                        .collect()
+                });
+                let (type_, scope) = (rule.type_, rule.scope);
+                cfg.severity.insert(code.to_string(), severity);
+                cfg.enabled_lints.push(code.to_string());
+                cfg.tests.insert(
+                    code.to_string(),
+                    Arc::new(move |doc| {
+                        crate::lints::check_lockfile_only(
+                            doc,
+                            code,
+                            &doc.changed_files(),
+                            &patterns,
+                            &type_,
+                            scope.as_deref(),
+                        )
+                    }),
+                );
+            }
+        }
         // insert_builtin!(lints::TYPE_ENUM)
         // TODO: type_enum, scope_enum
         // handle built-in boolean lints
@@ -267,6 +1025,9 @@ impl Config {
             linting::default::SUBJECT_LEADING_SPACE,
             missing_subject_leading_space
         );
+        insert_severity!(linting::default::SUBJECT_WRAPPED, subject_wrapped);
+        insert_severity!(linting::default::BREAKING_CHANGE_CASE, breaking_change_case);
+        insert_severity!(linting::default::TRAILER_SEPARATOR, trailer_separator);
 
         for (code, plugin) in json.plugins {
             {
@@ -274,18 +1035,28 @@ impl Config {
                 //  failed because the Arc<Fn>'s lifetime kept capturing the HashMap's lifetime,
                 // requiring the HashMap to be borrowed for 'static.
                 // Instead of dealing with all that, always compile the query.
-                // TODO: display error messages to the user
-                let query = tree_sitter::Query::new(&LANGUAGE, &plugin.query).map_err(|e| {
-                    format!(
-                        "{:?} error compiling tree-sitter query `{}.query` @ {} line {} column {} : {:?}",
-                        e.kind,
-                        code,
-                        &src,
-                        e.row,
-                        e.column,
-                        e.message
-                    )
-                })?;
+                let query = tree_sitter::Query::new(&LANGUAGE, &plugin.query).map_err(
+                    |e| -> Box<dyn std::error::Error + Send + Sync> {
+                        let message = format!(
+                            "{:?} error compiling tree-sitter query `{}.query` @ line {} column {} : {:?}",
+                            e.kind, code, e.row, e.column, e.message
+                        );
+                        let Some(path) = config_file.clone() else {
+                            return message.into();
+                        };
+                        let start = std::fs::read_to_string(&path)
+                            .ok()
+                            .and_then(|text| json_ish::locate_query(&text, &code, e.row, e.column))
+                            .unwrap_or_default();
+                        let mut end = start;
+                        end.character += 1;
+                        Box::new(base::config::ConfigFileError {
+                            path,
+                            range: lsp_types::Range { start, end },
+                            message,
+                        })
+                    },
+                )?;
                 let code = code.clone();
                 cfg.tests.insert(
                     code.clone(),
@@ -307,7 +1078,87 @@ impl Config {
             );
             cfg.enabled_lints.push(code);
         }
+        let dylib_plugins = json.dylib_plugins.unwrap_or_default();
+        #[cfg(not(feature = "dylib_plugins"))]
+        if let Some((code, plugin)) = dylib_plugins.first() {
+            return Err(format!(
+                "unable to load plugin `{}` from {:?} @ {}: built without the `dylib_plugins` feature",
+                code, plugin.path, &src
+            )
+            .into());
+        }
+        #[cfg(feature = "dylib_plugins")]
+        for (code, plugin) in dylib_plugins {
+            cfg.tests.insert(
+                code.clone(),
+                crate::lints::dylib::load(&worktree_root.join(&plugin.path), code.clone())?,
+            );
+            let severity: Option<lsp_types::DiagnosticSeverity> = plugin.severity.into();
+            cfg.severity.insert(
+                code.clone(),
+                severity.unwrap_or(lsp_types::DiagnosticSeverity::ERROR),
+            );
+            cfg.enabled_lints.push(code);
+        }
+        if let Some(rule) = json.reference_exists {
+            #[cfg(not(feature = "forge_api"))]
+            return Err(format!(
+                "unable to check references to {:?} @ {}: built without the `forge_api` feature",
+                rule.repo, &src
+            )
+            .into());
+            #[cfg(feature = "forge_api")]
+            if let Some(severity) = rule.severity.into() {
+                use crate::lints::issues;
+                let code = crate::lints::REFERENCE_EXISTS;
+                let forge: issues::forge::Forge =
+                    serde_json::from_value(serde_json::Value::String(rule.forge.clone()))
+                        .map_err(|_| format!("unknown forge {:?} @ {}", rule.forge, src))?;
+                let resolver = issues::forge::ForgeResolver::new(
+                    forge,
+                    rule.repo,
+                    rule.api_url,
+                    rule.token_env.and_then(|var| std::env::var(var).ok()),
+                    rule.cache_ttl_secs.unwrap_or(60 * 60),
+                );
+                let keys = rule.keys.unwrap_or_else(|| {
+                    issues::DEFAULT_REFERENCE_KEYS
+                        .iter()
+                        .map(|k| k.to_string())
+                        .collect()
+                });
+                cfg.severity.insert(code.to_string(), severity);
+                cfg.enabled_lints.push(code.to_string());
+                cfg.tests.insert(
+                    code.to_string(),
+                    Arc::new(move |doc| {
+                        issues::check_reference_exists(doc, code, &keys, &resolver)
+                    }),
+                );
+            }
+        }
+        if let Some((path, text)) = config_file
+            .as_ref()
+            .and_then(|path| Some((path, std::fs::read_to_string(path).ok()?)))
+        {
+            if let Ok(uri) = lsp_types::Url::from_file_path(path) {
+                let locate = |section, keys| -> HashMap<String, lsp_types::Location> {
+                    json_ish::locate_keys(&text, section, keys)
+                        .into_iter()
+                        .map(|(key, range)| (key, lsp_types::Location::new(uri.clone(), range)))
+                        .collect()
+                };
+                cfg.type_definitions = locate("types", cfg.types.keys());
+                cfg.scope_definitions = locate("scopes", cfg.scopes.keys());
+            }
+        }
         log_debug!("enabled_lints: {:?}", cfg.enabled_lints);
+        for (name, profile) in profiles {
+            let source = format!("{src} (profile {name:?})");
+            let profile = Config::from_json(worktree_root, profile, source, config_file.clone())?;
+            #[allow(clippy::arc_with_non_send_sync)] // configs never leave the server's thread
+            cfg.profiles.insert(name, Arc::new(profile));
+        }
 
         Ok(cfg)
     }
@@ -317,27 +1168,601 @@ impl base::document::linting::LintConfig for Config {
     fn enabled_lint_codes(&self) -> Vec<&str> {
         self.enabled_lints.iter().map(|s| s.as_str()).collect()
     }
+    fn source(&self) -> &str {
+        &self.source
+    }
     fn worktree_root(&self) -> Option<PathBuf> {
         Some(self.worktree_root.clone())
     }
+    fn line_ending(&self) -> base::document::LineEnding {
+        self.format_options.line_ending
+    }
+    fn suggestions_only(&self) -> bool {
+        self.suggestions_only
+    }
+    fn is_subject_lint(&self, code: &str) -> bool {
+        base::document::linting::default::SUBJECT_LINTS.contains(&code)
+            || [
+                SCOPE_ENUM,
+                crate::lints::MISSING_SCOPE,
+                crate::lints::SCOPE_GLOSSARY,
+                crate::lints::SUBJECT_FIRST_WORD,
+                crate::lints::SUBJECT_CONJUNCTION,
+                crate::lints::TYPE_SCOPE_SWAP,
+            ]
+            .contains(&code)
+    }
+    fn lint_severity(&self, lint_code: &str) -> &lsp_types::DiagnosticSeverity {
+        self.severity.get(lint_code).unwrap_or_else(|| {
+            base::document::linting::default::LINT_SEVERITY
+                .get(lint_code)
+                .unwrap_or(&lsp_types::DiagnosticSeverity::WARNING)
+        })
+    }
 
-    fn get_test(&self, code: &str) -> Option<&std::sync::Arc<base::document::linting::LintFn>> {
+    fn get_test(&self, code: &str) -> Option<&std::sync::Arc<base::document::linting::LintFn<'_>>> {
         self.tests.get(code)
     }
 }
 
 impl base::config::Config for Config {
+    fn format_options(&self) -> FormatOptions {
+        FormatOptions {
+            parenthesize_bracket_scopes: self
+                .enabled_lints
+                .iter()
+                .any(|code| code == base::document::linting::default::BRACKET_SCOPE),
+            ..self.format_options.clone()
+        }
+    }
+    fn comments_are_content(&self) -> bool {
+        self.comments_are_content
+    }
+    fn parse_timeout_micros(&self) -> u64 {
+        base::config::parse_timeout_from_env()
+            .or(self.parse_timeout_micros)
+            .unwrap_or(base::document::DEFAULT_PARSE_TIMEOUT_MICROS)
+    }
+    fn gerrit(&self) -> bool {
+        self.gerrit
+    }
+    fn gitmoji(&self) -> bool {
+        self.gitmoji
+    }
+    fn max_subject_length(&self) -> u16 {
+        self.max_subject_length
+    }
     fn type_suggestions(&self) -> Vec<(String, String)> {
         self.types
             .iter()
             .map(|(k, v)| (k.to_owned(), v.to_owned()))
             .collect()
     }
+    fn trailer_suggestions(&self) -> Vec<(String, String)> {
+        let mut suggestions: Vec<(String, String)> = base::config::DEFAULT_TRAILERS
+            .iter()
+            .map(|(key, detail)| (key.to_string(), detail.to_string()))
+            .collect();
+        // keys from `trailer_format.keys` are ones the team uses
+        for key in &self.format_options.trailer_keys {
+            if !suggestions.iter().any(|(k, _)| k.eq_ignore_ascii_case(key)) {
+                suggestions.push((key.clone(), "A trailer from the project's config.".into()));
+            }
+        }
+        suggestions
+    }
     fn scope_suggestions(&self) -> Vec<(String, String)> {
-        // TODO: sort by recency of use on the affected files
-        self.scopes
+        let mut cache = self.scope_suggestion_cache.borrow_mut();
+        if let Some((computed, suggestions)) = cache.as_ref() {
+            if computed.elapsed() < self.scope_suggestion_ttl {
+                return suggestions.clone();
+            }
+        }
+        let suggestions = self.compute_scope_suggestions();
+        *cache = Some((Instant::now(), suggestions.clone()));
+        suggestions
+    }
+    fn features(&self) -> base::config::Features {
+        self.features
+    }
+    fn type_definition(&self, type_: &str) -> Option<lsp_types::Location> {
+        self.type_definitions.get(type_).cloned()
+    }
+    fn scope_definition(&self, scope: &str) -> Option<lsp_types::Location> {
+        let scope = self.scope_aliases.get(scope).map_or(scope, |s| s.as_str());
+        self.scope_definitions.get(scope).cloned()
+    }
+    fn scope_owner(&self, scope: &str) -> Option<String> {
+        self.scope_owners.get(scope).cloned()
+    }
+    fn load_errors(&self) -> Vec<base::config::ConfigFileError> {
+        self.load_errors.clone()
+    }
+    fn profile(&self, name: &str) -> Option<Arc<dyn base::config::Config>> {
+        self.profiles
+            .get(name)
+            .map(|profile| profile.clone() as Arc<dyn base::config::Config>)
+    }
+    fn deprecated_types(&self) -> Vec<String> {
+        self.deprecated_types.clone()
+    }
+    fn semver_bump(&self, type_: &str) -> base::config::SemverBump {
+        self.semver
+            .get(type_)
+            .copied()
+            .unwrap_or_else(|| base::config::SemverBump::for_type(type_))
+    }
+}
+
+#[test]
+fn test_scope_owner_hover() {
+    use base::config::Config as _;
+    let dir = base::git::TempRepo::new("owners");
+    dir.write(".github/CODEOWNERS", "/pkg/ui/ @org/frontend\n");
+    dir.write(
+        "commit_convention.json",
+        r#"{
+            "scopes": {"api": "The HTTP API."},
+            "scope_owners": {"api": "@org/backend"},
+            "codeowners": {"severity": "none"}
+        }"#,
+    );
+    let cfg = Config::new(&dir).unwrap();
+    assert_eq!(
+        cfg.scope_hover("api").unwrap(),
+        "The HTTP API.\n\nowned by @org/backend"
+    );
+    assert_eq!(cfg.scope_hover("ui").unwrap(), "owned by @org/frontend");
+    assert_eq!(cfg.scope_hover("db"), None);
+}
+
+#[test]
+fn test_gitmoji() {
+    use base::{config::Config as _, document::linting::LintConfig};
+    let load = |json: &str| {
+        let json: json_ish::JsonConfig = serde_json::from_str(json).unwrap();
+        Config::from_json(Path::new("/tmp"), json, "test".into(), None).unwrap()
+    };
+    let without = GitCommitDocument::new().with_text("feat: add a thing\n".into());
+    let with = GitCommitDocument::new().with_text("feat: ✨ add a thing\n".into());
+    let is_gitmoji = |lint: &lsp_types::Diagnostic| {
+        lint.code
+            == Some(lsp_types::NumberOrString::String(
+                base::document::linting::default::SUBJECT_GITMOJI.into(),
+            ))
+    };
+
+    let cfg = load("{}");
+    assert!(!cfg.gitmoji());
+    assert!(!cfg.lint(&without).iter().any(is_gitmoji));
+
+    let cfg = load(r#"{"gitmoji": {}}"#);
+    assert!(cfg.gitmoji());
+    assert!(!cfg.lint(&without).iter().any(is_gitmoji));
+
+    let cfg = load(r#"{"gitmoji": {"severity": "error"}}"#);
+    assert!(cfg.gitmoji());
+    let lints: Vec<_> = cfg.lint(&without).into_iter().filter(is_gitmoji).collect();
+    assert_eq!(lints.len(), 1);
+    assert_eq!(
+        lints[0].severity,
+        Some(lsp_types::DiagnosticSeverity::ERROR)
+    );
+    assert!(!cfg.lint(&with).iter().any(is_gitmoji));
+}
+
+#[test]
+fn test_apply_settings() {
+    use base::{config::Config as _, document::linting::LintConfig};
+    let json: json_ish::JsonConfig =
+        serde_json::from_str(r#"{"profiles": {"release": {}}}"#).unwrap();
+    let mut cfg = Config::from_json(Path::new("/tmp"), json, "test".into(), None).unwrap();
+    assert_eq!(cfg.max_subject_length(), 50);
+    cfg.apply_settings(&base::config::ClientSettings {
+        max_subject_length: Some(72),
+        enabled_lints: Some(vec!["header_max_line_length".into()]),
+    });
+    let doc = GitCommitDocument::new().with_text(format!("feat: {}\n", "a".repeat(60)));
+    assert_eq!(cfg.enabled_lint_codes(), vec!["header_max_line_length"]);
+    assert_eq!(cfg.max_subject_length(), 72);
+    assert!(cfg.lint(&doc).is_empty());
+    let profile = cfg.profile("release").unwrap();
+    assert_eq!(profile.enabled_lint_codes(), vec!["header_max_line_length"]);
+    assert!(profile.lint(&doc).is_empty());
+}
+
+#[test]
+fn test_source() {
+    use base::document::linting::{LintConfig, DEFAULT_SOURCE};
+    let dir = base::git::TempRepo::new("source");
+    let unconfigured = Config::new(&dir).map(|cfg| cfg.source().to_owned());
+    let file = dir.write("commit_convention.json", "{}");
+    let configured = Config::new(&dir).map(|cfg| cfg.source().to_owned());
+
+    assert_eq!(unconfigured.unwrap(), DEFAULT_SOURCE);
+    assert_eq!(configured.unwrap(), file.to_string_lossy());
+}
+
+#[test]
+fn test_scope_required() {
+    use base::document::linting::LintConfig;
+    let dir = base::git::TempRepo::new("scope-req");
+    let check = |extra: &str| -> Vec<Vec<String>> {
+        let config = format!(r#"{{"scopes": {{"api": "the API", "a": "one letter"}}{extra}}}"#);
+        dir.write("commit_convention.json", &config);
+        let cfg = Config::new(&dir).unwrap();
+        ["feat(api): x", "feat(a)!: x", "feat(db): x", "feat: x"]
+            .into_iter()
+            .map(|text| {
+                let doc = GitCommitDocument::new().with_text(text.into());
+                cfg.lint(&doc)
+                    .into_iter()
+                    .filter_map(|lint| match lint.code {
+                        Some(lsp_types::NumberOrString::String(code)) => Some(code),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect()
+    };
+    let optional = check("");
+    let required = check(r#", "scope_required": true"#);
+    let overridden = check(r#", "scope_required": false, "missing_scope": {"severity": "error"}"#);
+
+    let (none, invalid, missing) = (vec![], vec![SCOPE_ENUM], vec![crate::lints::MISSING_SCOPE]);
+    assert_eq!(
+        optional,
+        vec![none.clone(), none.clone(), invalid.clone(), none.clone()]
+    );
+    assert_eq!(
+        required,
+        vec![none.clone(), none.clone(), invalid.clone(), missing]
+    );
+    assert_eq!(overridden, vec![none.clone(), none.clone(), invalid, none]);
+}
+
+#[test]
+fn test_line_ending() {
+    use base::document::linting::{utils, LintConfig};
+    let dir = base::git::TempRepo::new("line-ending");
+    let fix_text = |line_ending: &str| -> String {
+        let config = format!(r#"{{"revert_type": {{}}{line_ending}}}"#);
+        dir.write("commit_convention.json", &config);
+        let cfg = Config::new(&dir).unwrap();
+        let doc = GitCommitDocument::new().with_text(
+            "Revert \"feat: x\"\r\n\r\nThis reverts commit abc1234.\r\n\r\nbecause\r\n".into(),
+        );
+        cfg.lint(&doc)
+            .iter()
+            .filter(|lint| {
+                lint.code
+                    == Some(lsp_types::NumberOrString::String(
+                        crate::lints::REVERT_TYPE.into(),
+                    ))
+            })
+            .filter_map(utils::get_fix)
+            .flatten()
+            .map(|edit| edit.new_text)
+            .collect()
+    };
+    let auto = fix_text("");
+    let lf = fix_text(r#", "line_ending": "lf""#);
+    assert!(auto.contains("\r\n\r\nRefs: abc1234"), "{auto:?}");
+    assert!(
+        lf.contains("\n\nRefs: abc1234") && !lf.contains('\r'),
+        "{lf:?}"
+    );
+}
+
+#[test]
+fn test_suggestions_only() {
+    use base::document::linting::LintConfig;
+    let dir = base::git::TempRepo::new("suggestions");
+    let severities = |config: &str| -> Vec<lsp_types::DiagnosticSeverity> {
+        dir.write("commit_convention.json", config);
+        let cfg = Config::new(&dir).unwrap();
+        let doc = GitCommitDocument::new().with_text("feat():\nbody\n".into());
+        cfg.lint(&doc)
             .iter()
-            .map(|(scope, doc)| (scope.to_owned(), doc.to_owned()))
+            .map(|lint| lint.severity.unwrap())
+            .collect()
+    };
+    let default = severities("{}");
+    let clamped = severities(r#"{"suggestions_only": true}"#);
+    assert!(
+        default.contains(&lsp_types::DiagnosticSeverity::ERROR),
+        "{default:?}"
+    );
+    assert_eq!(clamped.len(), default.len());
+    assert!(
+        clamped.iter().all(
+            |severity| *severity == lsp_types::DiagnosticSeverity::INFORMATION
+                || *severity == lsp_types::DiagnosticSeverity::HINT
+        ),
+        "{clamped:?}"
+    );
+}
+
+#[test]
+fn test_case_rules() {
+    use base::document::linting::LintConfig;
+    let dir = base::git::TempRepo::new("case");
+    let codes = |config: &str| -> Vec<String> {
+        dir.write("commit_convention.json", config);
+        let cfg = Config::new(&dir).unwrap();
+        let doc = GitCommitDocument::new().with_text("Feat(uiKit): Add a thing\n".into());
+        cfg.lint(&doc)
+            .iter()
+            .filter_map(|lint| match &lint.code {
+                Some(lsp_types::NumberOrString::String(code)) if code.ends_with("_case") => {
+                    Some(code.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    };
+    let unset = codes("{}");
+    let configured = codes(
+        r#"{
+            "type_case": {"severity": "warning", "case": "lower"},
+            "scope_case": {"severity": "warning", "case": "kebab"},
+            "subject_case": {"severity": "warning", "case": "sentence"}
+        }"#,
+    );
+    assert!(unset.is_empty(), "{unset:?}");
+    assert_eq!(configured, vec!["type_case", "scope_case"]);
+}
+
+#[test]
+fn test_profiles() {
+    let dir = base::git::TempRepo::new("profiles");
+    let types = |config: &str, directive: &str| -> Vec<String> {
+        dir.write("commit_convention.json", config);
+        #[allow(clippy::arc_with_non_send_sync)] // the test never leaves its thread
+        let cfg: Arc<dyn base::config::Config> = Arc::new(Config::new(&dir).unwrap());
+        let doc = GitCommitDocument::new().with_text(format!("feat: x\n\n{directive}"));
+        base::config::with_profile(cfg, &doc)
+            .type_suggestions()
+            .into_iter()
+            .map(|(type_, _)| type_)
+            .collect()
+    };
+    let config = r#"{
+        "types": {"top": "top-level"},
+        "profiles": {
+            "release": {"types": {"release": "cuts a release"}},
+            "default": {"types": {"feat": "a feature"}}
+        }
+    }"#;
+    let release = types(config, "# cconvention-profile: release\n");
+    let default = types(config, "");
+    let unknown = types(config, "# cconvention-profile: missing\n");
+    let no_default = types(r#"{"types": {"top": "top-level"}}"#, "");
+    assert_eq!(release, vec!["release"]);
+    assert_eq!(default, vec!["feat"]);
+    assert_eq!(unknown, vec!["top"]);
+    assert_eq!(no_default, vec!["top"]);
+}
+
+#[test]
+fn test_scope_required_when_breaking() {
+    use base::document::linting::LintConfig;
+    let dir = base::git::TempRepo::new("breaking");
+    let check = |config: &str| -> Vec<bool> {
+        dir.write("commit_convention.json", config);
+        let cfg = Config::new(&dir).unwrap();
+        ["feat!: x", "feat(api)!: x", "feat: x"]
+            .into_iter()
+            .map(|text| {
+                let doc = GitCommitDocument::new().with_text(text.into());
+                cfg.lint(&doc).iter().any(|lint| {
+                    lint.code
+                        == Some(lsp_types::NumberOrString::String(
+                            crate::lints::BREAKING_SCOPE.into(),
+                        ))
+                })
+            })
             .collect()
+    };
+    let flagged = check(r#"{"scope_required_when_breaking": true}"#);
+    let unflagged = check("{}");
+    assert_eq!(flagged, vec![true, false, false]);
+    assert_eq!(unflagged, vec![false, false, false]);
+}
+
+#[test]
+fn test_semver_override() {
+    use base::config::{Config as _, SemverBump};
+    let dir = base::git::TempRepo::new("semver");
+    dir.write(
+        "commit_convention.json",
+        r#"{"semver": {"refactor": "patch", "perf": "none"}}"#,
+    );
+    let cfg = Config::new(&dir).unwrap();
+    assert_eq!(cfg.semver_bump("refactor"), SemverBump::Patch);
+    assert_eq!(cfg.semver_bump("perf"), SemverBump::None);
+    assert_eq!(cfg.semver_bump("feat"), SemverBump::Minor);
+}
+
+#[test]
+fn test_scope_aliases() {
+    use base::config::Config as _;
+    use base::document::linting::LintConfig;
+    let dir = base::git::TempRepo::new("aliases");
+    let load = |config: &str| {
+        dir.write("commit_convention.json", config);
+        Config::new(&dir)
+    };
+    let cfg = load(r#"{"scopes": {"ui": "the UI"}, "scope_aliases": {"fe": "ui"}}"#);
+    let unknown_target = load(r#"{"scopes": {"ui": "the UI"}, "scope_aliases": {"fe": "web"}}"#);
+    let chained = load(r#"{"scope_aliases": {"fe": "frontend", "frontend": "ui"}}"#);
+    let cfg = cfg.unwrap();
+    let scope_enum_lints = |text: &str| {
+        let doc = GitCommitDocument::new().with_text(text.into());
+        cfg.lint(&doc)
+            .into_iter()
+            .filter(|lint| lint.code == Some(lsp_types::NumberOrString::String(SCOPE_ENUM.into())))
+            .count()
+    };
+    assert_eq!(scope_enum_lints("feat(fe): x"), 0);
+    assert_eq!(scope_enum_lints("feat(ui): x"), 0);
+    assert_eq!(scope_enum_lints("feat(web): x"), 1);
+    assert_eq!(scope_enum_lints("feat[ui]: x"), 0);
+    assert_eq!(scope_enum_lints("feat[web]: x"), 1);
+    let options = cfg.format_options();
+    assert!(!options.parenthesize_bracket_scopes);
+    assert_eq!(
+        options.scope_aliases.get("fe").map(String::as_str),
+        Some("ui")
+    );
+    let scopes: Vec<String> = cfg
+        .scope_suggestions()
+        .into_iter()
+        .map(|(s, _)| s)
+        .collect();
+    assert_eq!(scopes, vec!["ui"]);
+    assert!(unknown_target.is_err());
+    assert!(chained.is_err());
+}
+
+#[test]
+fn test_malformed_config() {
+    use base::config::Config as _;
+    use base::document::linting::LintConfig;
+    let dir = base::git::TempRepo::new("malformed");
+    let file = dir.write(
+        "commit_convention.json",
+        "{\n  \"scopes\": {\"api\": }\n}\n",
+    );
+    let cfg = Config::new(&dir).unwrap();
+    let errors = cfg.load_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].path, file);
+    assert_eq!(errors[0].range.start, lsp_types::Position::new(1, 20));
+    assert!(errors[0]
+        .to_string()
+        .contains("commit_convention.json:2:21: invalid config"));
+    // the defaults still lint
+    let doc = GitCommitDocument::new().with_text("feat(): x\n".into());
+    assert!(!cfg.lint(&doc).is_empty());
+}
+
+#[test]
+fn test_definitions() {
+    use base::config::Config as _;
+    let dir = base::git::TempRepo::new("definitions");
+    let file = dir.write(
+        "commit_convention.json",
+        r#"{
+  "types": {
+    "feat": "adds a feature",
+    "fix": "fixes a bug"
+  },
+  "scopes": {"api": "the API", "ui": "the UI"},
+  "scope_aliases": {"fe": "ui"}
+}"#,
+    );
+    let cfg = Config::new(&dir).unwrap();
+    let fix = cfg.type_definition("fix").unwrap();
+    assert_eq!(fix.uri, lsp_types::Url::from_file_path(&file).unwrap());
+    assert_eq!(
+        fix.range,
+        lsp_types::Range::new(
+            lsp_types::Position::new(3, 5),
+            lsp_types::Position::new(3, 8)
+        )
+    );
+    let ui = cfg.scope_definition("ui").unwrap();
+    assert_eq!(ui.range.start, lsp_types::Position::new(5, 32));
+    // aliases jump to their canonical scope
+    assert_eq!(cfg.scope_definition("fe"), Some(ui));
+    assert_eq!(cfg.type_definition("chore"), None);
+}
+
+#[test]
+fn test_env_overrides() {
+    use base::document::linting::{default, LintConfig};
+    let json: json_ish::JsonConfig = serde_json::from_str("{}").unwrap();
+    let mut cfg = Config::from_json(Path::new("/tmp"), json, "test".into(), None).unwrap();
+    assert!(cfg.enabled_lint_codes().contains(&default::SUBJECT_EMPTY));
+    let vars = [
+        ("GIT_CC_LS_DISABLE_LINTS", default::SUBJECT_EMPTY),
+        ("GIT_CC_LS_SEVERITY_SCOPE_EMPTY", "hint"),
+    ];
+    cfg.apply_env_overrides(&base::config::EnvLintOverrides::from_vars(
+        vars.map(|(name, value)| (name.into(), value.into())),
+    ));
+    assert!(!cfg.enabled_lint_codes().contains(&default::SUBJECT_EMPTY));
+    assert_eq!(
+        cfg.lint_severity(default::SCOPE_EMPTY),
+        &lsp_types::DiagnosticSeverity::HINT
+    );
+}
+
+#[test]
+fn test_scope_history() {
+    use base::config::Config as _;
+    let dir = base::git::TempRepo::new("history");
+    for (subject, text) in [("feat(api): x", "x"), ("feat(ui): y", "y")] {
+        dir.write("ui/a.txt", text);
+        dir.git(&["add", "ui/a.txt"]);
+        dir.commit(&["--message", subject]);
     }
+    dir.write("ui/a.txt", "z");
+    dir.git(&["add", "ui/a.txt"]);
+    let suggest = |config: &str| {
+        let json: json_ish::JsonConfig = serde_json::from_str(config).unwrap();
+        let cfg = Config::from_json(&dir, json, "test".into(), None).unwrap();
+        cfg.scope_suggestions()
+    };
+    let scopes = r#""scopes": {"api": "the API", "ui": "the UI"}"#;
+    let ranked = suggest(&format!("{{{scopes}}}"));
+    let latest_only = suggest(&format!("{{{scopes}, \"scope_history_limit\": 1}}"));
+    let unranked = suggest(&format!(
+        "{{{scopes}, \"scope_suggest_from_history\": false}}"
+    ));
+
+    let used_in_ui = |suggestions: &[(String, String)]| -> Vec<String> {
+        suggestions
+            .iter()
+            .filter(|(_, doc)| doc.ends_with("(matches staged files in ui/)"))
+            .map(|(scope, _)| scope.clone())
+            .collect()
+    };
+    assert_eq!(used_in_ui(&ranked), vec!["ui", "api"]);
+    assert_eq!(used_in_ui(&latest_only), vec!["ui"]);
+    // without scanning history, only the configured scopes are suggested, as configured
+    assert_eq!(
+        unranked,
+        vec![
+            ("api".to_string(), "the API".to_string()),
+            ("ui".to_string(), "the UI".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_scope_suggestion_ttl() {
+    use base::config::Config as _;
+    let dir = base::git::TempRepo::new("ttl");
+    let stage = |path: &str| {
+        dir.write(path, "x");
+        dir.git(&["add", path]);
+    };
+    let load = |config: &str| {
+        let json: json_ish::JsonConfig = serde_json::from_str(config).unwrap();
+        Config::from_json(&dir, json, "test".into(), None).unwrap()
+    };
+    let first = |cfg: &Config| cfg.scope_suggestions().remove(0).0;
+    let cached = load(r#"{"scope_directory": {"depth": 1}}"#);
+    let uncached = load(r#"{"scope_directory": {"depth": 1}, "scope_suggestion_ttl_ms": 0}"#);
+    stage("ui/a.txt");
+    let before = (first(&cached), first(&uncached));
+    dir.git(&["rm", "--cached", "--quiet", "ui/a.txt"]);
+    stage("api/b.txt");
+    // the cached suggestions don't ask git what's staged again
+    let after = (first(&cached), first(&uncached));
+
+    assert_eq!(before, ("ui".to_string(), "ui".to_string()));
+    assert_eq!(after, ("ui".to_string(), "api".to_string()));
 }
@@ -0,0 +1,236 @@
+// © Steven Kalt
+// SPDX-License-Identifier: Polyform-Noncommercial-1.0.0 OR LicenseRef-PolyForm-Free-Trial-1.0.0
+
+//! Lint functions loaded from shared-object (`cdylib`) plugins.
+//!
+//! ## ABI (version 1)
+//!
+//! A plugin must export the following C-ABI symbols:
+//!
+//! ```c
+//! // must return CCONVENTION_PLUGIN_ABI_VERSION (currently 1)
+//! uint32_t cconvention_plugin_abi_version(void);
+//! // lint a NUL-terminated UTF-8 JSON document, returning a NUL-terminated UTF-8 JSON array of
+//! // LSP diagnostics, or NULL on failure. The returned pointer is owned by the plugin.
+//! char *cconvention_lint(const char *document);
+//! // release a pointer returned by `cconvention_lint`
+//! void cconvention_free(char *diagnostics);
+//! ```
+//!
+//! The input document is a JSON object:
+//!
+//! ```json
+//! {"abi_version": 1, "code": "<lint code>", "text": "<commit message>", "syntax_tree": "<s-expression>"}
+//! ```
+//!
+//! The output is a JSON array of [LSP `Diagnostic`s][diagnostic]. Diagnostics without a `code`
+//! are assigned the plugin's configured code; diagnostics without a severity get the configured
+//! severity like any other lint.
+//!
+//! Only JSON crosses the boundary so that plugins don't need to match this crate's Rust ABI or
+//! dependency versions. See `../../examples/dylib_plugin.rs` for a sample plugin.
+//!
+//! ## Safety
+//!
+//! Loading a shared object runs arbitrary code with the server's privileges: only configure
+//! plugins you trust. Plugins must not unwind across the FFI boundary, must return valid UTF-8,
+//! and must not retain the input pointer after `cconvention_lint` returns. Plugins are never
+//! unloaded while a config referencing them is alive.
+//!
+//! [diagnostic]: https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#diagnostic
+use std::{
+    ffi::{c_char, CStr, CString},
+    path::Path,
+    sync::Arc,
+};
+
+use base::{document::GitCommitDocument, log_debug};
+
+/// the version of the plugin ABI described in this module's docs
+pub const CCONVENTION_PLUGIN_ABI_VERSION: u32 = 1;
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type LintFn = unsafe extern "C" fn(*const c_char) -> *mut c_char;
+type FreeFn = unsafe extern "C" fn(*mut c_char);
+
+/// a loaded plugin, holding its library open for as long as its lint-fn lives
+struct Plugin {
+    library: libloading::Library,
+}
+
+impl Plugin {
+    fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        // SAFETY: see the module-level docs; configuring a plugin opts into running its code.
+        let library = unsafe { libloading::Library::new(path) }
+            .map_err(|e| format!("unable to load plugin {:?}: {}", path, e))?;
+        let version = unsafe {
+            let abi_version: libloading::Symbol<AbiVersionFn> = library
+                .get(b"cconvention_plugin_abi_version\0")
+                .map_err(|e| format!("{:?} is not a cconvention plugin: {}", path, e))?;
+            abi_version()
+        };
+        if version != CCONVENTION_PLUGIN_ABI_VERSION {
+            return Err(format!(
+                "plugin {:?} uses ABI version {}; expected {}",
+                path, version, CCONVENTION_PLUGIN_ABI_VERSION
+            )
+            .into());
+        }
+        Ok(Plugin { library })
+    }
+
+    fn lint(
+        &self,
+        doc: &GitCommitDocument,
+        code: &str,
+    ) -> Result<Vec<lsp_types::Diagnostic>, Box<dyn std::error::Error + Send + Sync>> {
+        let input = CString::new(serde_json::to_string(&serde_json::json!({
+            "abi_version": CCONVENTION_PLUGIN_ABI_VERSION,
+            "code": code,
+            "text": doc.code.to_string(),
+            "syntax_tree": doc.syntax_tree.root_node().to_sexp(),
+        }))?)?;
+        let output = unsafe {
+            let lint: libloading::Symbol<LintFn> = self.library.get(b"cconvention_lint\0")?;
+            let free: libloading::Symbol<FreeFn> = self.library.get(b"cconvention_free\0")?;
+            let ptr = lint(input.as_ptr());
+            if ptr.is_null() {
+                return Err(format!("plugin {} failed", code).into());
+            }
+            let output = CStr::from_ptr(ptr).to_str().map(|s| s.to_owned());
+            free(ptr);
+            output?
+        };
+        let mut diagnostics: Vec<lsp_types::Diagnostic> = serde_json::from_str(&output)?;
+        for diagnostic in diagnostics.iter_mut() {
+            if diagnostic.code.is_none() {
+                diagnostic.code = Some(lsp_types::NumberOrString::String(code.to_owned()));
+            }
+        }
+        Ok(diagnostics)
+    }
+}
+
+/// load the plugin at `path`, returning a lint-fn that reports under `code`
+pub(crate) fn load(
+    path: &Path,
+    code: String,
+) -> Result<Arc<base::document::linting::LintFn<'static>>, Box<dyn std::error::Error + Send + Sync>>
+{
+    let plugin = Plugin::load(path)?;
+    Ok(Arc::new(move |doc| {
+        plugin.lint(doc, &code).unwrap_or_else(|_e| {
+            log_debug!("plugin {} failed: {}", code, _e);
+            vec![]
+        })
+    }))
+}
+
+/// compile `source` into a shared object in `dir`, like a third-party plugin would be built
+#[cfg(test)]
+fn build_plugin(dir: &Path, name: &str, source: &str) -> std::path::PathBuf {
+    let src = dir.join(format!("{}.rs", name));
+    std::fs::write(&src, source).unwrap();
+    let out = dir.join(format!(
+        "{}{}{}",
+        std::env::consts::DLL_PREFIX,
+        name,
+        std::env::consts::DLL_SUFFIX
+    ));
+    let status = std::process::Command::new(std::env::var("RUSTC").unwrap_or("rustc".into()))
+        .args([
+            "--edition=2021",
+            "--crate-type=cdylib",
+            "--crate-name",
+            name,
+            "-o",
+        ])
+        .arg(&out)
+        .arg(&src)
+        .status()
+        .unwrap();
+    assert!(status.success(), "unable to compile {}", name);
+    out
+}
+
+#[test]
+fn test_plugin_load_errors() {
+    let dir = base::git::TempRepo::new("dylib-load");
+    let missing = build_plugin(
+        &dir,
+        "missing",
+        "#[no_mangle]\npub extern \"C\" fn unrelated() {}\n",
+    );
+    let err = Plugin::load(&missing).err().unwrap().to_string();
+    assert!(err.contains("is not a cconvention plugin"), "{}", err);
+
+    let future = build_plugin(
+        &dir,
+        "future",
+        "#[no_mangle]\npub extern \"C\" fn cconvention_plugin_abi_version() -> u32 { 2 }\n",
+    );
+    let err = Plugin::load(&future).err().unwrap().to_string();
+    assert!(err.ends_with("uses ABI version 2; expected 1"), "{}", err);
+
+    let err = Plugin::load(&dir.join("nonexistent.so"))
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.starts_with("unable to load plugin"), "{}", err);
+}
+
+#[test]
+fn test_plugin_lint() {
+    let dir = base::git::TempRepo::new("dylib-lint");
+    let path = build_plugin(
+        &dir,
+        "fixed",
+        r##"
+use std::ffi::{c_char, CStr, CString};
+#[no_mangle]
+pub extern "C" fn cconvention_plugin_abi_version() -> u32 { 1 }
+#[no_mangle]
+pub unsafe extern "C" fn cconvention_lint(document: *const c_char) -> *mut c_char {
+    let input = CStr::from_ptr(document).to_str().unwrap();
+    if input.contains("FAIL") {
+        return std::ptr::null_mut();
+    }
+    let range = r#"{"start":{"line":0,"character":0},"end":{"line":0,"character":4}}"#;
+    let output = format!(
+        r#"[{{"range":{r},"message":"mine","code":"custom"}},{{"range":{r},"message":"default","severity":2}}]"#,
+        r = range
+    );
+    CString::new(output).unwrap().into_raw()
+}
+#[no_mangle]
+pub unsafe extern "C" fn cconvention_free(diagnostics: *mut c_char) {
+    drop(CString::from_raw(diagnostics));
+}
+"##,
+    );
+    let plugin = Plugin::load(&path).unwrap();
+    let doc = GitCommitDocument::new().with_text("feat: add a thing".into());
+    let summary: Vec<_> = plugin
+        .lint(&doc, "my_plugin")
+        .unwrap()
+        .into_iter()
+        .map(|d| (d.message, d.code, d.severity, d.range.end.character))
+        .collect();
+    let code = |c: &str| Some(lsp_types::NumberOrString::String(c.into()));
+    assert_eq!(
+        summary,
+        vec![
+            ("mine".to_string(), code("custom"), None, 4),
+            (
+                "default".to_string(),
+                code("my_plugin"),
+                Some(lsp_types::DiagnosticSeverity::WARNING),
+                4
+            ),
+        ]
+    );
+
+    let doc = GitCommitDocument::new().with_text("feat: FAIL".into());
+    let err = plugin.lint(&doc, "my_plugin").err().unwrap().to_string();
+    assert_eq!(err, "plugin my_plugin failed");
+}
@@ -0,0 +1,418 @@
+// © Steven Kalt
+// SPDX-License-Identifier: Polyform-Noncommercial-1.0.0 OR LicenseRef-PolyForm-Free-Trial-1.0.0
+
+//! Check that issues referenced in a message's footer (e.g. `Fixes #123`) exist and are open.
+//!
+//! Looking up issues is delegated to an [`IssueResolver`]. Lookups must never block linting:
+//! resolvers that hit the network do so in the background, reporting [`IssueState::Unknown`] until
+//! an answer arrives, and failures are reported as hints rather than errors.
+use std::error::Error;
+
+use base::document::{linting::utils, GitCommitDocument};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueState {
+    Open,
+    Closed,
+    Missing,
+    /// the resolver can't tell, e.g. because it's a no-op
+    Unknown,
+}
+
+/// looks up the state of an issue by number
+pub trait IssueResolver {
+    fn resolve(&self, issue: u64) -> Result<IssueState, Box<dyn Error + Send + Sync>>;
+}
+
+/// the default resolver, which never looks anything up
+pub struct NoopResolver;
+impl IssueResolver for NoopResolver {
+    fn resolve(&self, _issue: u64) -> Result<IssueState, Box<dyn Error + Send + Sync>> {
+        Ok(IssueState::Unknown)
+    }
+}
+
+/// the footer keys whose values reference issues. `Refs` isn't included since it may point at
+/// closed or historical issues.
+pub const DEFAULT_REFERENCE_KEYS: &[&str] = &[
+    "Close", "Closes", "Closed", "Fix", "Fixes", "Fixed", "Resolve", "Resolves", "Resolved",
+];
+
+/// find `#123`-style references on footer lines starting with one of `keys`, returning
+/// `(line_number, start_char, end_char, issue_number)` for each. A `#` only starts a reference
+/// after whitespace, a comma, or the key's `:`, so references to other repositories like
+/// `org/repo#12` and URL fragments aren't mistaken for this repository's issues.
+fn find_references(doc: &GitCommitDocument, keys: &[String]) -> Vec<(usize, u32, u32, u64)> {
+    let mut references = vec![];
+    let mut body: Vec<(usize, String)> = doc
+        .get_body()
+        .map(|(line_number, line)| (line_number, line.to_string()))
+        .collect();
+    while body.last().is_some_and(|(_, line)| line.trim().is_empty()) {
+        body.pop();
+    }
+    // the footer is the body's last paragraph; prose before it isn't parsed for references
+    let footer = body
+        .iter()
+        .rposition(|(_, line)| line.trim().is_empty())
+        .map(|blank| &body[blank + 1..])
+        .unwrap_or_default();
+    for (line_number, line) in footer {
+        let line_number = *line_number;
+        let key = line
+            .split(|c: char| c == ':' || c.is_whitespace())
+            .next()
+            .unwrap_or_default();
+        if key.is_empty() || !keys.iter().any(|k| k.eq_ignore_ascii_case(key)) {
+            continue;
+        }
+        let chars: Vec<char> = line.chars().collect();
+        let key_len = key.chars().count();
+        let mut i = key_len;
+        while i < chars.len() {
+            let prev = chars[i - 1];
+            let standalone =
+                prev.is_whitespace() || prev == ',' || (prev == ':' && i == key_len + 1);
+            if chars[i] == '#' && standalone {
+                let digits: String = chars[i + 1..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_digit())
+                    .collect();
+                if let Ok(number) = digits.parse() {
+                    let end = i + 1 + digits.len();
+                    references.push((line_number, i as u32, end as u32, number));
+                    i = end;
+                    continue;
+                }
+            }
+            i += 1;
+        }
+    }
+    references
+}
+
+/// Flag references to issues that are closed or don't exist.
+pub fn check_reference_exists(
+    doc: &GitCommitDocument,
+    code: &str,
+    keys: &[String],
+    resolver: &dyn IssueResolver,
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    for (line_number, start, end, number) in find_references(doc, keys) {
+        let (message, severity) = match resolver.resolve(number) {
+            Ok(IssueState::Open) | Ok(IssueState::Unknown) => continue,
+            Ok(IssueState::Closed) => (format!("Issue #{number} is already closed."), None),
+            Ok(IssueState::Missing) => (format!("Issue #{number} does not exist."), None),
+            Err(e) => (
+                format!("Unable to check issue #{number}: {e}"),
+                // degrade to a hint rather than blocking the commit
+                Some(lsp_types::DiagnosticSeverity::HINT),
+            ),
+        };
+        let mut lint = utils::make_line_diagnostic(message, line_number, start, end);
+        lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+        lint.severity = severity;
+        lints.push(lint);
+    }
+    lints
+}
+
+#[cfg(feature = "forge_api")]
+pub(crate) mod forge {
+    //! Look up issues via GitHub's or GitLab's REST API, caching results on disk.
+    //!
+    //! Requests are made by shelling out to `curl`, the way the rest of this crate shells out to
+    //! `git`, to avoid pulling an HTTP stack into the server. Each uncached issue is fetched on its
+    //! own thread; until it finishes, the issue resolves to [`IssueState::Unknown`] and the next
+    //! lint picks up the result.
+    use std::{
+        collections::{HashMap, HashSet},
+        error::Error,
+        path::PathBuf,
+        sync::{Arc, Mutex},
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use serde::Deserialize;
+
+    use super::{IssueResolver, IssueState};
+
+    /// how long to remember a failed lookup, so offline or rate-limited users don't re-run `curl`
+    /// on every keystroke
+    pub(crate) const FAILURE_TTL_SECS: u64 = 60;
+    /// marks a cached failure's state, followed by the error message
+    const FAILED: &str = "failed: ";
+
+    #[derive(Deserialize, Debug, Clone, Copy)]
+    #[serde(rename_all = "lowercase")]
+    pub(crate) enum Forge {
+        GitHub,
+        GitLab,
+    }
+
+    /// the state shared with background lookups
+    pub(crate) struct ForgeResolver(Arc<Shared>);
+
+    struct Shared {
+        forge: Forge,
+        /// `owner/name` on GitHub, or the URL-encoded project path on GitLab
+        repo: String,
+        api_url: String,
+        token: Option<String>,
+        ttl_secs: u64,
+        cache_file: Option<PathBuf>,
+        /// issue key => (state, fetched-at unix seconds)
+        cache: Mutex<HashMap<String, (String, u64)>>,
+        /// the keys of issues being fetched
+        pending: Mutex<HashSet<String>>,
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default()
+    }
+
+    fn state_to_str(state: IssueState) -> &'static str {
+        match state {
+            IssueState::Open => "open",
+            IssueState::Closed => "closed",
+            IssueState::Missing => "missing",
+            IssueState::Unknown => "unknown",
+        }
+    }
+
+    fn state_from_str(state: &str) -> IssueState {
+        match state {
+            "open" | "opened" => IssueState::Open,
+            "closed" => IssueState::Closed,
+            "missing" => IssueState::Missing,
+            _ => IssueState::Unknown,
+        }
+    }
+
+    impl ForgeResolver {
+        pub(crate) fn new(
+            forge: Forge,
+            repo: String,
+            api_url: Option<String>,
+            token: Option<String>,
+            ttl_secs: u64,
+        ) -> Self {
+            let cache_file = base::config::cache_dir().map(|dir| dir.join("issues.json"));
+            Self::with_cache_file(forge, repo, api_url, token, ttl_secs, cache_file)
+        }
+
+        fn with_cache_file(
+            forge: Forge,
+            repo: String,
+            api_url: Option<String>,
+            token: Option<String>,
+            ttl_secs: u64,
+            cache_file: Option<PathBuf>,
+        ) -> Self {
+            let api_url = api_url.unwrap_or_else(|| {
+                match forge {
+                    Forge::GitHub => "https://api.github.com",
+                    Forge::GitLab => "https://gitlab.com/api/v4",
+                }
+                .to_owned()
+            });
+            let cache = cache_file
+                .as_ref()
+                .and_then(|file| std::fs::read_to_string(file).ok())
+                .and_then(|text| serde_json::from_str(&text).ok())
+                .unwrap_or_default();
+            ForgeResolver(Arc::new(Shared {
+                forge,
+                repo,
+                api_url,
+                token,
+                ttl_secs,
+                cache_file,
+                cache: Mutex::new(cache),
+                pending: Mutex::new(HashSet::new()),
+            }))
+        }
+
+        /// the cached answer for `key`, if it's still fresh
+        fn cached(&self, key: &str) -> Option<Result<IssueState, Box<dyn Error + Send + Sync>>> {
+            let cache = self.0.cache.lock().unwrap_or_else(|e| e.into_inner());
+            let (state, fetched_at) = cache.get(key)?;
+            let age = now().saturating_sub(*fetched_at);
+            match state.strip_prefix(FAILED) {
+                Some(error) if age < FAILURE_TTL_SECS => Some(Err(error.into())),
+                None if age < self.0.ttl_secs => Some(Ok(state_from_str(state))),
+                _ => None,
+            }
+        }
+    }
+
+    impl Shared {
+        fn cache_key(&self, issue: u64) -> String {
+            format!(
+                "{}#{}",
+                self.api_url.trim_end_matches('/'),
+                self.issue_path(issue)
+            )
+        }
+
+        fn issue_path(&self, issue: u64) -> String {
+            match self.forge {
+                Forge::GitHub => format!("/repos/{}/issues/{}", self.repo, issue),
+                Forge::GitLab => format!("/projects/{}/issues/{}", self.repo, issue),
+            }
+        }
+
+        fn fetch(&self, issue: u64) -> Result<IssueState, Box<dyn Error + Send + Sync>> {
+            let url = format!(
+                "{}{}",
+                self.api_url.trim_end_matches('/'),
+                self.issue_path(issue)
+            );
+            let mut cmd = std::process::Command::new("curl");
+            cmd.args(["--silent", "--show-error", "--max-time", "5"])
+                .args(["--write-out", "\n%{http_code}"]);
+            if let Some(token) = &self.token {
+                cmd.arg("--header").arg(match self.forge {
+                    Forge::GitHub => format!("Authorization: Bearer {token}"),
+                    Forge::GitLab => format!("PRIVATE-TOKEN: {token}"),
+                });
+            }
+            let output = cmd.arg(url).output()?;
+            if !output.status.success() {
+                return Err(String::from_utf8_lossy(&output.stderr)
+                    .trim()
+                    .to_owned()
+                    .into());
+            }
+            let stdout = String::from_utf8(output.stdout)?;
+            let (body, status) = stdout.rsplit_once('\n').unwrap_or(("", &stdout));
+            match status.trim() {
+                "200" => {
+                    let issue: serde_json::Value = serde_json::from_str(body)?;
+                    Ok(state_from_str(issue["state"].as_str().unwrap_or_default()))
+                }
+                "404" | "410" => Ok(IssueState::Missing),
+                "403" | "429" => Err("rate-limited by the forge's API".into()),
+                other => Err(format!("unexpected HTTP status {other}").into()),
+            }
+        }
+
+        /// fetch `issue` and cache the answer, including failures
+        fn refresh(&self, issue: u64, key: String) {
+            let state = match self.fetch(issue) {
+                Ok(state) => state_to_str(state).to_owned(),
+                Err(e) => format!("{FAILED}{e}"),
+            };
+            let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+            cache.insert(key.clone(), (state, now()));
+            self.persist(&cache);
+            drop(cache);
+            self.pending
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&key);
+        }
+
+        fn persist(&self, cache: &HashMap<String, (String, u64)>) {
+            if let Some(file) = &self.cache_file {
+                // a stale or missing cache is only a performance problem, so ignore failures
+                let _ = file.parent().map(std::fs::create_dir_all);
+                if let Ok(text) = serde_json::to_string(cache) {
+                    let _ = std::fs::write(file, text);
+                }
+            }
+        }
+    }
+
+    impl IssueResolver for ForgeResolver {
+        fn resolve(&self, issue: u64) -> Result<IssueState, Box<dyn Error + Send + Sync>> {
+            let key = self.0.cache_key(issue);
+            if let Some(cached) = self.cached(&key) {
+                return cached;
+            }
+            let already_fetching = !self
+                .0
+                .pending
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(key.clone());
+            if !already_fetching {
+                let shared = self.0.clone();
+                std::thread::spawn(move || shared.refresh(issue, key));
+            }
+            Ok(IssueState::Unknown)
+        }
+    }
+
+    #[test]
+    fn test_forge_resolver() {
+        // nothing listens on port 1, so curl fails fast
+        let resolver = ForgeResolver::with_cache_file(
+            Forge::GitHub,
+            "owner/name".into(),
+            Some("http://127.0.0.1:1".into()),
+            None,
+            60,
+            None,
+        );
+        assert_eq!(resolver.resolve(1).unwrap(), IssueState::Unknown);
+        // a second lint while the lookup is in flight doesn't start another
+        assert_eq!(resolver.resolve(1).unwrap(), IssueState::Unknown);
+        assert_eq!(resolver.0.pending.lock().unwrap().len(), 1);
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        while !resolver.0.pending.lock().unwrap().is_empty() {
+            assert!(std::time::Instant::now() < deadline, "curl never finished");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        // the failure is remembered rather than retried on every lint
+        assert!(resolver.resolve(1).is_err());
+        assert!(resolver.0.pending.lock().unwrap().is_empty());
+
+        let mut cache = resolver.0.cache.lock().unwrap();
+        cache.insert(resolver.0.cache_key(2), ("closed".into(), now()));
+        cache.insert(resolver.0.cache_key(3), ("failed: offline".into(), 0));
+        drop(cache);
+        assert_eq!(resolver.resolve(2).unwrap(), IssueState::Closed);
+        // stale failures are retried
+        assert_eq!(resolver.resolve(3).unwrap(), IssueState::Unknown);
+    }
+}
+
+#[test]
+fn test_reference_exists() {
+    struct FakeResolver;
+    impl IssueResolver for FakeResolver {
+        fn resolve(&self, issue: u64) -> Result<IssueState, Box<dyn Error + Send + Sync>> {
+            match issue {
+                1 => Ok(IssueState::Open),
+                2 => Ok(IssueState::Closed),
+                3 => Ok(IssueState::Missing),
+                _ => Err("offline".into()),
+            }
+        }
+    }
+    let keys: Vec<String> = DEFAULT_REFERENCE_KEYS
+        .iter()
+        .map(|k| k.to_string())
+        .collect();
+    let doc = GitCommitDocument::new()
+        .with_text("fix: x\n\nCloses: see below, #5\n\nFixes #1, #2\nResolves: #3 #4\nRefs: #2\nFixes: org/other#3,https://example.com/issues/1#3\n\n# a comment\n".into());
+    let lints = check_reference_exists(&doc, "reference_exists", &keys, &FakeResolver);
+    let summary: Vec<_> = lints
+        .iter()
+        .map(|l| (l.range.start.line, l.range.start.character, l.severity))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (4, 10, None),
+            (5, 10, None),
+            (5, 13, Some(lsp_types::DiagnosticSeverity::HINT)),
+        ]
+    );
+    assert!(check_reference_exists(&doc, "reference_exists", &keys, &NoopResolver).is_empty());
+}
@@ -1,8 +1,13 @@
 // © Steven Kalt
 // SPDX-License-Identifier: Polyform-Noncommercial-1.0.0 OR LicenseRef-PolyForm-Free-Trial-1.0.0
-use base::document::GitCommitDocument;
+use base::document::{linting::utils, GitCommitDocument};
 use base::LANGUAGE;
 use lazy_static::lazy_static;
+#[cfg(feature = "dylib_plugins")]
+pub(crate) mod dylib;
+pub mod issues;
+use serde::Deserialize;
+use std::{collections::HashMap, path::PathBuf, sync::Mutex};
 lazy_static! {
     static ref BODY_QUERY: tree_sitter::Query =
         tree_sitter::Query::new(&LANGUAGE, include_str!("./queries/body.scm")).unwrap();
@@ -12,6 +17,73 @@ lazy_static! {
 pub(crate) const MISSING_BODY: &str = "missing_body";
 pub(crate) const MISSING_DCO: &str = "missing_dco";
 pub(crate) const MISSING_SCOPE: &str = "missing_scope";
+pub(crate) const SCOPE_GLOSSARY: &str = "scope_glossary";
+pub(crate) const CLOSING_KEYWORDS: &str = "closing_keywords";
+pub(crate) const LOCKFILE_ONLY: &str = "lockfile_only";
+pub(crate) const LARGE_DIFF_BODY: &str = "large_diff_body";
+pub(crate) const DEFAULT_LARGE_DIFF_LINES: usize = 200;
+pub(crate) const SUBJECT_FIRST_WORD: &str = "subject_first_word";
+pub(crate) const SCOPE_DIRECTORY: &str = "scope_directory";
+pub(crate) const SUBJECT_CONJUNCTION: &str = "subject_conjunction";
+pub(crate) const TYPE_SCOPE_SWAP: &str = "type_scope_swap";
+pub(crate) const TRAILER_VALUES: &str = "trailer_values";
+pub(crate) const TRAILER_EXISTS: &str = "trailer_exists";
+/// https://commitlint.js.org/#/reference-rules?id=references-empty
+pub(crate) const REFERENCES_EMPTY: &str = "references_empty";
+/// what `references_empty` counts as a reference unless configured otherwise
+pub(crate) const DEFAULT_REFERENCE_PATTERN: &str = r"#\d+";
+pub(crate) const AUTHOR_DOMAIN: &str = "author_domain";
+pub(crate) const SUBJECT_DUPLICATE_RECENT: &str = "subject_duplicate_recent";
+pub(crate) const DEFAULT_RECENT_SUBJECTS: usize = 20;
+pub(crate) const BODY_STYLE: &str = "body_style";
+pub(crate) const REVERT_TYPE: &str = "revert_type";
+pub(crate) const BREAKING_SCOPE: &str = "breaking_scope";
+/// words that start a first-person body, e.g. `I fixed the thing`
+pub(crate) const DEFAULT_FIRST_PERSON_WORDS: &[&str] = &["I", "I'm", "I've", "I'd", "I'll"];
+/// an issue reference: `#123`, `owner/repo#123`, `ABC-123`, or a URL
+const ISSUE_REFERENCE: &str = r"(?:#\d+|[\w.-]+/[\w.-]+#\d+|[A-Z][A-Z0-9]+-\d+|https?://\S+)";
+lazy_static! {
+    /// (trailer key, the pattern its value must match, a description of the expected form)
+    pub(crate) static ref DEFAULT_TRAILER_PATTERNS: Vec<(&'static str, String, &'static str)> = {
+        let person = r"^[^<>]*[^<>\s] <[^<>@\s]+@[^<>\s]+>$".to_owned();
+        let issues = format!(r"^{ISSUE_REFERENCE}(?:,?\s+{ISSUE_REFERENCE})*$");
+        let (as_person, as_issues) = ("`Name <email>`", "issue references like `#123`");
+        vec![
+            ("Signed-off-by", person.clone(), as_person),
+            ("Co-authored-by", person, as_person),
+            ("Refs", issues.clone(), as_issues),
+            ("Closes", issues.clone(), as_issues),
+            ("Fixes", issues, as_issues),
+        ]
+    };
+}
+/// (worktree root, the commit the history ends before, how many commits) => their subjects
+type RecentSubjectsKey = (Option<PathBuf>, String, usize);
+lazy_static! {
+    static ref RECENT_SUBJECTS: Mutex<HashMap<RecentSubjectsKey, Vec<String>>> = Default::default();
+}
+
+/// words joining clauses that likely describe separate changes
+pub(crate) const DEFAULT_CONJUNCTIONS: &[&str] = &["and", "&", ","];
+pub(crate) const DEFAULT_SCOPE_DIRECTORY_DEPTH: usize = 1;
+#[cfg_attr(not(feature = "forge_api"), allow(dead_code))]
+pub(crate) const REFERENCE_EXISTS: &str = "reference_exists";
+pub(crate) const DEFAULT_LOCKFILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "poetry.lock",
+    "Pipfile.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+];
+/// see https://docs.github.com/en/issues/tracking-your-work-with-issues/linking-a-pull-request-to-an-issue
+pub(crate) const DEFAULT_CLOSING_KEYWORDS: &[&str] = &[
+    "Close", "Closes", "Closed", "Fix", "Fixes", "Fixed", "Resolve", "Resolves", "Resolved",
+];
 pub(crate) fn missing_body(doc: &GitCommitDocument, code: &str) -> Vec<lsp_types::Diagnostic> {
     base::document::linting::utils::query_lint(
         doc,
@@ -33,7 +105,7 @@ pub fn check_scope_present(doc: &GitCommitDocument, code: &str) -> Vec<lsp_types
     let mut lints = vec![];
     if let Some(subject) = &doc.subject {
         let scope_text = subject.scope_text();
-        if scope_text.len() <= 2 || scope_text[1..scope_text.len() - 2].trim().is_empty() {
+        if scope_text.len() <= 2 || scope_text[1..scope_text.len() - 1].trim().is_empty() {
             let type_end = subject.type_text().chars().count();
             let mut lint = base::document::linting::utils::make_line_diagnostic(
                 "Missing scope".into(),
@@ -47,3 +119,1200 @@ pub fn check_scope_present(doc: &GitCommitDocument, code: &str) -> Vec<lsp_types
     }
     lints
 }
+
+/// Require breaking changes to name the component they affect.
+pub(crate) fn check_breaking_scope(
+    doc: &GitCommitDocument,
+    code: &str,
+) -> Vec<lsp_types::Diagnostic> {
+    if !doc.is_breaking() {
+        return vec![];
+    }
+    let mut lints = check_scope_present(doc, code);
+    lints
+        .iter_mut()
+        .for_each(|lint| lint.message = "Breaking changes must have a scope".into());
+    lints
+}
+
+/// Advise on scopes that aren't in the project's glossary, suggesting the nearest glossary
+/// term by edit distance.
+pub fn check_scope_glossary(
+    doc: &GitCommitDocument,
+    code: &str,
+    glossary: &[String],
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    if let Some(subject) = &doc.subject {
+        let scope_text = subject.scope_text();
+        let scope = subject.scope_name().trim();
+        if scope.is_empty() || glossary.iter().any(|term| term == scope) {
+            return lints;
+        }
+        let start = (subject.type_text().chars().count()
+            + scope_text
+                .find(scope)
+                .map(|i| scope_text[..i].chars().count())
+                .unwrap_or(0)) as u32;
+        let end = start + scope.chars().count() as u32;
+        // only suggest terms that are plausibly misspellings of the scope
+        let max_distance = (scope.chars().count() / 3).max(1);
+        let nearest = glossary
+            .iter()
+            .map(|term| (utils::edit_distance(scope, term), term))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, term)| term);
+        let message = match nearest {
+            Some(term) => format!(
+                "Scope {:?} is not in the glossary; did you mean {:?}?",
+                scope, term
+            ),
+            None => format!("Scope {:?} is not in the glossary.", scope),
+        };
+        let mut lint = utils::make_line_diagnostic(message, subject.line_number.into(), start, end);
+        lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+        if let Some(term) = nearest {
+            let range = lint.range;
+            utils::set_fix(
+                &mut lint,
+                vec![lsp_types::TextEdit {
+                    range,
+                    new_text: term.to_owned(),
+                }],
+            );
+        }
+        lints.push(lint);
+    }
+    lints
+}
+
+#[test]
+fn test_scope_glossary_near_miss() {
+    let glossary = vec!["database".to_string(), "api".to_string()];
+    let doc = GitCommitDocument::new().with_text("feat(databse): add index".into());
+    let lints = check_scope_glossary(&doc, SCOPE_GLOSSARY, &glossary);
+    assert_eq!(lints.len(), 1);
+    assert!(lints[0].message.contains("did you mean \"database\""));
+    let fix = utils::get_fix(&lints[0]).unwrap();
+    assert_eq!(fix[0].new_text, "database");
+    assert_eq!(fix[0].range.start.character, 5);
+    assert_eq!(fix[0].range.end.character, 12);
+
+    let doc = GitCommitDocument::new().with_text("feat(api): add endpoint".into());
+    assert!(check_scope_glossary(&doc, SCOPE_GLOSSARY, &glossary).is_empty());
+    let doc = GitCommitDocument::new().with_text("feat[api]: add endpoint".into());
+    assert!(check_scope_glossary(&doc, SCOPE_GLOSSARY, &glossary).is_empty());
+}
+
+/// match `text` against a glob `pattern` where `*` matches any run of characters
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == text,
+        Some((prefix, rest)) => {
+            let Some(text) = text.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=text.len())
+                .filter(|i| text.is_char_boundary(*i))
+                .any(|i| glob_match(rest, &text[i..]))
+        }
+    }
+}
+
+/// Flag issue-closing keywords (e.g. `Fixes #123`) in the message, suggesting `Refs` instead.
+pub fn check_closing_keywords(
+    doc: &GitCommitDocument,
+    code: &str,
+    keywords: &[String],
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    for (line_number, line) in doc.get_body() {
+        let line = line.to_string();
+        let word = line
+            .split(|c: char| c == ':' || c.is_whitespace())
+            .next()
+            .unwrap_or_default();
+        if word.is_empty() || !keywords.iter().any(|k| k.eq_ignore_ascii_case(word)) {
+            continue;
+        }
+        let end = word.chars().count() as u32;
+        let mut lint = utils::make_line_diagnostic(
+            format!(
+                "{:?} would close the referenced issue; use \"Refs\" instead.",
+                word
+            ),
+            line_number,
+            0,
+            end,
+        );
+        lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+        let range = lint.range;
+        utils::set_fix(
+            &mut lint,
+            vec![lsp_types::TextEdit {
+                range,
+                new_text: "Refs".into(),
+            }],
+        );
+        lints.push(lint);
+    }
+    lints
+}
+
+/// Like `check_closing_keywords`, but only while writing a commit on a branch matching one of
+/// the `branches` patterns. Historical commits have no single branch context, so they're skipped.
+pub fn check_closing_keywords_on_branch(
+    doc: &GitCommitDocument,
+    code: &str,
+    branches: &[String],
+    keywords: &[String],
+) -> Vec<lsp_types::Diagnostic> {
+    if doc.revision.is_some() {
+        return vec![];
+    }
+    match base::git::current_branch(doc.worktree_root.clone()) {
+        Some(branch) if branches.iter().any(|pattern| glob_match(pattern, &branch)) => {
+            check_closing_keywords(doc, code, keywords)
+        }
+        _ => vec![],
+    }
+}
+
+#[test]
+fn test_closing_keywords() {
+    assert!(glob_match("release/*", "release/1.2"));
+    assert!(!glob_match("release/*", "main"));
+    assert!(glob_match("*-stable", "1.x-stable"));
+
+    let keywords: Vec<String> = DEFAULT_CLOSING_KEYWORDS
+        .iter()
+        .map(|k| k.to_string())
+        .collect();
+    let doc = GitCommitDocument::new()
+        .with_text("fix: x\n\nfixes the thing\n\nFixes #123\nRefs #456\n".into());
+    let lints = check_closing_keywords(&doc, CLOSING_KEYWORDS, &keywords);
+    // "fixes the thing" is prose, but still starts with a closing keyword as GitHub would see it
+    assert_eq!(lints.len(), 2);
+    assert_eq!(lints[1].range.start.line, 4);
+    assert_eq!(utils::get_fix(&lints[1]).unwrap()[0].new_text, "Refs");
+
+    let historical = GitCommitDocument::new()
+        .with_text("fix: x\n\nFixes #123\n".into())
+        .with_revision("abc123");
+    let branches = vec!["*".to_string()];
+    assert!(
+        check_closing_keywords_on_branch(&historical, CLOSING_KEYWORDS, &branches, &keywords)
+            .is_empty()
+    );
+}
+
+/// When every changed file matches one of the lockfile `patterns` (by path or file name), require
+/// the subject to use `type_` and, if given, `scope`.
+pub fn check_lockfile_only(
+    doc: &GitCommitDocument,
+    code: &str,
+    files: &[String],
+    patterns: &[String],
+    type_: &str,
+    scope: Option<&str>,
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let Some(subject) = &doc.subject else {
+        return lints;
+    };
+    let is_lockfile = |file: &String| {
+        let name = file.rsplit('/').next().unwrap_or(file);
+        patterns
+            .iter()
+            .any(|pattern| glob_match(pattern, file) || glob_match(pattern, name))
+    };
+    if files.is_empty() || !files.iter().all(is_lockfile) {
+        return lints;
+    }
+    let scope_text = subject.scope_text();
+    let actual_scope = subject.scope_name();
+    if subject.type_text() == type_ && scope.map(|s| s == actual_scope).unwrap_or(true) {
+        return lints;
+    }
+    let expected = match scope {
+        Some(scope) => format!("{type_}({scope})"),
+        None => type_.to_owned(),
+    };
+    let mut lint = utils::make_line_diagnostic(
+        format!("Commits changing only lockfiles should use {expected:?}"),
+        subject.line_number.into(),
+        0,
+        (subject.type_text().chars().count() + scope_text.chars().count()) as u32,
+    );
+    lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+    lints.push(lint);
+    lints
+}
+
+/// Require a body explaining changes of more than `max_lines` added or deleted lines.
+pub fn check_large_diff_body(
+    doc: &GitCommitDocument,
+    code: &str,
+    changed_lines: usize,
+    max_lines: usize,
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let Some(subject) = &doc.subject else {
+        return lints;
+    };
+    if changed_lines <= max_lines {
+        return lints;
+    }
+    let trailer_lines: Vec<u32> = doc
+        .get_trailer_keys()
+        .iter()
+        .map(|(range, _)| range.start.line)
+        .collect();
+    let has_body = doc.get_body().any(|(line_number, line)| {
+        !trailer_lines.contains(&(line_number as u32)) && !line.to_string().trim().is_empty()
+    });
+    if !has_body {
+        let mut lint = utils::make_line_diagnostic(
+            format!(
+                "This commit changes {changed_lines} lines (more than {max_lines}); add a body explaining it."
+            ),
+            subject.line_number.into(),
+            0,
+            subject.line.chars().count() as u32,
+        );
+        lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+        lints.push(lint);
+    }
+    lints
+}
+
+#[test]
+fn test_large_diff_body() {
+    let doc = GitCommitDocument::new()
+        .with_text("feat: rewrite everything\n\nSigned-off-by: A U Thor <a@example.com>\n".into());
+    assert_eq!(
+        check_large_diff_body(&doc, LARGE_DIFF_BODY, 500, 200).len(),
+        1
+    );
+    assert!(check_large_diff_body(&doc, LARGE_DIFF_BODY, 200, 200).is_empty());
+    let doc =
+        GitCommitDocument::new().with_text("feat: rewrite everything\n\nbecause reasons\n".into());
+    assert!(check_large_diff_body(&doc, LARGE_DIFF_BODY, 500, 200).is_empty());
+}
+
+#[test]
+fn test_lockfile_only() {
+    let patterns: Vec<String> = DEFAULT_LOCKFILES.iter().map(|p| p.to_string()).collect();
+    let lockfiles = vec![
+        "Cargo.lock".to_string(),
+        "web/package-lock.json".to_string(),
+    ];
+    let doc = GitCommitDocument::new().with_text("feat: bump serde".into());
+    let lints = check_lockfile_only(
+        &doc,
+        LOCKFILE_ONLY,
+        &lockfiles,
+        &patterns,
+        "chore",
+        Some("deps"),
+    );
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].range.end.character, 4);
+
+    let doc = GitCommitDocument::new().with_text("chore(deps): bump serde".into());
+    assert!(check_lockfile_only(
+        &doc,
+        LOCKFILE_ONLY,
+        &lockfiles,
+        &patterns,
+        "chore",
+        Some("deps")
+    )
+    .is_empty());
+
+    let mixed = vec!["Cargo.lock".to_string(), "src/lib.rs".to_string()];
+    let doc = GitCommitDocument::new().with_text("feat: bump serde".into());
+    assert!(check_lockfile_only(
+        &doc,
+        LOCKFILE_ONLY,
+        &mixed,
+        &patterns,
+        "chore",
+        Some("deps")
+    )
+    .is_empty());
+}
+
+/// the casing a subject's first word must have
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum WordCase {
+    Lower,
+    Upper,
+    /// an uppercase first letter followed by lowercase letters
+    Capitalized,
+}
+
+impl WordCase {
+    fn apply(&self, word: &str) -> String {
+        match self {
+            WordCase::Lower => word.to_lowercase(),
+            WordCase::Upper => word.to_uppercase(),
+            WordCase::Capitalized => {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| {
+                        first
+                            .to_uppercase()
+                            .chain(chars.flat_map(char::to_lowercase))
+                    })
+                    .into_iter()
+                    .flatten()
+                    .collect()
+            }
+        }
+    }
+}
+
+/// constraints on the first word of a subject's message, usually its verb
+#[derive(Debug, Default, Clone)]
+pub(crate) struct FirstWordConstraints {
+    pub max_length: Option<usize>,
+    pub min_length: Option<usize>,
+    pub case: Option<WordCase>,
+}
+
+/// Check the first word of the subject's message against `constraints`, returning at most one
+/// diagnostic per violated constraint.
+pub(crate) fn check_subject_first_word(
+    doc: &GitCommitDocument,
+    code: &str,
+    constraints: &FirstWordConstraints,
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let Some(subject) = &doc.subject else {
+        return lints;
+    };
+    let Some((start, word)) = utils::first_word(subject) else {
+        return lints;
+    };
+    let length = word.chars().count();
+    let end = start + length as u32;
+    let mut push = |message: String, fix: Option<String>| {
+        let mut lint = utils::make_line_diagnostic(message, subject.line_number.into(), start, end);
+        lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+        if let Some(new_text) = fix {
+            let range = lint.range;
+            utils::set_fix(&mut lint, vec![lsp_types::TextEdit { range, new_text }]);
+        }
+        lints.push(lint);
+    };
+    if let Some(max_length) = constraints.max_length {
+        if length > max_length {
+            push(
+                format!("{word:?} is longer than {max_length} characters."),
+                None,
+            );
+        }
+    }
+    if let Some(min_length) = constraints.min_length {
+        if length < min_length {
+            push(
+                format!("{word:?} is shorter than {min_length} characters."),
+                None,
+            );
+        }
+    }
+    if let Some(case) = constraints.case {
+        let expected = case.apply(word);
+        if expected != word {
+            push(format!("{word:?} should be {expected:?}."), Some(expected));
+        }
+    }
+    lints
+}
+
+#[test]
+fn test_subject_first_word_length() {
+    let doc = GitCommitDocument::new().with_text("feat: internationalize dates\n".into());
+    let too_long = FirstWordConstraints {
+        max_length: Some(10),
+        ..Default::default()
+    };
+    let lints = check_subject_first_word(&doc, SUBJECT_FIRST_WORD, &too_long);
+    assert_eq!(lints.len(), 1);
+    assert_eq!(
+        (lints[0].range.start.character, lints[0].range.end.character),
+        (6, 22)
+    );
+    let doc = GitCommitDocument::new().with_text("fix: a typo\n".into());
+    let too_short = FirstWordConstraints {
+        min_length: Some(2),
+        ..Default::default()
+    };
+    assert_eq!(
+        check_subject_first_word(&doc, SUBJECT_FIRST_WORD, &too_short).len(),
+        1
+    );
+    assert!(check_subject_first_word(&doc, SUBJECT_FIRST_WORD, &too_long).is_empty());
+}
+
+#[test]
+fn test_subject_first_word_case() {
+    let doc = GitCommitDocument::new().with_text("fix: handle Empty input\n".into());
+    let fix = |case| {
+        let constraints = FirstWordConstraints {
+            case: Some(case),
+            ..Default::default()
+        };
+        check_subject_first_word(&doc, SUBJECT_FIRST_WORD, &constraints)
+            .first()
+            .and_then(utils::get_fix)
+            .map(|edits| edits[0].new_text.clone())
+    };
+    assert_eq!(fix(WordCase::Lower), None);
+    assert_eq!(fix(WordCase::Upper), Some("HANDLE".into()));
+    assert_eq!(fix(WordCase::Capitalized), Some("Handle".into()));
+}
+
+/// the scope implied by `files`: the last of the `depth` leading directories they all share,
+/// e.g. `web` for `apps/web/*` at depth 2
+pub(crate) fn scope_from_directory(files: &[String], depth: usize) -> Option<String> {
+    let mut expected: Option<Vec<&str>> = None;
+    for file in files {
+        let mut dirs: Vec<&str> = file.split('/').collect();
+        dirs.pop(); // the file name
+        if depth == 0 || dirs.len() < depth {
+            return None;
+        }
+        dirs.truncate(depth);
+        match &expected {
+            Some(expected) if *expected != dirs => return None,
+            Some(_) => {}
+            None => expected = Some(dirs),
+        }
+    }
+    expected.and_then(|dirs| dirs.last().map(|dir| dir.to_string()))
+}
+
+/// Flag an explicit scope that differs from the directory containing all the changed `files`.
+pub(crate) fn check_scope_directory(
+    doc: &GitCommitDocument,
+    code: &str,
+    files: &[String],
+    depth: usize,
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let Some(subject) = &doc.subject else {
+        return lints;
+    };
+    if !subject.scope_is_closed() {
+        return lints; // no scope, or a malformed one
+    }
+    let actual = subject.scope_name();
+    let Some(expected) = scope_from_directory(files, depth) else {
+        return lints;
+    };
+    if actual.trim() == expected {
+        return lints;
+    }
+    let start = subject.type_text().chars().count() as u32 + 1;
+    let mut lint = utils::make_line_diagnostic(
+        format!("All changed files are in {expected:?}; expected scope {expected:?}."),
+        subject.line_number.into(),
+        start,
+        start + actual.chars().count() as u32,
+    );
+    lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+    let range = lint.range;
+    utils::set_fix(
+        &mut lint,
+        vec![lsp_types::TextEdit {
+            range,
+            new_text: expected,
+        }],
+    );
+    lints.push(lint);
+    lints
+}
+
+#[test]
+fn test_scope_directory() {
+    let files = vec![
+        "apps/web/index.ts".to_owned(),
+        "apps/web/src/app.ts".to_owned(),
+    ];
+    assert_eq!(scope_from_directory(&files, 1), Some("apps".into()));
+    assert_eq!(scope_from_directory(&files, 2), Some("web".into()));
+    assert_eq!(scope_from_directory(&files, 3), None);
+    let mixed = vec![
+        "apps/web/index.ts".to_owned(),
+        "apps/api/main.rs".to_owned(),
+    ];
+    assert_eq!(scope_from_directory(&mixed, 2), None);
+
+    let doc = GitCommitDocument::new().with_text("feat(api): add dark mode\n".into());
+    let lints = check_scope_directory(&doc, SCOPE_DIRECTORY, &files, 2);
+    assert_eq!(lints.len(), 1);
+    let fix = utils::get_fix(&lints[0]).unwrap();
+    assert_eq!(
+        (fix[0].range.start.character, fix[0].range.end.character),
+        (5, 8)
+    );
+    assert_eq!(fix[0].new_text, "web");
+    let bracketed = GitCommitDocument::new().with_text("feat[api]: add dark mode\n".into());
+    assert_eq!(
+        check_scope_directory(&bracketed, SCOPE_DIRECTORY, &files, 2).len(),
+        1
+    );
+    assert!(check_scope_directory(&doc, SCOPE_DIRECTORY, &mixed, 2).is_empty());
+    let unscoped = GitCommitDocument::new().with_text("feat: add dark mode\n".into());
+    assert!(check_scope_directory(&unscoped, SCOPE_DIRECTORY, &files, 2).is_empty());
+}
+
+/// Flag conjunctions like `and` in the subject's message, which hint that a commit makes more than
+/// one change. Text within `"` or `` ` `` quotes is ignored; a `,` only counts before whitespace.
+pub(crate) fn check_subject_conjunction(
+    doc: &GitCommitDocument,
+    code: &str,
+    words: &[String],
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let Some(subject) = &doc.subject else {
+        return lints;
+    };
+    let offset = subject.prefix_text().chars().count();
+    // blank out quoted text, keeping char offsets intact
+    let mut quote = None;
+    let masked: String = subject
+        .message_text()
+        .chars()
+        .map(|c| match (quote, c) {
+            (None, '"' | '`') => {
+                quote = Some(c);
+                ' '
+            }
+            (Some(q), _) if q == c => {
+                quote = None;
+                ' '
+            }
+            (Some(_), _) => ' ',
+            (None, _) => c,
+        })
+        .collect();
+    let mut tokens: Vec<(usize, String)> = vec![];
+    for (i, c) in masked.chars().enumerate() {
+        match tokens.last_mut() {
+            _ if c.is_whitespace() => {}
+            Some((start, token)) if *start + token.chars().count() == i => token.push(c),
+            _ => tokens.push((i, c.to_string())),
+        }
+    }
+    let mut flag = |start: usize, word: &str| {
+        let start = (offset + start) as u32;
+        let mut lint = utils::make_line_diagnostic(
+            format!("{word:?} suggests more than one change; consider splitting this commit."),
+            subject.line_number.into(),
+            start,
+            start + word.chars().count() as u32,
+        );
+        lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+        lints.push(lint);
+    };
+    for (start, token) in tokens {
+        let bare = token.trim_end_matches(',');
+        if words.iter().any(|word| word.eq_ignore_ascii_case(bare)) {
+            flag(start, bare);
+        }
+        if bare.len() < token.len() && words.iter().any(|word| word == ",") {
+            flag(start + bare.chars().count(), ",");
+        }
+    }
+    lints
+}
+
+#[test]
+fn test_subject_conjunction() {
+    let words: Vec<String> = DEFAULT_CONJUNCTIONS.iter().map(|w| w.to_string()).collect();
+    let flagged = |text: &str| -> Vec<u32> {
+        let doc = GitCommitDocument::new().with_text(format!("{text}\n"));
+        check_subject_conjunction(&doc, SUBJECT_CONJUNCTION, &words)
+            .iter()
+            .map(|lint| lint.range.start.character)
+            .collect()
+    };
+    assert_eq!(flagged("feat: add login and fix logout"), vec![16]);
+    assert_eq!(
+        flagged("feat(ui): add login, fix logout & tidy"),
+        vec![19, 32]
+    );
+    assert!(flagged("fix: escape \"and\" in search queries").is_empty());
+    assert!(flagged("feat: support 1,000 rows").is_empty());
+    assert!(flagged("feat: add sign-in").is_empty());
+}
+
+/// Flag a type and scope that look swapped, e.g. `api(feat): x`: the type is unknown but the scope
+/// is a known type. If any scopes are configured, the type must also be a known scope.
+pub(crate) fn check_type_scope_swap(
+    doc: &GitCommitDocument,
+    code: &str,
+    types: &[String],
+    scopes: &[String],
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let Some(subject) = &doc.subject else {
+        return lints;
+    };
+    let type_text = subject.type_text();
+    let scope_text = subject.scope_text();
+    if !subject.scope_is_closed() {
+        return lints;
+    }
+    let scope = subject.scope_name();
+    let is_type = |text: &str| types.iter().any(|t| t == text);
+    let is_scope = |text: &str| scopes.is_empty() || scopes.iter().any(|s| s == text);
+    if is_type(type_text) || !is_type(scope) || !is_scope(type_text) {
+        return lints;
+    }
+    let mut lint = utils::make_line_diagnostic(
+        format!(
+            "{type_text:?} is a scope and {scope:?} is a type; did you mean {scope}({type_text})?"
+        ),
+        subject.line_number.into(),
+        0,
+        (type_text.chars().count() + scope_text.chars().count()) as u32,
+    );
+    lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+    let range = lint.range;
+    utils::set_fix(
+        &mut lint,
+        vec![lsp_types::TextEdit {
+            range,
+            new_text: format!("{scope}({type_text})"),
+        }],
+    );
+    lints.push(lint);
+    lints
+}
+
+#[test]
+fn test_type_scope_swap() {
+    let types: Vec<String> = ["feat", "fix"].iter().map(|t| t.to_string()).collect();
+    let scopes: Vec<String> = ["api", "ui"].iter().map(|s| s.to_string()).collect();
+    let check = |text: &str, scopes: &[String]| {
+        let doc = GitCommitDocument::new().with_text(format!("{text}\n"));
+        check_type_scope_swap(&doc, TYPE_SCOPE_SWAP, &types, scopes)
+    };
+    let lints = check("api(feat): x", &scopes);
+    assert_eq!(lints.len(), 1);
+    let fix = utils::get_fix(&lints[0]).unwrap();
+    assert_eq!(fix[0].new_text, "feat(api)");
+    assert_eq!(fix[0].range.end.character, 9);
+    assert!(check("feat(api): x", &scopes).is_empty());
+    assert_eq!(check("api[feat]: x", &scopes).len(), 1);
+    assert!(check("db(feat): x", &scopes).is_empty()); // not a known scope
+    assert_eq!(check("db(feat): x", &[]).len(), 1);
+    assert!(check("api(core): x", &scopes).is_empty());
+}
+
+/// (trailer key, the pattern its value must match, a description of the expected form)
+pub(crate) type TrailerPattern = (String, regex::Regex, String);
+
+/// Check each trailer's value against the pattern for its key, compared case-insensitively.
+/// Trailers with other keys or empty values are skipped; base's trailer value check reports
+/// empty values.
+pub(crate) fn check_trailer_values(
+    doc: &GitCommitDocument,
+    code: &str,
+    patterns: &[TrailerPattern],
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    for (range, key) in doc.get_trailer_keys() {
+        let Some((_, pattern, expected)) =
+            patterns.iter().find(|(k, ..)| k.eq_ignore_ascii_case(&key))
+        else {
+            continue;
+        };
+        let line = doc.code.line(range.start.line as usize).to_string();
+        let rest = line[key.len()..].trim_start_matches(|c: char| c == ':' || c.is_whitespace());
+        let value = rest.trim_end();
+        // empty values are already reported by base's trailer value check
+        if value.is_empty() || pattern.is_match(value) {
+            continue;
+        }
+        let start = line[..line.len() - rest.len()].chars().count();
+        let mut lint = utils::make_line_diagnostic(
+            format!("{key} should be followed by {expected}"),
+            range.start.line as usize,
+            start as u32,
+            (start + value.chars().count()) as u32,
+        );
+        lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+        lints.push(lint);
+    }
+    lints
+}
+
+/// Report each required trailer key, compared case-insensitively, that the message lacks.
+/// Like a missing required query capture, each diagnostic sits at the start of the message.
+pub(crate) fn check_trailer_exists(
+    doc: &GitCommitDocument,
+    code: &str,
+    required: &[String],
+) -> Vec<lsp_types::Diagnostic> {
+    let trailers = doc.get_trailers();
+    required
+        .iter()
+        .filter(|key| !trailers.iter().any(|(k, _)| k.eq_ignore_ascii_case(key)))
+        .map(|key| {
+            let mut lint =
+                utils::make_line_diagnostic(format!("missing `{key}:` trailer"), 0, 0, 0);
+            lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+            lint
+        })
+        .collect()
+}
+
+/// Check the body or trailers mention at least one issue reference matching `pattern`. The
+/// diagnostic sits at the start of the message, since there's nowhere better to put it.
+pub(crate) fn check_references_empty(
+    doc: &GitCommitDocument,
+    code: &str,
+    pattern: &regex::Regex,
+) -> Vec<lsp_types::Diagnostic> {
+    if doc
+        .get_body()
+        .any(|(_, line)| pattern.is_match(&line.to_string()))
+    {
+        return vec![];
+    }
+    let mut lint = utils::make_line_diagnostic(
+        format!("missing an issue reference matching `{pattern}`"),
+        0,
+        0,
+        0,
+    );
+    lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+    vec![lint]
+}
+
+/// Flag an author `email` outside the allowed `domains`, compared case-insensitively. The
+/// diagnostic sits at the start of the message, since the email isn't part of its text.
+pub(crate) fn check_author_domain(
+    code: &str,
+    email: Option<&str>,
+    domains: &[String],
+) -> Vec<lsp_types::Diagnostic> {
+    let Some(email) = email else {
+        return vec![]; // nothing to check without a configured email
+    };
+    let domain = email.rsplit_once('@').map(|(_, d)| d).unwrap_or_default();
+    if domains.iter().any(|d| d.eq_ignore_ascii_case(domain)) {
+        return vec![];
+    }
+    let allowed = domains
+        .iter()
+        .map(|d| format!("`@{d}`"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut lint = utils::make_line_diagnostic(
+        format!("author email `{email}` isn't from an allowed domain: {allowed}"),
+        0,
+        0,
+        0,
+    );
+    lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+    vec![lint]
+}
+
+#[test]
+fn test_author_domain() {
+    let domains = vec!["example.com".to_owned()];
+    let check = |email: Option<&str>| check_author_domain(AUTHOR_DOMAIN, email, &domains);
+    assert!(check(Some("a@example.com")).is_empty());
+    assert!(check(Some("a@EXAMPLE.com")).is_empty());
+    assert!(check(None).is_empty());
+    let lints = check(Some("a@gmail.com"));
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].range.start, lsp_types::Position::new(0, 0));
+    assert_eq!(check(Some("a@sub.example.com")).len(), 1);
+}
+
+/// the subjects of the `count` commits before `doc`'s, cached until HEAD moves
+pub(crate) fn recent_subjects(doc: &GitCommitDocument, count: usize) -> Vec<String> {
+    let root = doc.worktree_root.clone();
+    let revision = doc
+        .revision
+        .clone()
+        .or_else(|| base::git::head(root.clone()))
+        .unwrap_or_default();
+    let key = (root.clone(), revision, count);
+    let mut cache = RECENT_SUBJECTS.lock().unwrap_or_else(|e| e.into_inner());
+    cache
+        .entry(key)
+        .or_insert_with(|| base::git::recent_subjects(doc.revision.as_deref(), count, root))
+        .clone()
+}
+
+/// Flag a subject identical to one of the `recent` subjects, which is likely unintended.
+pub(crate) fn check_subject_duplicate_recent(
+    doc: &GitCommitDocument,
+    code: &str,
+    recent: &[String],
+) -> Vec<lsp_types::Diagnostic> {
+    let Some(subject) = &doc.subject else {
+        return vec![];
+    };
+    let line = subject.line.trim();
+    let Some(ago) = recent.iter().position(|s| s == line) else {
+        return vec![];
+    };
+    let mut lint = utils::make_line_diagnostic(
+        format!(
+            "the same subject was used {} commit{} ago",
+            ago + 1,
+            if ago == 0 { "" } else { "s" }
+        ),
+        subject.line_number.into(),
+        0,
+        line.chars().count() as u32,
+    );
+    lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+    vec![lint]
+}
+
+#[test]
+fn test_subject_duplicate_recent() {
+    let dir = base::git::TempRepo::new("recent");
+    for subject in ["fix: typo", "feat: add x", "docs: y"] {
+        dir.commit(&["--allow-empty", "--message", subject]);
+    }
+    let check = |text: &str, count: usize| -> Vec<String> {
+        let mut doc = GitCommitDocument::new().with_text(text.into());
+        doc.worktree_root = Some(dir.to_path_buf());
+        let recent = recent_subjects(&doc, count);
+        check_subject_duplicate_recent(&doc, SUBJECT_DUPLICATE_RECENT, &recent)
+            .into_iter()
+            .map(|lint| lint.message)
+            .collect()
+    };
+    let duplicate = check("fix: typo\n", 3);
+    let outside_window = check("fix: typo\n", 2);
+    let latest = check("docs: y\n", 2);
+    let fresh = check("fix: other typo\n", 3);
+    let head = base::git::head(Some(dir.to_path_buf())).unwrap();
+    let mut doc = GitCommitDocument::new().with_text("docs: y\n".into());
+    doc.worktree_root = Some(dir.to_path_buf());
+    let doc = doc.with_revision(&head);
+    let own_commit =
+        base::git::recent_subjects(doc.revision.as_deref(), 3, Some(dir.to_path_buf()));
+
+    assert_eq!(duplicate, vec!["the same subject was used 3 commits ago"]);
+    assert!(outside_window.is_empty());
+    assert_eq!(latest, vec!["the same subject was used 1 commit ago"]);
+    assert!(fresh.is_empty());
+    // a commit's own subject isn't part of its recent history
+    assert_eq!(own_commit, vec!["feat: add x", "fix: typo"]);
+}
+
+#[test]
+fn test_trailer_exists() {
+    let required = vec!["Signed-off-by".to_owned(), "Reviewed-by".to_owned()];
+    let missing = |text: &str| -> Vec<String> {
+        let doc = GitCommitDocument::new().with_text(text.into());
+        check_trailer_exists(&doc, TRAILER_EXISTS, &required)
+            .into_iter()
+            .map(|lint| lint.message)
+            .collect()
+    };
+    assert_eq!(
+        missing(
+            "feat: x
+
+body
+
+signed-off-by: A U Thor <a@example.com>
+"
+        ),
+        vec!["missing `Reviewed-by:` trailer"]
+    );
+    assert_eq!(
+        missing(
+            "feat: x
+"
+        )
+        .len(),
+        2
+    );
+    assert!(missing(
+        "feat: x
+
+Signed-off-by: A <a@example.com>
+Reviewed-by: B <b@example.com>
+"
+    )
+    .is_empty());
+}
+
+#[test]
+fn test_trailer_values() {
+    let patterns: Vec<TrailerPattern> = DEFAULT_TRAILER_PATTERNS
+        .iter()
+        .map(|(key, pattern, expected)| {
+            let regex = regex::Regex::new(pattern).unwrap();
+            (key.to_string(), regex, expected.to_string())
+        })
+        .collect();
+    let flagged = |trailers: &str| -> Vec<u32> {
+        let doc = GitCommitDocument::new().with_text(format!("feat: x\n\nbody\n\n{trailers}"));
+        check_trailer_values(&doc, TRAILER_VALUES, &patterns)
+            .iter()
+            .map(|lint| lint.range.start.line)
+            .collect()
+    };
+    assert!(flagged(
+        "Signed-off-by: A U Thor <a@example.com>\nCo-authored-by: B <b@example.com>\nRefs: #1, org/repo#2 ABC-3\nCloses: https://example.com/issues/4\nReviewed-by: anyone\n"
+    )
+    .is_empty());
+    assert_eq!(
+        flagged("Signed-off-by: a@example.com\nsigned-off-by: A U Thor\nRefs: soon\n"),
+        vec![4, 5, 6]
+    );
+}
+
+/// Advise on bodies that merely repeat the subject, or paragraphs starting in the first person.
+/// Trailers aren't part of the prose, so they're skipped.
+pub(crate) fn check_body_style(
+    doc: &GitCommitDocument,
+    code: &str,
+    first_person: &[String],
+) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let trailer_lines: Vec<u32> = doc
+        .get_trailer_keys()
+        .iter()
+        .map(|(range, _)| range.start.line)
+        .collect();
+    let prose: Vec<(usize, String)> = doc
+        .get_body()
+        .filter(|(n, _)| !trailer_lines.contains(&(*n as u32)))
+        .map(|(n, line)| (n, line.to_string()))
+        .collect();
+    let mut lint = |message: String, line_number: usize, start: usize, end: usize| {
+        let mut lint = utils::make_line_diagnostic(message, line_number, start as u32, end as u32);
+        lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+        lints.push(lint);
+    };
+    let mut previous_blank = true;
+    for (line_number, line) in &prose {
+        let is_blank = line.trim().is_empty();
+        if previous_blank && !is_blank {
+            let first_word = line.split_whitespace().next().unwrap_or_default();
+            if first_person.iter().any(|w| w == first_word) {
+                let start = line.chars().take_while(|c| c.is_whitespace()).count();
+                lint(
+                    "Describe what the change does rather than what you did, e.g. \"Fix\" \
+                    rather than \"I fixed\"."
+                        .into(),
+                    *line_number,
+                    start,
+                    start + first_word.chars().count(),
+                );
+            }
+        }
+        previous_blank = is_blank;
+    }
+    let mut content = prose.iter().filter(|(_, line)| !line.trim().is_empty());
+    if let (Some(subject), Some((line_number, line)), None) =
+        (&doc.subject, content.next(), content.next())
+    {
+        let normalize = |s: &str| s.trim().trim_end_matches('.').to_lowercase();
+        if normalize(line) == normalize(subject.message_text()) {
+            lint(
+                "The body only repeats the subject; explain why the change is needed instead."
+                    .into(),
+                *line_number,
+                0,
+                line.chars().count(),
+            );
+        }
+    }
+    lints
+}
+
+#[test]
+fn test_body_style() {
+    let words: Vec<String> = DEFAULT_FIRST_PERSON_WORDS
+        .iter()
+        .map(|w| w.to_string())
+        .collect();
+    let check = |text: &str| {
+        let doc = GitCommitDocument::new().with_text(text.into());
+        check_body_style(&doc, BODY_STYLE, &words)
+            .into_iter()
+            .map(|lint| (lint.range.start.line, lint.range.start.character))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        check("fix: handle empty input\n\nI noticed a crash.\n\nI'm fixing it here.\n"),
+        vec![(2, 0), (4, 0)]
+    );
+    assert_eq!(
+        check("fix: handle empty input\n\nHandle empty input.\n\nSigned-off-by: A <a@b.c>\n"),
+        vec![(2, 0)]
+    );
+    assert!(check(
+        "fix: handle empty input\n\nEmpty input crashed the parser, since\nI/O errors weren't handled.\n\nSigned-off-by: A <a@b.c>\n"
+    )
+    .is_empty());
+}
+
+/// Flag git's default `Revert "<subject>"` subject, offering to rewrite it as
+/// `revert: <subject>` with git's `This reverts commit <sha>.` line moved to a `Refs:` footer.
+pub(crate) fn check_revert_type(doc: &GitCommitDocument, code: &str) -> Vec<lsp_types::Diagnostic> {
+    let mut lints = vec![];
+    let Some(subject) = &doc.subject else {
+        return lints;
+    };
+    let line = subject.line.trim_end();
+    let Some(reverted) = line
+        .strip_prefix("Revert \"")
+        .and_then(|rest| rest.strip_suffix('"'))
+    else {
+        return lints;
+    };
+    let line_number = subject.line_number as usize;
+    let mut lint = utils::make_line_diagnostic(
+        "Use the `revert` type rather than git's default `Revert \"...\"` subject.".into(),
+        line_number,
+        0,
+        line.chars().count() as u32,
+    );
+    lint.code = Some(lsp_types::NumberOrString::String(code.into()));
+    let mut edits = vec![lsp_types::TextEdit {
+        range: lint.range,
+        new_text: format!("revert: {reverted}"),
+    }];
+    let content: Vec<(usize, String)> = doc
+        .get_body()
+        .map(|(n, line)| (n, line.to_string()))
+        .filter(|(_, line)| !line.trim().is_empty())
+        .collect();
+    let reverts = content.iter().find_map(|(n, line)| {
+        let sha = line
+            .trim()
+            .strip_prefix("This reverts commit ")?
+            .strip_suffix('.')?;
+        Some((*n, line.chars().count(), sha.to_owned()))
+    });
+    if let (Some((n, n_chars, sha)), Some((last, last_line))) = (reverts, content.last()) {
+        let position = |line: usize, character: usize| lsp_types::Position {
+            line: line as u32,
+            character: character as u32,
+        };
+        if n == *last {
+            edits.push(lsp_types::TextEdit {
+                range: lsp_types::Range::new(position(n, 0), position(n, n_chars)),
+                new_text: format!("Refs: {sha}"),
+            });
+        } else {
+            let end = position(*last, last_line.chars().count());
+            edits.push(lsp_types::TextEdit {
+                range: lsp_types::Range::new(position(n, 0), position(n + 1, 0)),
+                new_text: String::new(),
+            });
+            edits.push(lsp_types::TextEdit {
+                range: lsp_types::Range::new(end, end),
+                new_text: format!("\n\nRefs: {sha}"),
+            });
+        }
+    }
+    utils::set_fix(&mut lint, edits);
+    lints.push(lint);
+    lints
+}
+
+#[test]
+fn test_revert_type() {
+    let fix = |text: &str| -> Option<Vec<String>> {
+        let doc = GitCommitDocument::new().with_text(text.into());
+        let lints = check_revert_type(&doc, REVERT_TYPE);
+        assert!(lints.len() <= 1);
+        let edits = utils::get_fix(lints.first()?)?;
+        Some(
+            edits
+                .into_iter()
+                .map(|e| {
+                    let (start, end) = (e.range.start, e.range.end);
+                    let (from, to) = (start.line, end.line);
+                    format!(
+                        "{from}:{}-{to}:{} {:?}",
+                        start.character, end.character, e.new_text
+                    )
+                })
+                .collect(),
+        )
+    };
+    assert_eq!(
+        fix("Revert \"feat: add x\"\n\nThis reverts commit 0dbb32d.\n# comment\n"),
+        Some(vec![
+            r#"0:0-0:20 "revert: feat: add x""#.to_owned(),
+            r#"2:0-2:28 "Refs: 0dbb32d""#.to_owned(),
+        ])
+    );
+    assert_eq!(
+        fix("Revert \"feat: add x\"\n\nThis reverts commit 0dbb32d.\n\nIt broke the build.\n"),
+        Some(vec![
+            r#"0:0-0:20 "revert: feat: add x""#.to_owned(),
+            r#"2:0-3:0 """#.to_owned(),
+            r#"4:19-4:19 "\n\nRefs: 0dbb32d""#.to_owned(),
+        ])
+    );
+    assert_eq!(fix("revert: feat: add x\n\nRefs: 0dbb32d\n"), None);
+}
+
+#[test]
+fn test_breaking_scope() {
+    let check = |text: &str| {
+        let doc = GitCommitDocument::new().with_text(text.into());
+        check_breaking_scope(&doc, BREAKING_SCOPE).len()
+    };
+    assert_eq!(check("feat!: x"), 1);
+    assert_eq!(check("feat: x\n\nBREAKING CHANGE: y\n"), 1);
+    assert_eq!(check("feat(api)!: x"), 0);
+    assert_eq!(check("feat: x"), 0);
+}
+
+#[test]
+fn test_references_empty() {
+    let check = |text: &str, pattern: &str| {
+        let doc = GitCommitDocument::new().with_text(text.into());
+        let pattern = regex::Regex::new(pattern).unwrap();
+        check_references_empty(&doc, REFERENCES_EMPTY, &pattern)
+            .into_iter()
+            .map(|lint| (lint.range.start.line, lint.message))
+            .collect::<Vec<_>>()
+    };
+    let missing = vec![(0, "missing an issue reference matching `#\\d+`".to_owned())];
+    assert_eq!(
+        check("feat: x\n\nbody\n", DEFAULT_REFERENCE_PATTERN),
+        missing
+    );
+    assert_eq!(
+        check("fix: #12 in the subject\n", DEFAULT_REFERENCE_PATTERN),
+        missing
+    );
+    assert!(check("fix: x\n\nsee #12\n", DEFAULT_REFERENCE_PATTERN).is_empty());
+    assert!(check("fix: x\n\nbody\n\nRefs: #12\n", DEFAULT_REFERENCE_PATTERN).is_empty());
+    // commented-out references don't count
+    assert_eq!(
+        check("fix: x\n# see #12\n", DEFAULT_REFERENCE_PATTERN),
+        missing
+    );
+
+    let jira = r"[A-Z]+-\d+";
+    assert!(check("fix: x\n\nFixes: PROJ-42\n", jira).is_empty());
+    assert_eq!(check("fix: x\n\nsee #12\n", jira).len(), 1);
+}
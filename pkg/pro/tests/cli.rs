@@ -0,0 +1,57 @@
+// © Steven Kalt
+// SPDX-License-Identifier: Polyform-Noncommercial-1.0.0 OR LicenseRef-PolyForm-Free-Trial-1.0.0
+
+//! invoke the built `pro_language_server` binary
+#![cfg(feature = "cli")]
+use std::{io::Write, path::Path, process::Command};
+
+use base::git::TempRepo;
+
+const BIN: &str = env!("CARGO_BIN_EXE_pro_language_server");
+
+/// run `check --stdin --require-config` in `dir`, returning whether it passed and its stderr
+fn check_requiring_config(dir: &Path) -> (bool, String) {
+    let mut child = Command::new(BIN)
+        .current_dir(dir)
+        .args(["check", "--stdin", "--require-config"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    // without a usable config, the check may fail before reading stdin
+    let _ = child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"feat: add a thing\n");
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.success(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn test_require_config() {
+    let dir = TempRepo::new("cli-require-config");
+    let (passed, stderr) = check_requiring_config(&dir);
+    assert!(!passed);
+    assert!(
+        stderr.contains("no commit convention config found"),
+        "{stderr}"
+    );
+
+    dir.write(".config/commit_convention.json", "{}");
+    let (passed, stderr) = check_requiring_config(&dir);
+    assert!(passed, "{stderr}");
+
+    // without --require-config, a malformed config only warns and falls back to the defaults
+    dir.write(".config/commit_convention.json", "{\"types\": ");
+    let (passed, stderr) = check_requiring_config(&dir);
+    assert!(!passed);
+    assert!(
+        stderr.contains("unable to load the commit convention config"),
+        "{stderr}"
+    );
+}